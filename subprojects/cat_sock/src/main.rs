@@ -1,12 +1,63 @@
 use std::{
-    io::{stdout, BufWriter},
+    io::{stdout, BufWriter, Write},
+    net::Shutdown,
     os::unix::net::UnixStream,
     path::Path,
 };
 
+/// The version of rustbar's socket protocol this speaks
+const VERSION: u32 = 1;
+
+/// Quotes `s` as a JSON string
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let request = match args.first().map(String::as_str) {
+        None => None,
+        Some(request @ ("get" | "subscribe")) if args.len() == 1 => Some(format!(
+            r#"{{"version":{},"request":"{}"}}"#,
+            VERSION, request
+        )),
+        // `cat_sock send progress 42 󰕾`
+        Some("send") if args.len() > 1 => Some(format!(
+            r#"{{"version":{},"request":"command","command":{}}}"#,
+            VERSION,
+            json_string(&args[1..].join(" "))
+        )),
+        _ => {
+            eprintln!("usage: cat_sock [get|subscribe|send <command>...]");
+            std::process::exit(1);
+        }
+    };
+
     let socket_path = Path::new(&std::env::var_os("XDG_RUNTIME_DIR").unwrap()).join("rustbar-0");
     if let Ok(mut socket) = UnixStream::connect(&socket_path) {
-        std::io::copy(&mut socket, &mut BufWriter::new(stdout().lock())).unwrap();
+        match request {
+            // Sending nothing gets the table
+            None => socket.shutdown(Shutdown::Write).unwrap(),
+            Some(ref request) => writeln!(socket, "{}", request).unwrap(),
+        }
+
+        if args.first().map(String::as_str) == Some("subscribe") {
+            // stdout is line buffered, so every event shows up as soon
+            // as it arrives
+            std::io::copy(&mut socket, &mut stdout().lock()).unwrap();
+        } else {
+            std::io::copy(&mut socket, &mut BufWriter::new(stdout().lock())).unwrap();
+        }
     }
 }
@@ -145,6 +145,10 @@ static const Key keys[] = {
 	{ 1, 0,                    XKB_KEY_space,      wob,            {.w = ZNET_TAPESOFTWARE_DWL_WM_V1_WOB_COMMAND_LAUNCH_APP} },
 	{ 1, 0,                    XKB_KEY_p,          wob,            {.w = ZNET_TAPESOFTWARE_DWL_WM_V1_WOB_COMMAND_POWER_BUTTON} },
 	{ 1, 0,                    XKB_KEY_b,          wob,            {.w = ZNET_TAPESOFTWARE_DWL_WM_V1_WOB_COMMAND_OVERLAY} },
+	{ 1, 0,                    XKB_KEY_o,          wob,            {.w = ZNET_TAPESOFTWARE_DWL_WM_V1_WOB_COMMAND_WINDOW_SWITCHER} },
+	{ 1, 0,                    XKB_KEY_equal,      wob,            {.w = ZNET_TAPESOFTWARE_DWL_WM_V1_WOB_COMMAND_CALCULATOR} },
+	{ 1, 0,                    XKB_KEY_semicolon,  wob,            {.w = ZNET_TAPESOFTWARE_DWL_WM_V1_WOB_COMMAND_GLYPH_PICKER} },
+	{ 1, WLR_MODIFIER_SHIFT,   XKB_KEY_V,          wob,            {.w = ZNET_TAPESOFTWARE_DWL_WM_V1_WOB_COMMAND_CLIPBOARD_HISTORY} },
 	{ 1, 0,                    XKB_KEY_Return,     spawn,          {.v = termcmd} },
 	{ 1, WLR_MODIFIER_SHIFT,   XKB_KEY_N,          spawn,          {.v = thunarcmd} },
 	{ 1, 0,                    XKB_KEY_s,          spawn,          {.v = grimcmd}},
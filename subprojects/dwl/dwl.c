@@ -158,6 +158,7 @@ typedef struct {
 	uint32_t tags;
 	int isfloating, isurgent, isfullscreen;
 	uint32_t resize; /* configure serial of a pending resize */
+	uint32_t id; /* for dwl_wm_v1.focus_client */
 } Client;
 
 typedef struct {
@@ -420,6 +421,7 @@ static struct wlr_allocator *alloc;
 static struct wlr_compositor *compositor;
 static struct wlr_session *session;
 static struct wl_resource *dwl_resource;
+static uint32_t lastclientid;
 
 static struct wlr_xdg_shell *xdg_shell;
 static struct wlr_xdg_activation_v1 *activation;
@@ -1112,6 +1114,7 @@ createnotify(struct wl_listener *listener, void *data)
 
 	/* Allocate a Client for this surface */
 	c = toplevel->base->data = ecalloc(1, sizeof(*c));
+	c->id = ++lastclientid;
 	c->surface.xdg = toplevel->base;
 	c->bw = borderpx;
 
@@ -3464,7 +3467,7 @@ setup(void)
 	output_mgr = wlr_output_manager_v1_create(dpy);
 	LISTEN_STATIC(&output_mgr->events.apply, outputmgrapply);
 	LISTEN_STATIC(&output_mgr->events.test, outputmgrtest);
-	wl_global_create(dpy, &znet_tapesoftware_dwl_wm_v1_interface, 1, NULL, dwl_wm_bind);
+	wl_global_create(dpy, &znet_tapesoftware_dwl_wm_v1_interface, 2, NULL, dwl_wm_bind);
 
 	/* Make sure XWayland clients don't connect to the parent X server,
 	 * e.g when running in the x11 backend or the wayland backend and the
@@ -3944,6 +3947,7 @@ createnotifyx11(struct wl_listener *listener, void *data)
 
 	/* Allocate a Client for this surface */
 	c = xsurface->data = ecalloc(1, sizeof(*c));
+	c->id = ++lastclientid;
 	c->surface.xwayland = xsurface;
 	c->type = X11;
 	c->bw = borderpx;
@@ -4218,6 +4222,42 @@ dwl_wm_handle_get_monitor(struct wl_client *client, struct wl_resource *resource
 	dwl_wm_printstatus_to(m, dwl_wm_monitor);
 }
 
+static void
+dwl_wm_handle_list_clients(struct wl_client *client, struct wl_resource *resource)
+{
+	Client *c, *focused = focustop(selmon);
+	const char *title, *appid;
+	/* most recently focused first */
+	wl_list_for_each(c, &fstack, flink) {
+		if (!c->mon)
+			continue;
+		if (!(title = client_get_title(c)))
+			title = broken;
+		if (!(appid = client_get_appid(c)))
+			appid = broken;
+		znet_tapesoftware_dwl_wm_v1_send_client(resource, c->id, title, appid,
+			c->tags, c->mon->wlr_output->name, c == focused);
+	}
+	znet_tapesoftware_dwl_wm_v1_send_clients_done(resource);
+}
+
+static void
+dwl_wm_handle_focus_client(struct wl_client *client, struct wl_resource *resource,
+	uint32_t id)
+{
+	Client *c;
+	wl_list_for_each(c, &clients, link) {
+		if (c->id != id || !c->mon)
+			continue;
+		selmon = c->mon;
+		if (!VISIBLEON(c, selmon))
+			view(&(Arg){.ui = c->tags});
+		focusclient(c, 1);
+		printstatus();
+		return;
+	}
+}
+
 static void
 dwl_wm_handle_destroy(struct wl_resource *resource)
 {
@@ -4227,6 +4267,8 @@ dwl_wm_handle_destroy(struct wl_resource *resource)
 static const struct znet_tapesoftware_dwl_wm_v1_interface dwl_wm_implementation = {
 	.release = dwl_wm_handle_release,
 	.get_monitor = dwl_wm_handle_get_monitor,
+	.list_clients = dwl_wm_handle_list_clients,
+	.focus_client = dwl_wm_handle_focus_client,
 };
 
 static void
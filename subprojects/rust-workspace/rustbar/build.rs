@@ -13,7 +13,7 @@ fn main() {
 
     out_enum
         .write_all(
-            b"#[repr(u16)] #[derive(Default,num_enum::FromPrimitive,Debug)] pub enum NodeKind {",
+            b"#[repr(u16)] #[derive(Default,num_enum::FromPrimitive,Debug,Clone,Copy,PartialEq,Eq)] pub enum NodeKind {",
         )
         .unwrap();
 
@@ -0,0 +1,355 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+    os::fd::AsRawFd,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// The i2c address monitors answer DDC/CI on
+const DDC_ADDRESS: i32 = 0x37;
+/// The VCP code for the luminance of the monitor
+const LUMINANCE: u8 = 0x10;
+
+nix::ioctl_write_int_bad!(i2c_slave, 0x0703);
+
+pub enum Backend {
+    /// A device in `/sys/class/backlight`, written to through logind
+    Sysfs { name: String, path: PathBuf },
+    /// An external monitor, talked to over `/dev/i2c-*`
+    Ddc { device: PathBuf },
+}
+
+/// Steps are even to the eye rather than in raw backlight values, the
+/// raw value is `max_brightness * (percent / 100) ^ GAMMA`
+const GAMMA: f32 = 2.2;
+
+pub struct Backlight {
+    /// The connector the backlight belongs to, like `eDP-1`. `None` if
+    /// the backlight isn't tied to a connector, which is the case for
+    /// ACPI and vendor backlights of the internal panel
+    pub connector: Option<String>,
+    pub raw: u32,
+    pub max_brightness: u32,
+    pub backend: Backend,
+}
+
+impl Backlight {
+    /// Reads the brightness of a sysfs backlight again, DDC/CI monitors
+    /// only change when we change them
+    pub fn reload(&mut self) {
+        if let Backend::Sysfs { ref path, .. } = self.backend {
+            if let Some(raw) = read_number(&path.join("brightness")) {
+                self.raw = raw;
+            }
+        }
+    }
+
+    /// Perceived brightness, from 0 to 100
+    pub fn perceived(&self) -> usize {
+        ((self.raw as f32 / self.max_brightness as f32).powf(1.0 / GAMMA) * 100.0).round() as usize
+    }
+
+    /// The raw value for a perceived brightness from 0 to 100, never 0 so
+    /// the backlight doesn't turn off completely
+    pub fn raw_for(&self, perceived: usize) -> u32 {
+        ((perceived.min(100) as f32 / 100.0).powf(GAMMA) * self.max_brightness as f32)
+            .round()
+            .max(1.0) as u32
+    }
+
+    /// The raw value after stepping the perceived brightness by `change`
+    /// percent. Backlights with few levels would round a small step back
+    /// to the same value, so it always moves by at least 1 unless it's
+    /// already at the end
+    pub fn stepped(&self, change: isize) -> u32 {
+        let raw = self.raw_for(self.perceived().saturating_add_signed(change));
+        match change.signum() {
+            1 => raw.max((self.raw + 1).min(self.max_brightness)),
+            -1 => raw.min(self.raw.saturating_sub(1).max(1)),
+            _ => raw,
+        }
+    }
+}
+
+/// Turns `card0-eDP-1` into `eDP-1`
+fn connector_name(drm_name: &str) -> Option<&str> {
+    drm_name
+        .strip_prefix("card")
+        .and_then(|n| n.split_once('-'))
+        .map(|(_, connector)| connector)
+}
+
+fn read_number(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Every backlight in `<root>/sys/class/backlight`, `root` is `/` unless
+/// a fake sysfs is being used
+pub fn sysfs_backlights(root: &Path) -> Vec<Backlight> {
+    let Ok(dir) = std::fs::read_dir(root.join("sys/class/backlight")) else {
+        return Vec::new();
+    };
+    let mut backlights: Vec<Backlight> = dir
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            let connector = std::fs::canonicalize(path.join("device"))
+                .ok()
+                .and_then(|device| {
+                    connector_name(&device.file_name()?.to_string_lossy()).map(str::to_owned)
+                });
+            Some(Backlight {
+                connector,
+                raw: read_number(&path.join("brightness"))?,
+                max_brightness: read_number(&path.join("max_brightness"))?.max(1),
+                backend: Backend::Sysfs { name, path },
+            })
+        })
+        .collect();
+    backlights.sort_by(|a, b| a.connector.is_none().cmp(&b.connector.is_none()));
+    backlights
+}
+
+/// The connected monitors in `<root>/sys/class/drm` that have a DDC bus,
+/// with the `/dev/i2c-*` device of that bus
+pub fn ddc_displays(root: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(dir) = std::fs::read_dir(root.join("sys/class/drm")) else {
+        return Vec::new();
+    };
+    let mut displays: Vec<(String, PathBuf)> = dir
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let connector = connector_name(&path.file_name()?.to_string_lossy())?.to_owned();
+            if std::fs::read_to_string(path.join("status")).ok()?.trim() != "connected" {
+                return None;
+            }
+            let bus = std::fs::canonicalize(path.join("ddc")).ok()?;
+            Some((connector, root.join("dev").join(bus.file_name()?)))
+        })
+        .collect();
+    displays.sort();
+    displays
+}
+
+/// Every backlight on the machine. External monitors that don't speak
+/// DDC/CI (or whose bus we can't open) are left out
+pub fn enumerate(root: &Path) -> Vec<Backlight> {
+    let mut backlights = sysfs_backlights(root);
+    for (connector, device) in ddc_displays(root) {
+        if backlights
+            .iter()
+            .any(|b| b.connector.as_deref() == Some(&connector))
+        {
+            continue;
+        }
+        if let Ok((raw, max_brightness)) = ddc_get_luminance(&device) {
+            backlights.push(Backlight {
+                connector: Some(connector),
+                raw: raw as u32,
+                max_brightness: max_brightness.max(1) as u32,
+                backend: Backend::Ddc { device },
+            });
+        }
+    }
+    backlights
+}
+
+/// The backlight shown on the bar of `output`. Internal panels fall back
+/// to a backlight without a connector
+pub fn for_output(backlights: &[Backlight], output: &str) -> Option<usize> {
+    backlights
+        .iter()
+        .position(|b| b.connector.as_deref() == Some(output))
+        .or_else(|| {
+            if ["eDP", "LVDS", "DSI"]
+                .iter()
+                .any(|internal| output.starts_with(internal))
+            {
+                backlights.iter().position(|b| b.connector.is_none())
+            } else {
+                None
+            }
+        })
+}
+
+/// Wraps `data` in a DDC/CI packet going from the host to the monitor
+fn ddc_packet(data: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(data.len() + 3);
+    packet.push(0x51);
+    packet.push(0x80 | data.len() as u8);
+    packet.extend_from_slice(data);
+    packet.push(packet.iter().fold(0x6E, |checksum, b| checksum ^ b));
+    packet
+}
+
+/// Parses the reply to a "Get VCP Feature" request for the luminance,
+/// returns the current and the maximum value
+fn parse_luminance(reply: &[u8; 11]) -> Option<(u16, u16)> {
+    let checksum = reply[..10].iter().fold(0x50, |checksum, b| checksum ^ b);
+    if checksum != reply[10] || reply[2] != 0x02 || reply[3] != 0x00 || reply[4] != LUMINANCE {
+        return None;
+    }
+    Some((
+        u16::from_be_bytes([reply[8], reply[9]]),
+        u16::from_be_bytes([reply[6], reply[7]]),
+    ))
+}
+
+fn ddc_open(device: &Path) -> std::io::Result<File> {
+    let file = OpenOptions::new().read(true).write(true).open(device)?;
+    unsafe { i2c_slave(file.as_raw_fd(), DDC_ADDRESS) }?;
+    Ok(file)
+}
+
+pub fn ddc_get_luminance(device: &Path) -> std::io::Result<(u16, u16)> {
+    let mut file = ddc_open(device)?;
+    file.write_all(&ddc_packet(&[0x01, LUMINANCE]))?;
+    // The monitor needs some time before it can answer
+    std::thread::sleep(Duration::from_millis(40));
+    let mut reply = [0; 11];
+    file.read_exact(&mut reply)?;
+    parse_luminance(&reply).ok_or(std::io::ErrorKind::InvalidData.into())
+}
+
+pub fn ddc_set_luminance(device: &Path, value: u16) -> std::io::Result<()> {
+    let [high, low] = value.to_be_bytes();
+    ddc_open(device)?.write_all(&ddc_packet(&[0x03, LUMINANCE, high, low]))
+}
+
+#[cfg(test)]
+fn fake_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("rustbar-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    root
+}
+
+#[cfg(test)]
+fn fake_backlight(root: &Path, name: &str, device: &str, brightness: u32, max: u32) {
+    let path = root.join("sys/class/backlight").join(name);
+    std::fs::create_dir_all(&path).unwrap();
+    std::fs::create_dir_all(root.join(device)).unwrap();
+    std::os::unix::fs::symlink(root.join(device), path.join("device")).unwrap();
+    std::fs::write(path.join("brightness"), format!("{}\n", brightness)).unwrap();
+    std::fs::write(path.join("max_brightness"), format!("{}\n", max)).unwrap();
+}
+
+#[test]
+fn test_no_backlight() {
+    let root = fake_root("no-backlight");
+    assert!(enumerate(&root).is_empty());
+    assert_eq!(for_output(&enumerate(&root), "eDP-1"), None);
+}
+
+#[test]
+fn test_sysfs_backlights() {
+    let root = fake_root("sysfs-backlights");
+    fake_backlight(
+        &root,
+        "acpi_video0",
+        "devices/pci0000:00/0000:00:02.0",
+        5,
+        10,
+    );
+    fake_backlight(
+        &root,
+        "intel_backlight",
+        "devices/pci0000:00/0000:00:02.0/drm/card0/card0-eDP-1",
+        600,
+        1200,
+    );
+
+    let backlights = sysfs_backlights(&root);
+    assert_eq!(backlights.len(), 2);
+    let edp = for_output(&backlights, "eDP-1").unwrap();
+    assert_eq!(backlights[edp].connector.as_deref(), Some("eDP-1"));
+    assert_eq!(backlights[edp].raw, 600);
+    assert_eq!(backlights[edp].max_brightness, 1200);
+    assert_eq!(for_output(&backlights, "HDMI-A-1"), None);
+}
+
+#[test]
+fn test_unmapped_backlight_falls_back_to_internal_panel() {
+    let root = fake_root("unmapped-backlight");
+    fake_backlight(
+        &root,
+        "amdgpu_bl0",
+        "devices/platform/AMDI0010:00",
+        128,
+        255,
+    );
+
+    let backlights = sysfs_backlights(&root);
+    assert_eq!(for_output(&backlights, "eDP-1"), Some(0));
+    assert_eq!(for_output(&backlights, "DP-2"), None);
+}
+
+#[test]
+fn test_step_few_levels() {
+    let root = fake_root("few-levels");
+    fake_backlight(
+        &root,
+        "acpi_video0",
+        "devices/pci0000:00/0000:00:02.0",
+        1,
+        15,
+    );
+
+    let mut backlight = sysfs_backlights(&root).remove(0);
+    assert_eq!(backlight.perceived(), 29);
+    assert_eq!(backlight.raw_for(34), 1);
+    assert_eq!(backlight.stepped(5), 2);
+    assert_eq!(backlight.stepped(-5), 1);
+    backlight.raw = 15;
+    assert_eq!(backlight.stepped(5), 15);
+    assert_eq!(backlight.stepped(-5), 13);
+}
+
+#[test]
+fn test_ddc_displays() {
+    let root = fake_root("ddc-displays");
+    for (connector, bus, status) in [
+        ("card0-DP-1", "i2c-4", "connected"),
+        ("card0-HDMI-A-1", "i2c-5", "disconnected"),
+        ("card1-DP-2", "i2c-7", "connected"),
+    ] {
+        let path = root.join("sys/class/drm").join(connector);
+        let bus = root.join("devices/i2c").join(bus);
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::create_dir_all(&bus).unwrap();
+        std::fs::write(path.join("status"), format!("{}\n", status)).unwrap();
+        std::os::unix::fs::symlink(bus, path.join("ddc")).unwrap();
+    }
+    std::fs::create_dir_all(root.join("sys/class/drm/card0")).unwrap();
+
+    assert_eq!(
+        ddc_displays(&root),
+        vec![
+            ("DP-1".to_owned(), root.join("dev/i2c-4")),
+            ("DP-2".to_owned(), root.join("dev/i2c-7")),
+        ]
+    );
+    // None of the fake buses can be opened as an i2c device
+    assert!(enumerate(&root).is_empty());
+}
+
+#[test]
+fn test_ddc_packet() {
+    assert_eq!(
+        ddc_packet(&[0x01, LUMINANCE]),
+        [0x51, 0x82, 0x01, 0x10, 0xAC]
+    );
+}
+
+#[test]
+fn test_parse_luminance() {
+    let mut reply = [
+        0x6E, 0x88, 0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32, 0x00,
+    ];
+    reply[10] = reply[..10].iter().fold(0x50, |checksum, b| checksum ^ b);
+    assert_eq!(parse_luminance(&reply), Some((50, 100)));
+
+    reply[10] ^= 1;
+    assert_eq!(parse_luminance(&reply), None);
+}
@@ -0,0 +1,333 @@
+//! What the browser launcher offers, the history and bookmarks of the
+//! Firefox or Chromium profile the browser starts with
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use rusqlite::{Connection, OpenFlags};
+use serde_json::Value;
+use url::{form_urlencoded, Url};
+
+use crate::{
+    desktop_entries::{DesktopCommand, Target},
+    launch,
+};
+
+/// Pages past this many are left out, the least visited ones
+const MAX_PAGES: usize = 5000;
+
+/// Every section of an ini file with its keys
+fn ini_sections(ini: &str) -> Vec<(&str, HashMap<&str, &str>)> {
+    let mut sections: Vec<(&str, HashMap<&str, &str>)> = Vec::new();
+    for line in ini.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push((name, HashMap::new()));
+        } else if let (Some((key, value)), Some((_, section))) =
+            (line.split_once('='), sections.last_mut())
+        {
+            section.insert(key.trim(), value.trim());
+        }
+    }
+    sections
+}
+
+/// The profile Firefox starts with, `dir` is the directory with
+/// `profiles.ini` in it
+fn firefox_profile(dir: &Path, profiles_ini: &str) -> Option<PathBuf> {
+    let sections = ini_sections(profiles_ini);
+    // Firefox 67 and newer pick a profile per install
+    if let Some(path) = sections
+        .iter()
+        .filter(|(name, _)| name.starts_with("Install"))
+        .find_map(|(_, keys)| keys.get("Default"))
+    {
+        return Some(dir.join(path));
+    }
+    let mut profiles = sections
+        .iter()
+        .filter(|(name, _)| name.starts_with("Profile"))
+        .map(|(_, keys)| keys);
+    let profile = profiles
+        .clone()
+        .find(|keys| keys.get("Default") == Some(&"1"))
+        .or_else(|| profiles.next())?;
+    let path = profile.get("Path")?;
+    Some(if profile.get("IsRelative") == Some(&"0") {
+        PathBuf::from(path)
+    } else {
+        dir.join(path)
+    })
+}
+
+/// Opens a browser's database without locking it, the browser may
+/// be running
+fn open_immutable(path: &Path) -> Option<Connection> {
+    let mut uri = Url::from_file_path(path).ok()?;
+    uri.set_query(Some("immutable=1"));
+    Connection::open_with_flags(
+        uri.as_str(),
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI,
+    )
+    .ok()
+}
+
+/// The title and URL of every bookmark and visited page, bookmarks are
+/// named after the bookmark instead of the page
+fn firefox(profile: &Path) -> Vec<(String, String)> {
+    let Some(conn) = open_immutable(&profile.join("places.sqlite")) else {
+        return Vec::new();
+    };
+    let Ok(mut stmt) = conn.prepare(
+        "SELECT \
+        COALESCE(\
+            (SELECT title FROM moz_bookmarks WHERE fk = moz_places.id AND title != '' LIMIT 1), \
+            moz_places.title, \
+            ''\
+        ), \
+        url \
+        FROM moz_places \
+        WHERE hidden = 0 AND (visit_count > 0 OR foreign_count > 0) \
+        ORDER BY frecency DESC \
+        LIMIT ?1;",
+    ) else {
+        return Vec::new();
+    };
+    let Ok(rows) = stmt.query_map([MAX_PAGES], |row| Ok((row.get(0)?, row.get(1)?))) else {
+        return Vec::new();
+    };
+    rows.flatten().collect()
+}
+
+/// The profile Chromium was last used with, `dir` is the directory
+/// with `Local State` in it
+fn chromium_profile(dir: &Path) -> PathBuf {
+    let last_used = std::fs::read_to_string(dir.join("Local State"))
+        .ok()
+        .and_then(|local_state| serde_json::from_str::<Value>(&local_state).ok())
+        .and_then(|local_state| {
+            local_state["profile"]["last_used"]
+                .as_str()
+                .map(str::to_owned)
+        });
+    dir.join(last_used.as_deref().unwrap_or("Default"))
+}
+
+fn chromium_bookmarks(node: &Value, pages: &mut Vec<(String, String)>) {
+    if node["type"] == "url" {
+        if let (Some(name), Some(url)) = (node["name"].as_str(), node["url"].as_str()) {
+            pages.push((name.to_owned(), url.to_owned()));
+        }
+    } else if let Some(children) = node["children"].as_array() {
+        for child in children {
+            chromium_bookmarks(child, pages);
+        }
+    }
+}
+
+/// Every bookmark, then the pages in the history from the most visited
+fn chromium(profile: &Path) -> Vec<(String, String)> {
+    let mut pages = Vec::new();
+    if let Some(bookmarks) = std::fs::read_to_string(profile.join("Bookmarks"))
+        .ok()
+        .and_then(|bookmarks| serde_json::from_str::<Value>(&bookmarks).ok())
+    {
+        if let Some(roots) = bookmarks["roots"].as_object() {
+            roots
+                .values()
+                .for_each(|root| chromium_bookmarks(root, &mut pages));
+        }
+    }
+
+    let Some(conn) = open_immutable(&profile.join("History")) else {
+        return pages;
+    };
+    let Ok(mut stmt) = conn.prepare(
+        "SELECT title, url FROM urls \
+        WHERE hidden = 0 \
+        ORDER BY visit_count DESC, last_visit_time DESC \
+        LIMIT ?1;",
+    ) else {
+        return pages;
+    };
+    let Ok(rows) = stmt.query_map([MAX_PAGES], |row| Ok((row.get(0)?, row.get(1)?))) else {
+        return pages;
+    };
+    let bookmarked: HashSet<String> = pages.iter().map(|(_, url)| url.clone()).collect();
+    pages.extend(rows.flatten().filter(|(_, url)| !bookmarked.contains(url)));
+    pages
+}
+
+/// Every page the browser launcher offers. `dir` is where the browser
+/// keeps its profiles relative to ~, Firefox if there's a
+/// `profiles.ini` in it and Chromium otherwise
+pub fn entries(dir: &str, browser: &str) -> Vec<DesktopCommand> {
+    let Some(dir) = dirs::home_dir().map(|home| home.join(dir)) else {
+        return Vec::new();
+    };
+    let pages = match std::fs::read_to_string(dir.join("profiles.ini")) {
+        Ok(profiles_ini) => firefox_profile(&dir, &profiles_ini)
+            .map(|profile| firefox(&profile))
+            .unwrap_or_default(),
+        Err(_) => chromium(&chromium_profile(&dir)),
+    };
+    pages
+        .into_iter()
+        .map(|(title, url)| DesktopCommand {
+            // Both are matched against what's typed
+            name: if title.is_empty() {
+                url.clone()
+            } else {
+                format!("{}  {}", title, url)
+            },
+            target: Target::Run {
+                args: open_args(browser, &url),
+                terminal: false,
+            },
+            id: None,
+            frecency: 0.0,
+        })
+        .collect()
+}
+
+/// The browser setting with `url` after it
+fn open_args(browser: &str, url: &str) -> Vec<String> {
+    browser
+        .split_whitespace()
+        .chain([url])
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Opens `url` with the browser setting
+pub fn open(browser: &str, url: &str) -> std::io::Result<()> {
+    let args = open_args(browser, url);
+    let mut command = Command::new(&args[0]);
+    command.args(&args[1..]);
+    launch::spawn(command, "browser")
+}
+
+/// `input` as a URL if it looks like one, like `https://example.com`,
+/// `example.com/page` or `localhost:8080`
+pub fn as_url(input: &str) -> Option<String> {
+    let input = input.trim();
+    if input.is_empty() || input.contains(char::is_whitespace) {
+        return None;
+    }
+    if let Ok(url) = Url::parse(input) {
+        if matches!(url.scheme(), "http" | "https" | "file" | "ftp" | "about") {
+            return Some(input.to_owned());
+        }
+    }
+    let url = Url::parse(&format!("https://{}", input)).ok()?;
+    let host = url.host_str()?;
+    let tld = host.rsplit_once('.').map(|(_, tld)| tld);
+    (host == "localhost"
+        || tld.is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic())))
+    .then(|| url.into())
+}
+
+/// Searches for `input` with `search_engine`, which has `%s` where the
+/// query goes
+pub fn search_url(input: &str, search_engine: &str) -> String {
+    let query: String = form_urlencoded::byte_serialize(input.trim().as_bytes()).collect();
+    search_engine.replace("%s", &query)
+}
+
+#[test]
+fn test_firefox_profile() {
+    let dir = Path::new("/home/user/.mozilla/firefox");
+    let profiles_ini = "\
+[Profile1]
+Name=default
+IsRelative=1
+Path=abcd.default
+
+[Profile0]
+Name=default-release
+IsRelative=1
+Path=efgh.default-release
+Default=1
+
+[General]
+StartWithLastProfile=1
+";
+    assert_eq!(
+        firefox_profile(dir, profiles_ini),
+        Some(dir.join("efgh.default-release"))
+    );
+
+    let with_install = format!(
+        "{}\n[Install4F96D1932A9F858E]\nDefault=abcd.default\nLocked=1\n",
+        profiles_ini
+    );
+    assert_eq!(
+        firefox_profile(dir, &with_install),
+        Some(dir.join("abcd.default"))
+    );
+
+    assert_eq!(
+        firefox_profile(dir, "[Profile0]\nIsRelative=0\nPath=/tmp/profile\n"),
+        Some(PathBuf::from("/tmp/profile"))
+    );
+    assert_eq!(firefox_profile(dir, "[General]\n"), None);
+}
+
+#[test]
+fn test_chromium_bookmarks() {
+    let bookmarks: Value = serde_json::from_str(
+        r#"{"roots": {"bookmark_bar": {"type": "folder", "children": [
+            {"type": "url", "name": "Rust", "url": "https://www.rust-lang.org/"},
+            {"type": "folder", "name": "Docs", "children": [
+                {"type": "url", "name": "std", "url": "https://doc.rust-lang.org/std/"}
+            ]}
+        ]}}}"#,
+    )
+    .unwrap();
+    let mut pages = Vec::new();
+    chromium_bookmarks(&bookmarks["roots"]["bookmark_bar"], &mut pages);
+    assert_eq!(
+        pages,
+        [
+            ("Rust".to_owned(), "https://www.rust-lang.org/".to_owned()),
+            (
+                "std".to_owned(),
+                "https://doc.rust-lang.org/std/".to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_as_url() {
+    assert_eq!(
+        as_url("https://example.com/a b").as_deref(),
+        None,
+        "whitespace means it's a search"
+    );
+    assert_eq!(
+        as_url("https://example.com/").as_deref(),
+        Some("https://example.com/")
+    );
+    assert_eq!(
+        as_url("example.com/page").as_deref(),
+        Some("https://example.com/page")
+    );
+    assert_eq!(
+        as_url("localhost:8080").as_deref(),
+        Some("https://localhost:8080/")
+    );
+    assert_eq!(as_url("about:config").as_deref(), Some("about:config"));
+    assert_eq!(as_url("rust"), None);
+    assert_eq!(as_url("3.14"), None);
+    assert_eq!(as_url("what is rust"), None);
+}
+
+#[test]
+fn test_search_url() {
+    assert_eq!(
+        search_url("rust & c++", "https://duckduckgo.com/?q=%s"),
+        "https://duckduckgo.com/?q=rust+%26+c%2B%2B"
+    );
+}
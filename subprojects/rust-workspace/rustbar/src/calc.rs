@@ -0,0 +1,328 @@
+//! The launcher's calculator. giac runs on a thread of its own, so an
+//! expression that never finishes can't hold up the bar
+use std::{
+    ffi::CString,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    time::Duration,
+};
+
+use smithay_client_toolkit::reexports::calloop::{
+    channel::{self, Channel, Event},
+    timer::{TimeoutAction, Timer},
+    LoopHandle,
+};
+
+use crate::{ffi, SimpleLayer};
+
+/// giac is interrupted when it takes longer than this
+const TIMEOUT: Duration = Duration::from_secs(2);
+/// Results past this many are forgotten, the oldest first
+const MAX_HISTORY: usize = 100;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evaluation {
+    /// What was typed, before `ans` and units were filled in
+    pub input: String,
+    /// The result, or giac's error
+    pub result: Result<String, String>,
+}
+
+pub struct Calculator {
+    requests: mpsc::Sender<(u64, String, String)>,
+    /// The id of the request giac is busy with, 0 when it's idle
+    evaluating: Arc<AtomicU64>,
+    handle: LoopHandle<'static, SimpleLayer>,
+    last_id: u64,
+    /// The id of the newest result that came back
+    received: u64,
+    /// The input that was sent last
+    requested: String,
+    /// The newest result that came back
+    pub latest: Option<Evaluation>,
+    /// Every input that was entered with its result, the newest last
+    pub history: Vec<Evaluation>,
+}
+
+/// Evaluates `expression` with giac on this thread
+pub fn eval(ctx: &ffi::context, expression: &str) -> Result<String, String> {
+    let expression = CString::new(expression).map_err(|e| e.to_string())?;
+    unsafe { ffi::eval(expression.as_ptr(), ctx) }.map_err(|e| e.what().to_owned())
+}
+
+/// Starts the thread with the giac context, it answers requests until
+/// the other end goes away
+fn spawn_worker(
+    results: channel::Sender<(u64, Evaluation)>,
+    evaluating: Arc<AtomicU64>,
+) -> mpsc::Sender<(u64, String, String)> {
+    let (requests, receiver) = mpsc::channel::<(u64, String, String)>();
+    std::thread::spawn(move || {
+        let ctx = ffi::new_ctx();
+        while let Ok(mut request) = receiver.recv() {
+            // Only the newest input is worth evaluating when typing
+            // outpaced giac
+            while let Ok(newer) = receiver.try_recv() {
+                request = newer;
+            }
+            let (id, input, expression) = request;
+            evaluating.store(id, Ordering::SeqCst);
+            let result = eval(ctx.as_ref().unwrap(), &expression);
+            evaluating.store(0, Ordering::SeqCst);
+            if results.send((id, Evaluation { input, result })).is_err() {
+                break;
+            }
+        }
+    });
+    requests
+}
+
+impl Calculator {
+    pub fn new(handle: &LoopHandle<'static, SimpleLayer>) -> Self {
+        let (results, channel): (_, Channel<(u64, Evaluation)>) = channel::channel();
+        handle
+            .insert_source(channel, |event, _, data| {
+                if let Event::Msg((id, evaluation)) = event {
+                    data.calculator.receive(id, evaluation);
+                    data.refresh_launcher();
+                }
+            })
+            .unwrap();
+        let evaluating = Arc::new(AtomicU64::new(0));
+        Self {
+            requests: spawn_worker(results, Arc::clone(&evaluating)),
+            evaluating,
+            handle: handle.clone(),
+            last_id: 0,
+            received: 0,
+            requested: String::new(),
+            latest: None,
+            history: Vec::new(),
+        }
+    }
+
+    /// Has the worker evaluate `input`, unless that's what it was asked
+    /// last. The result ends up in [`Calculator::latest`]
+    pub fn evaluate(&mut self, input: &str) {
+        if input == self.requested {
+            return;
+        }
+        self.requested = input.to_owned();
+        if input.trim().is_empty() {
+            self.received = self.last_id;
+            self.latest = None;
+            return;
+        }
+        self.send();
+    }
+
+    fn send(&mut self) {
+        self.last_id += 1;
+        let id = self.last_id;
+        let expression = expression(&self.requested, &self.history);
+        let _ = self.requests.send((id, self.requested.clone(), expression));
+        self.handle
+            .insert_source(Timer::from_duration(TIMEOUT), move |_, _, data| {
+                if !data.calculator.time_out(id) {
+                    return TimeoutAction::ToDuration(TIMEOUT);
+                }
+                data.refresh_launcher();
+                TimeoutAction::Drop
+            })
+            .unwrap();
+    }
+
+    fn receive(&mut self, id: u64, evaluation: Evaluation) {
+        if id > self.received {
+            self.received = id;
+            self.latest = Some(evaluation);
+        }
+    }
+
+    /// Interrupts giac if it's still busy with request `id`. Whatever
+    /// was typed since is already waiting for the worker. `false` if giac
+    /// hasn't gotten to `id` yet, so it should be given more time
+    fn time_out(&mut self, id: u64) -> bool {
+        let evaluating = self.evaluating.load(Ordering::SeqCst);
+        // Skipped for a newer request, or done
+        if self.received >= id || evaluating > id {
+            return true;
+        }
+        if evaluating != id {
+            return false;
+        }
+        ffi::interrupt();
+        // The interrupted eval's error comes back late, it's ignored
+        self.received = id;
+        if id == self.last_id {
+            self.latest = Some(Evaluation {
+                input: self.requested.clone(),
+                result: Err(format!("gave up after {}s", TIMEOUT.as_secs())),
+            });
+        }
+        true
+    }
+
+    /// Keeps the result for `input` in the history, if it's there and
+    /// isn't an error
+    pub fn commit(&mut self, input: &str) -> bool {
+        match self.latest.take() {
+            Some(evaluation) if evaluation.input == input && evaluation.result.is_ok() => {
+                self.history.push(evaluation);
+                if self.history.len() > MAX_HISTORY {
+                    self.history.remove(0);
+                }
+                self.requested.clear();
+                true
+            }
+            latest => {
+                self.latest = latest;
+                false
+            }
+        }
+    }
+}
+
+/// Replaces `ans` with the newest result in `history` and `ansN` with
+/// the Nth newest, so `ans1` is `ans`. Results that don't exist are
+/// left alone for giac to complain about
+fn replace_answers(input: &str, history: &[Evaluation]) -> String {
+    let mut replaced = String::new();
+    let mut rest = input;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        replaced.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let word = &rest[..end];
+        let back = match word.strip_prefix("ans") {
+            Some("") => Some(1),
+            Some(n) => n.parse::<usize>().ok(),
+            None => None,
+        };
+        match back
+            .filter(|&back| back > 0)
+            .and_then(|back| history.len().checked_sub(back))
+            .and_then(|i| history[i].result.as_ref().ok())
+        {
+            Some(result) => write!(replaced, "({})", result).unwrap(),
+            None => replaced.push_str(word),
+        }
+        rest = &rest[end..];
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+fn is_unit_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_*/^".contains(c)
+}
+
+/// `km/h` as giac's `_km/_h`
+fn giac_units(units: &str) -> String {
+    let mut giac = String::new();
+    let mut in_name = false;
+    for c in units.chars() {
+        if c.is_ascii_alphabetic() && !in_name {
+            giac.push('_');
+        }
+        in_name = c.is_ascii_alphanumeric() || c == '_';
+        giac.push(c);
+    }
+    giac
+}
+
+/// Multiplies the numbers in `quantity` that have units after them
+/// with giac's units, `5 km` becomes `5*_km`
+fn attach_units(quantity: &str) -> String {
+    let mut attached = String::new();
+    let mut rest = quantity;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        // A digit in a name like `x2` isn't a number
+        let in_name = rest[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+        let end = start
+            + rest[start..]
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len() - start);
+        attached.push_str(&rest[..end]);
+        rest = &rest[end..];
+        let units = rest.trim_start_matches(' ');
+        if !in_name && units.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let len = units.find(|c| !is_unit_char(c)).unwrap_or(units.len());
+            write!(attached, "*{}", giac_units(&units[..len])).unwrap();
+            rest = &units[len..];
+        }
+    }
+    attached.push_str(rest);
+    attached
+}
+
+/// `5 km in mi` or `to` as giac's `convert(5*_km,_mi)`
+fn convert_units(expression: &str) -> Option<String> {
+    let (quantity, units) = [" in ", " to "]
+        .into_iter()
+        .filter_map(|separator| expression.rsplit_once(separator))
+        .max_by_key(|(quantity, _)| quantity.len())?;
+    let units = units.trim();
+    if units.is_empty()
+        || !units.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !units.chars().all(is_unit_char)
+    {
+        return None;
+    }
+    Some(format!(
+        "convert({},{})",
+        attach_units(quantity),
+        giac_units(units)
+    ))
+}
+
+/// What giac is asked for `input`, with the results it refers to filled
+/// in and unit conversions spelled the way giac wants
+pub fn expression(input: &str, history: &[Evaluation]) -> String {
+    let expression = replace_answers(input, history);
+    convert_units(&expression).unwrap_or(expression)
+}
+
+#[test]
+fn test_expression() {
+    let history = [
+        Evaluation {
+            input: "1+1".to_owned(),
+            result: Ok("2".to_owned()),
+        },
+        Evaluation {
+            input: "5 km in mi".to_owned(),
+            result: Ok("3.10685596119_mi".to_owned()),
+        },
+    ];
+    assert_eq!(expression("ans*2", &history), "(3.10685596119_mi)*2");
+    assert_eq!(expression("ans1+ans2", &history), "(3.10685596119_mi)+(2)");
+    assert_eq!(expression("ans3", &history), "ans3");
+    assert_eq!(expression("answer+ans0", &history), "answer+ans0");
+    assert_eq!(expression("ans", &[]), "ans");
+
+    assert_eq!(expression("5 km in mi", &[]), "convert(5*_km,_mi)");
+    assert_eq!(
+        expression("1.5km + 300 m to ft", &[]),
+        "convert(1.5*_km + 300*_m,_ft)"
+    );
+    assert_eq!(
+        expression("100 km/h in m/s", &[]),
+        "convert(100*_km/_h,_m/_s)"
+    );
+    assert_eq!(
+        expression("ans in km", &history),
+        "convert((3.10685596119_mi),_km)"
+    );
+    // Not a unit after `in`
+    assert_eq!(expression("x in [1,2]", &[]), "x in [1,2]");
+    assert_eq!(expression("2x+3", &[]), "2x+3");
+}
@@ -0,0 +1,81 @@
+//! wlr data control, which tells about every selection even when
+//! rustbar doesn't have keyboard focus
+use std::sync::Mutex;
+
+use smithay_client_toolkit::globals::GlobalData;
+use smithay_client_toolkit::reexports::client::{
+    event_created_child, Connection, Dispatch, QueueHandle,
+};
+use smithay_client_toolkit::reexports::protocols_wlr::data_control::v1::client::{
+    zwlr_data_control_device_v1::{self, ZwlrDataControlDeviceV1},
+    zwlr_data_control_manager_v1::{self, ZwlrDataControlManagerV1},
+    zwlr_data_control_offer_v1::{self, ZwlrDataControlOfferV1},
+};
+
+use crate::SimpleLayer;
+
+use super::state::SelectionTarget;
+
+impl Dispatch<ZwlrDataControlManagerV1, GlobalData> for SimpleLayer {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrDataControlManagerV1,
+        _: zwlr_data_control_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrDataControlDeviceV1, ()> for SimpleLayer {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwlrDataControlDeviceV1,
+        event: zwlr_data_control_device_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_data_control_device_v1::Event::Selection { id } => {
+                state.clipboard_state.selection_changed(
+                    SelectionTarget::Clipboard,
+                    id,
+                    &state.bar_settings.clipboard_exclude,
+                );
+            }
+            zwlr_data_control_device_v1::Event::PrimarySelection { id } => {
+                state.clipboard_state.selection_changed(
+                    SelectionTarget::Primary,
+                    id,
+                    &state.bar_settings.clipboard_exclude,
+                );
+            }
+            zwlr_data_control_device_v1::Event::Finished => {
+                proxy.destroy();
+                state.clipboard_state.seat.data_control_device = None;
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(SimpleLayer, ZwlrDataControlDeviceV1, [
+        zwlr_data_control_device_v1::EVT_DATA_OFFER_OPCODE => (ZwlrDataControlOfferV1, Mutex::new(Vec::new()))
+    ]);
+}
+
+impl Dispatch<ZwlrDataControlOfferV1, Mutex<Vec<String>>> for SimpleLayer {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrDataControlOfferV1,
+        event: zwlr_data_control_offer_v1::Event,
+        mime_types: &Mutex<Vec<String>>,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let zwlr_data_control_offer_v1::Event::Offer { mime_type } = event {
+            mime_types.lock().unwrap().push(mime_type);
+        }
+    }
+}
@@ -0,0 +1,346 @@
+//! Every selection that was copied, so the launcher can offer it again.
+//! Kept in `$XDG_DATA_HOME/rustbar/clipboard.sqlite`
+use std::rc::Rc;
+
+use rusqlite::{params, Connection};
+
+use crate::desktop_entries::{DesktopCommand, Target};
+
+use super::mime::{MimeType, Selection};
+use super::state::SelectionTarget;
+
+/// Selections past this many are forgotten, the oldest first
+const MAX_ENTRIES: i64 = 200;
+/// How many bytes of one selection are kept, the types that don't fit
+/// anymore are left out
+const MAX_ENTRY_BYTES: usize = 8 * 1024 * 1024;
+/// How much of a selection the launcher shows
+const PREVIEW_CHARS: usize = 200;
+
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    /// Opens the history, it's only kept in memory if the file can't
+    /// be opened
+    pub fn open() -> Self {
+        let conn = dirs::data_dir()
+            .and_then(|dir| {
+                let dir = dir.join("rustbar");
+                std::fs::create_dir_all(&dir).ok()?;
+                Connection::open(dir.join("clipboard.sqlite")).ok()
+            })
+            .unwrap_or_else(|| Connection::open_in_memory().unwrap());
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Self {
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        if version < 1 {
+            // The first version only kept text, which moves to `offers`.
+            // `content` is what the launcher shows, two images can look
+            // the same there
+            conn.execute_batch(
+                "BEGIN; \
+                CREATE TABLE IF NOT EXISTS selections ( \
+                    id INTEGER PRIMARY KEY AUTOINCREMENT, \
+                    target TEXT NOT NULL, \
+                    content TEXT NOT NULL UNIQUE \
+                ); \
+                CREATE TABLE new_selections ( \
+                    id INTEGER PRIMARY KEY AUTOINCREMENT, \
+                    target TEXT NOT NULL, \
+                    content TEXT NOT NULL \
+                ); \
+                INSERT INTO new_selections SELECT id, target, content FROM selections; \
+                CREATE TABLE offers ( \
+                    selection INTEGER NOT NULL REFERENCES selections(id) ON DELETE CASCADE, \
+                    position INTEGER NOT NULL, \
+                    mime TEXT NOT NULL, \
+                    data BLOB NOT NULL \
+                ); \
+                INSERT INTO offers \
+                    SELECT id, 0, 'text/plain;charset=utf-8', CAST(content AS BLOB) FROM selections; \
+                DROP TABLE selections; \
+                ALTER TABLE new_selections RENAME TO selections; \
+                CREATE INDEX offers_selection ON offers (selection); \
+                PRAGMA user_version = 1; \
+                COMMIT;",
+            )
+            .unwrap();
+        }
+        conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+        Self { conn }
+    }
+
+    /// Puts `selection` first, selections that are copied again move up
+    /// instead of showing up twice
+    pub fn record(&self, target: SelectionTarget, selection: &Selection) {
+        let offers = kept_offers(selection);
+        let content = describe(selection);
+        if offers.is_empty() || content.trim().is_empty() {
+            return;
+        }
+        let target = match target {
+            SelectionTarget::Clipboard => "clipboard",
+            SelectionTarget::Primary => "primary",
+        };
+        let Ok(transaction) = self.conn.unchecked_transaction() else {
+            return;
+        };
+        // Text is the same text whichever app it came from, anything else
+        // has to be the same bytes
+        let (mime_type, data) = offers[0];
+        let _ = transaction.execute(
+            "DELETE FROM selections WHERE content = ?1 AND (?2 OR id IN \
+            (SELECT selection FROM offers WHERE position = 0 AND mime = ?3 AND data = ?4))",
+            params![
+                content,
+                selection.as_text().is_some(),
+                mime_type.as_str(),
+                &data[..]
+            ],
+        );
+        if transaction
+            .execute(
+                "INSERT INTO selections (target, content) VALUES (?1, ?2)",
+                params![target, content],
+            )
+            .is_err()
+        {
+            return;
+        }
+        let id = transaction.last_insert_rowid();
+        for (position, (mime_type, data)) in offers.into_iter().enumerate() {
+            let _ = transaction.execute(
+                "INSERT INTO offers (selection, position, mime, data) VALUES (?1, ?2, ?3, ?4)",
+                params![id, position, mime_type.as_str(), &data[..]],
+            );
+        }
+        let _ = transaction.execute(
+            "DELETE FROM selections WHERE id NOT IN \
+            (SELECT id FROM selections ORDER BY id DESC LIMIT ?1)",
+            [MAX_ENTRIES],
+        );
+        let _ = transaction.commit();
+    }
+
+    /// Every selection with its id and what the launcher shows for it,
+    /// the newest first
+    pub fn entries(&self) -> Vec<(i64, SelectionTarget, String)> {
+        let Ok(mut stmt) = self
+            .conn
+            .prepare("SELECT id, target, content FROM selections ORDER BY id DESC")
+        else {
+            return Vec::new();
+        };
+        let Ok(rows) = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        }) else {
+            return Vec::new();
+        };
+        rows.flatten()
+            .map(|(id, target, content)| {
+                let target = match target.as_str() {
+                    "primary" => SelectionTarget::Primary,
+                    _ => SelectionTarget::Clipboard,
+                };
+                (id, target, content)
+            })
+            .collect()
+    }
+
+    /// The selection with `id` in every type it was copied as
+    pub fn get(&self, id: i64) -> Option<Selection> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT mime, data FROM offers WHERE selection = ?1 ORDER BY position")
+            .ok()?;
+        let rows = stmt
+            .query_map([id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })
+            .ok()?;
+        let offers: Vec<_> = rows
+            .flatten()
+            .map(|(mime_type, data)| (MimeType::from(mime_type.as_str()), Rc::from(data)))
+            .collect();
+        (!offers.is_empty()).then_some(Selection { offers })
+    }
+
+    /// Every selection as a launcher entry that offers it again
+    pub fn commands(&self) -> Vec<DesktopCommand> {
+        self.entries()
+            .into_iter()
+            .map(|(id, _, content)| DesktopCommand {
+                name: preview(&content),
+                target: Target::ClipboardEntry(id),
+                id: None,
+                frecency: 0.0,
+            })
+            .collect()
+    }
+}
+
+/// The types of `selection` worth keeping. Images are usually offered in
+/// several formats, only the first is kept
+fn kept_offers(selection: &Selection) -> Vec<&(MimeType, Rc<[u8]>)> {
+    let mut offers = Vec::new();
+    let mut bytes = 0;
+    let mut image = false;
+    for offer in &selection.offers {
+        let (mime_type, contents) = offer;
+        let is_image = mime_type.as_str().starts_with("image/");
+        if !mime_type.is_content()
+            || contents.is_empty()
+            || (is_image && image)
+            || bytes + contents.len() > MAX_ENTRY_BYTES
+        {
+            continue;
+        }
+        image |= is_image;
+        bytes += contents.len();
+        offers.push(offer);
+    }
+    offers
+}
+
+/// What the launcher shows for `selection`, its text, the files that
+/// were copied or what kind of image it is
+fn describe(selection: &Selection) -> String {
+    if let Some(text) = selection.as_text() {
+        text
+    } else if let Some(uris) = selection.uris() {
+        uris.join("\n")
+    } else if let Some((width, height)) = selection.png_size() {
+        format!("image/png {}×{}", width, height)
+    } else if let Some((mime_type, data)) = selection.offers.first() {
+        format!("{} {} bytes", mime_type.as_str(), data.len())
+    } else {
+        String::new()
+    }
+}
+
+/// The start of `content` on one line
+fn preview(content: &str) -> String {
+    content
+        .trim()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(PREVIEW_CHARS)
+        .collect()
+}
+
+#[test]
+fn test_history() {
+    let history = History::with_connection(Connection::open_in_memory().unwrap());
+    let record = |target, text: &str| history.record(target, &Selection::text(text.to_owned()));
+    record(SelectionTarget::Clipboard, "first");
+    record(SelectionTarget::Primary, "second");
+    record(SelectionTarget::Clipboard, "  \n");
+    record(SelectionTarget::Clipboard, "first");
+    let contents: Vec<String> = history
+        .entries()
+        .into_iter()
+        .map(|(_, _, content)| content)
+        .collect();
+    assert_eq!(contents, ["first", "second"]);
+
+    for i in 0..MAX_ENTRIES {
+        record(SelectionTarget::Clipboard, &i.to_string());
+    }
+    let entries = history.entries();
+    assert_eq!(entries.len(), MAX_ENTRIES as usize);
+    assert_eq!(entries[0].2, (MAX_ENTRIES - 1).to_string());
+
+    assert_eq!(
+        preview("  fn main() {\n\tprintln!();\r\n}\n"),
+        "fn main() { println!(); }"
+    );
+    assert_eq!(preview(&"a".repeat(500)).len(), PREVIEW_CHARS);
+}
+
+#[test]
+fn test_history_offers() {
+    let history = History::with_connection(Connection::open_in_memory().unwrap());
+    let image = |data: &[u8]| Selection {
+        offers: vec![
+            (MimeType::Png, Rc::from(data)),
+            (MimeType::Other("TIMESTAMP".to_owned()), Rc::from(&b"0"[..])),
+        ],
+    };
+    history.record(SelectionTarget::Clipboard, &image(b"one"));
+    history.record(SelectionTarget::Clipboard, &image(b"two"));
+    history.record(SelectionTarget::Clipboard, &image(b"one"));
+    let entries = history.entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].2, "image/png 3 bytes");
+    // Only what's worth offering again is kept
+    assert_eq!(
+        history.get(entries[0].0),
+        Some(Selection {
+            offers: vec![(MimeType::Png, Rc::from(&b"one"[..]))],
+        })
+    );
+
+    let files = Selection {
+        offers: vec![
+            (MimeType::UriList, Rc::from(&b"file:///tmp/a\r\n"[..])),
+            (MimeType::TextPlainUtf8, Rc::from(&b"/tmp/a"[..])),
+        ],
+    };
+    history.record(SelectionTarget::Clipboard, &files);
+    assert_eq!(history.get(history.entries()[0].0), Some(files));
+
+    let big = Selection {
+        offers: vec![
+            (MimeType::Png, Rc::from(&b"png"[..])),
+            (
+                MimeType::Other("image/bmp".to_owned()),
+                Rc::from(&b"bmp"[..]),
+            ),
+            (
+                MimeType::Other("application/octet-stream".to_owned()),
+                Rc::from(vec![0; MAX_ENTRY_BYTES]),
+            ),
+            (MimeType::TextPlainUtf8, Rc::from(&b"alt text"[..])),
+        ],
+    };
+    assert_eq!(
+        kept_offers(&big),
+        [
+            &(MimeType::Png, Rc::from(&b"png"[..])),
+            &(MimeType::TextPlainUtf8, Rc::from(&b"alt text"[..]))
+        ]
+    );
+}
+
+#[test]
+fn test_history_migration() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE selections ( \
+            id INTEGER PRIMARY KEY AUTOINCREMENT, \
+            target TEXT NOT NULL, \
+            content TEXT NOT NULL UNIQUE \
+        ); \
+        INSERT INTO selections (target, content) VALUES ('clipboard', 'copied');",
+    )
+    .unwrap();
+    let history = History::with_connection(conn);
+    let entries = history.entries();
+    assert_eq!(entries[0].2, "copied");
+    assert_eq!(
+        history.get(entries[0].0),
+        Some(Selection::text("copied".to_owned()))
+    );
+}
@@ -1,50 +1,202 @@
-/// List of allowed mimes.
-pub static ALLOWED_MIME_TYPES: [&str; 3] =
-    ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain"];
+use std::rc::Rc;
 
-/// Mime type supported by clipboard.
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+use url::Url;
+
+/// List of mimes text is offered as.
+pub static TEXT_MIME_TYPES: [&str; 3] = ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain"];
+
+/// Selections bigger than this aren't read, in any type.
+pub const MAX_PAYLOAD: usize = 32 * 1024 * 1024;
+
+/// Mime type of a selection.
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum MimeType {
     /// text/plain;charset=utf-8 mime type.
     ///
     /// The primary mime type used by most clients
-    TextPlainUtf8 = 0,
+    TextPlainUtf8,
     /// UTF8_STRING mime type.
     ///
     /// Some X11 clients are using only this mime type, so we
     /// should have it as a fallback just in case.
-    Utf8String = 1,
+    Utf8String,
     /// text/plain mime type.
     ///
     /// Fallback without charset parameter.
-    TextPlain = 2,
+    TextPlain,
+    /// text/uri-list mime type.
+    ///
+    /// What file managers copy files as.
+    UriList,
+    /// image/png mime type.
+    Png,
+    /// Any other mime type, only passed along.
+    Other(String),
 }
 
 impl MimeType {
-    /// Find first allowed mime type among the `offered_mime_types`.
+    /// Find first text mime type among the `offered_mime_types`.
     ///
-    /// `find_allowed()` searches for mime type clipboard supports, if we have a
+    /// `find_allowed()` searches for mime type clipboard can paste as text, if we have a
     /// match, returns `Some(MimeType)`, otherwise `None`.
     pub fn find_allowed(offered_mime_types: &[String]) -> Option<Self> {
         let mut fallback = None;
         for offered_mime_type in offered_mime_types.iter() {
-            if offered_mime_type == ALLOWED_MIME_TYPES[Self::TextPlainUtf8 as usize] {
-                return Some(Self::TextPlainUtf8);
-            } else if offered_mime_type == ALLOWED_MIME_TYPES[Self::Utf8String as usize] {
-                return Some(Self::Utf8String);
-            } else if offered_mime_type == ALLOWED_MIME_TYPES[Self::TextPlain as usize] {
-                // Only use this mime type as a fallback.
-                fallback = Some(Self::TextPlain);
+            match Self::from(offered_mime_type.as_str()) {
+                Self::TextPlain => {
+                    // Only use this mime type as a fallback.
+                    fallback = Some(Self::TextPlain);
+                }
+                mime_type if mime_type.is_text() => return Some(mime_type),
+                _ => (),
             }
         }
 
         fallback
     }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::TextPlainUtf8 => TEXT_MIME_TYPES[0],
+            Self::Utf8String => TEXT_MIME_TYPES[1],
+            Self::TextPlain => TEXT_MIME_TYPES[2],
+            Self::UriList => "text/uri-list",
+            Self::Png => "image/png",
+            Self::Other(mime_type) => mime_type,
+        }
+    }
+
+    /// Whether it's one of [`TEXT_MIME_TYPES`].
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self,
+            Self::TextPlainUtf8 | Self::Utf8String | Self::TextPlain
+        )
+    }
+
+    /// Whether it's worth keeping. X11 clients also offer targets like
+    /// `TIMESTAMP` and hints for clipboard managers, those aren't contents.
+    pub fn is_content(&self) -> bool {
+        self.is_text() || self.as_str().contains('/')
+    }
+}
+
+impl From<&str> for MimeType {
+    fn from(mime_type: &str) -> Self {
+        match mime_type {
+            "text/plain;charset=utf-8" => Self::TextPlainUtf8,
+            "UTF8_STRING" => Self::Utf8String,
+            "text/plain" => Self::TextPlain,
+            "text/uri-list" => Self::UriList,
+            "image/png" => Self::Png,
+            _ => Self::Other(mime_type.to_owned()),
+        }
+    }
 }
 
 impl ToString for MimeType {
     fn to_string(&self) -> String {
-        String::from(ALLOWED_MIME_TYPES[*self as usize])
+        String::from(self.as_str())
+    }
+}
+
+/// A selection in every type it was offered as.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selection {
+    /// The mime types with their contents, in the order they were offered.
+    pub offers: Vec<(MimeType, Rc<[u8]>)>,
+}
+
+impl Selection {
+    /// Text, offered as every text mime type.
+    pub fn text(text: String) -> Self {
+        Self {
+            offers: vec![(MimeType::TextPlainUtf8, Rc::from(text.into_bytes()))],
+        }
+    }
+
+    /// The mime types the selection can be offered as, text is offered as
+    /// every text mime type as long as it's there as one.
+    pub fn mime_types(&self) -> Vec<String> {
+        let mut mime_types: Vec<String> = self
+            .offers
+            .iter()
+            .filter(|(mime_type, _)| !mime_type.is_text())
+            .map(|(mime_type, _)| mime_type.to_string())
+            .collect();
+        if self.offers.iter().any(|(mime_type, _)| mime_type.is_text()) {
+            mime_types.extend(
+                TEXT_MIME_TYPES
+                    .iter()
+                    .map(|&mime_type| mime_type.to_owned()),
+            );
+        }
+        mime_types
+    }
+
+    /// The contents for `mime_type`, text mime types stand in for each other.
+    pub fn get(&self, mime_type: &str) -> Option<&Rc<[u8]>> {
+        let mime_type = MimeType::from(mime_type);
+        self.offers
+            .iter()
+            .find(|(offered, _)| *offered == mime_type)
+            .or_else(|| {
+                mime_type
+                    .is_text()
+                    .then(|| self.offers.iter().find(|(offered, _)| offered.is_text()))
+                    .flatten()
+            })
+            .map(|(_, contents)| contents)
+    }
+
+    /// The selection as text, if it was offered as text.
+    pub fn as_text(&self) -> Option<String> {
+        let offered: Vec<String> = self
+            .offers
+            .iter()
+            .map(|(mime_type, _)| mime_type.to_string())
+            .collect();
+        let mime_type = MimeType::find_allowed(&offered)?;
+        let contents = self.get(mime_type.as_str())?;
+        let text = String::from_utf8_lossy(contents).into_owned();
+        // Post-process the content according to mime type.
+        Some(match mime_type {
+            MimeType::TextPlainUtf8 | MimeType::TextPlain => normalize_to_lf(text),
+            _ => text,
+        })
+    }
+
+    /// The paths of the files in a `text/uri-list`, other URIs as they are.
+    pub fn uris(&self) -> Option<Vec<String>> {
+        let uri_list = String::from_utf8_lossy(self.get(MimeType::UriList.as_str())?).into_owned();
+        Some(
+            uri_list
+                .lines()
+                .map(str::trim)
+                // Comments are allowed by RFC-2483
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|uri| {
+                    Url::parse(uri)
+                        .ok()
+                        .filter(|url| url.scheme() == "file")
+                        .and_then(|url| url.to_file_path().ok())
+                        .map_or_else(|| uri.to_owned(), |path| path.display().to_string())
+                })
+                .collect(),
+        )
+    }
+
+    /// Width and height of an `image/png`.
+    pub fn png_size(&self) -> Option<(u32, u32)> {
+        let png = self.get(MimeType::Png.as_str())?;
+        // The signature, then the IHDR chunk's length and type
+        if png.len() < 24 || &png[12..16] != b"IHDR" {
+            return None;
+        }
+        Some((
+            u32::from_be_bytes(png[16..20].try_into().unwrap()),
+            u32::from_be_bytes(png[20..24].try_into().unwrap()),
+        ))
     }
 }
 
@@ -56,3 +208,53 @@ impl ToString for MimeType {
 pub fn normalize_to_lf(text: String) -> String {
     text.replace("\r\n", "\n").replace('\r', "\n")
 }
+
+#[test]
+fn test_selection() {
+    let selection = Selection {
+        offers: vec![
+            (
+                MimeType::UriList,
+                Rc::from(&b"# copied\r\nfile:///tmp/a%20b.png\r\nhttps://example.com/\r\n"[..]),
+            ),
+            (MimeType::Utf8String, Rc::from(&b"/tmp/a b.png"[..])),
+            (
+                MimeType::Other("x-special/gnome-copied-files".to_owned()),
+                Rc::from(&b"copy"[..]),
+            ),
+        ],
+    };
+    assert_eq!(
+        selection.mime_types(),
+        [
+            "text/uri-list",
+            "x-special/gnome-copied-files",
+            "text/plain;charset=utf-8",
+            "UTF8_STRING",
+            "text/plain"
+        ]
+    );
+    assert_eq!(
+        selection.get("text/plain").map(|c| &c[..]),
+        Some(&b"/tmp/a b.png"[..])
+    );
+    assert_eq!(selection.get("image/png"), None);
+    assert_eq!(selection.as_text().as_deref(), Some("/tmp/a b.png"));
+    assert_eq!(
+        selection.uris().unwrap(),
+        ["/tmp/a b.png", "https://example.com/"]
+    );
+
+    let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    png.extend_from_slice(&640u32.to_be_bytes());
+    png.extend_from_slice(&480u32.to_be_bytes());
+    let image = Selection {
+        offers: vec![(MimeType::Png, Rc::from(png))],
+    };
+    assert_eq!(image.png_size(), Some((640, 480)));
+    assert_eq!(image.as_text(), None);
+    assert_eq!(image.mime_types(), ["image/png"]);
+
+    assert!(!MimeType::from("TIMESTAMP").is_content());
+    assert!(MimeType::from("UTF8_STRING").is_content());
+}
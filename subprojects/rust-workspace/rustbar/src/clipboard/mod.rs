@@ -2,5 +2,7 @@
 ///! https://github.com/Smithay/smithay-clipboard/tree/master
 ///! https://github.com/Smithay/smithay-clipboard/tree/master
 ///! https://github.com/Smithay/smithay-clipboard/tree/master
+mod data_control;
+pub mod history;
 pub mod mime;
 pub mod state;
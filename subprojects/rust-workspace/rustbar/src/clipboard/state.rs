@@ -1,12 +1,13 @@
 use nix::fcntl::{FcntlArg, OFlag};
 use smithay_client_toolkit as sctk;
 
-use std::borrow::Cow;
 use std::cell::RefCell;
+use std::fs::File;
 use std::io::{Error, ErrorKind, Read, Result, Write};
 use std::mem;
-use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::io::{AsFd, AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::rc::Rc;
+use std::sync::Mutex;
 
 use sctk::data_device_manager::data_device::{DataDevice, DataDeviceHandler};
 use sctk::data_device_manager::data_offer::{DataOfferError, DataOfferHandler, DragOffer};
@@ -18,38 +19,49 @@ use sctk::primary_selection::PrimarySelectionManagerState;
 use sctk::seat::pointer::{PointerEvent, PointerEventKind};
 use sctk::seat::Capability;
 
-use sctk::reexports::calloop::{LoopHandle, PostAction};
+use sctk::globals::GlobalData;
+use sctk::reexports::calloop::generic::Generic;
+use sctk::reexports::calloop::{Interest, LoopHandle, Mode, PostAction};
 use sctk::reexports::client::globals::GlobalList;
 use sctk::reexports::client::protocol::wl_data_device::WlDataDevice;
 use sctk::reexports::client::protocol::wl_data_device_manager::DndAction;
 use sctk::reexports::client::protocol::wl_data_source::WlDataSource;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
-use sctk::reexports::client::{Connection, QueueHandle};
+use sctk::reexports::client::{Connection, Proxy, QueueHandle};
 use sctk::reexports::protocols::wp::primary_selection::zv1::client::{
     zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1,
     zwp_primary_selection_source_v1::ZwpPrimarySelectionSourceV1,
 };
+use sctk::reexports::protocols_wlr::data_control::v1::client::{
+    zwlr_data_control_device_v1::ZwlrDataControlDeviceV1,
+    zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+    zwlr_data_control_offer_v1::ZwlrDataControlOfferV1,
+};
 
-use crate::push_str::PushString;
 use crate::SimpleLayer;
 
-use super::mime::{normalize_to_lf, MimeType, ALLOWED_MIME_TYPES};
+use super::history::History;
+use super::mime::{MimeType, Selection, MAX_PAYLOAD};
 
 pub struct State {
     pub primary_selection_manager_state: Option<PrimarySelectionManagerState>,
     pub data_device_manager_state: Option<DataDeviceManagerState>,
+    /// Tells about every selection, even without keyboard focus, which
+    /// is what keeps the history going
+    pub data_control_manager: Option<ZwlrDataControlManagerV1>,
     pub exit: bool,
+    pub history: History,
 
-    seat: ClipboardSeatState,
+    pub(super) seat: ClipboardSeatState,
 
     loop_handle: LoopHandle<'static, SimpleLayer>,
     queue_handle: QueueHandle<SimpleLayer>,
 
     primary_sources: Vec<PrimarySelectionSource>,
-    primary_selection_content: Rc<[u8]>,
+    primary_selection_content: Selection,
 
     data_sources: Vec<CopyPasteSource>,
-    data_selection_content: Rc<[u8]>,
+    data_selection_content: Selection,
 }
 
 impl State {
@@ -62,6 +74,8 @@ impl State {
         let data_device_manager_state = DataDeviceManagerState::bind(globals, queue_handle).ok();
         let primary_selection_manager_state =
             PrimarySelectionManagerState::bind(globals, queue_handle).ok();
+        // Version 2 added the primary selection
+        let data_control_manager = globals.bind(queue_handle, 1..=2, GlobalData).ok();
 
         // When both globals are not available nothing could be done.
         if data_device_manager_state.is_none() && primary_selection_manager_state.is_none() {
@@ -70,34 +84,36 @@ impl State {
 
         Some(Self {
             seat: ClipboardSeatState::default(),
-            primary_selection_content: Rc::from([]),
-            data_selection_content: Rc::from([]),
+            primary_selection_content: Selection::default(),
+            data_selection_content: Selection::default(),
             queue_handle: queue_handle.clone(),
             primary_selection_manager_state,
             primary_sources: Vec::new(),
             data_device_manager_state,
             data_sources: Vec::new(),
+            data_control_manager,
             loop_handle,
             exit: false,
+            history: History::open(),
         })
     }
 
     /// Store selection for the given target.
     ///
     /// Selection source is only created when `Some(())` is returned.
-    pub fn store_selection(&mut self, ty: SelectionTarget, contents: String) -> Option<()> {
+    pub fn store_selection(&mut self, ty: SelectionTarget, selection: Selection) -> Option<()> {
         if !self.seat.has_focus {
             return None;
         }
 
-        let contents = Rc::from(contents.into_bytes());
+        self.history.record(ty, &selection);
+        let mime_types = selection.mime_types();
 
         match ty {
             SelectionTarget::Clipboard => {
                 let mgr = self.data_device_manager_state.as_ref()?;
-                self.data_selection_content = contents;
-                let source =
-                    mgr.create_copy_paste_source(&self.queue_handle, ALLOWED_MIME_TYPES.iter());
+                self.data_selection_content = selection;
+                let source = mgr.create_copy_paste_source(&self.queue_handle, mime_types.iter());
                 source.set_selection(
                     self.seat.data_device.as_ref().unwrap(),
                     self.seat.latest_serial,
@@ -106,9 +122,8 @@ impl State {
             }
             SelectionTarget::Primary => {
                 let mgr = self.primary_selection_manager_state.as_ref()?;
-                self.primary_selection_content = contents;
-                let source =
-                    mgr.create_selection_source(&self.queue_handle, ALLOWED_MIME_TYPES.iter());
+                self.primary_selection_content = selection;
+                let source = mgr.create_selection_source(&self.queue_handle, mime_types.iter());
                 source.set_selection(
                     self.seat.primary_device.as_ref().unwrap(),
                     self.seat.latest_serial,
@@ -120,17 +135,18 @@ impl State {
         Some(())
     }
 
-    /// Load selection for the given target.
+    /// Load selection for the given target in every type it's offered as,
+    /// `done` gets it once it's read.
     pub fn load_selection(
         &mut self,
         ty: SelectionTarget,
-        string: Rc<RefCell<PushString>>,
+        done: impl FnOnce(&mut SimpleLayer, Selection) + 'static,
     ) -> Result<()> {
         if !self.seat.has_focus {
             return Err(Error::new(ErrorKind::Other, "client doesn't have focus"));
         }
 
-        let (read_pipe, mime_type) = match ty {
+        let pipes = match ty {
             SelectionTarget::Clipboard => {
                 let selection = self
                     .seat
@@ -139,23 +155,23 @@ impl State {
                     .and_then(|data| data.data().selection_offer())
                     .ok_or_else(|| Error::new(ErrorKind::Other, "selection is empty"))?;
 
-                let mime_type = selection
-                    .with_mime_types(MimeType::find_allowed)
-                    .ok_or_else(|| {
-                        Error::new(ErrorKind::NotFound, "supported mime-type is not found")
-                    })?;
-
-                (
-                    selection
-                        .receive(mime_type.to_string())
-                        .map_err(|err| match err {
-                            DataOfferError::InvalidReceive => {
-                                Error::new(ErrorKind::Other, "offer is not ready yet")
-                            }
-                            DataOfferError::Io(err) => err,
-                        })?,
-                    mime_type,
-                )
+                content_mime_types(&selection.with_mime_types(<[String]>::to_vec))
+                    .into_iter()
+                    .map(|mime_type| {
+                        let read_pipe =
+                            selection
+                                .receive(mime_type.to_string())
+                                .map_err(|err| match err {
+                                    DataOfferError::InvalidReceive => {
+                                        Error::new(ErrorKind::Other, "offer is not ready yet")
+                                    }
+                                    DataOfferError::Io(err) => err,
+                                })?;
+                        Ok((mime_type, unsafe {
+                            File::from_raw_fd(read_pipe.into_raw_fd())
+                        }))
+                    })
+                    .collect::<Result<Vec<_>>>()?
             }
             SelectionTarget::Primary => {
                 let selection = self
@@ -165,80 +181,147 @@ impl State {
                     .and_then(|data| data.data().selection_offer())
                     .ok_or_else(|| Error::new(ErrorKind::Other, "selection is empty"))?;
 
-                let mime_type = selection
-                    .with_mime_types(MimeType::find_allowed)
-                    .ok_or_else(|| {
-                        Error::new(ErrorKind::NotFound, "supported mime-type is not found")
-                    })?;
+                content_mime_types(&selection.with_mime_types(<[String]>::to_vec))
+                    .into_iter()
+                    .map(|mime_type| {
+                        let read_pipe = selection.receive(mime_type.to_string())?;
+                        Ok((mime_type, unsafe {
+                            File::from_raw_fd(read_pipe.into_raw_fd())
+                        }))
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            }
+        };
+
+        if pipes.is_empty() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                "supported mime-type is not found",
+            ));
+        }
+        self.read_selection(pipes, done)
+    }
 
-                (selection.receive(mime_type.to_string())?, mime_type)
+    /// Reads a selection the data control device told about into the
+    /// history, unless it offers one of the `exclude` mime types
+    pub fn selection_changed(
+        &mut self,
+        ty: SelectionTarget,
+        offer: Option<ZwlrDataControlOfferV1>,
+        exclude: &[String],
+    ) {
+        let Some(offer) = offer else {
+            return;
+        };
+        let mime_types = {
+            let mime_types = offer.data::<Mutex<Vec<String>>>().unwrap().lock().unwrap();
+            if mime_types.iter().any(|mime| exclude.contains(mime)) {
+                Vec::new()
+            } else {
+                content_mime_types(&mime_types)
             }
         };
 
-        // Mark FD as non-blocking so we won't block ourselves.
-        unsafe {
-            set_non_blocking(read_pipe.as_raw_fd())?;
+        let pipes = mime_types
+            .into_iter()
+            .map(|mime_type| {
+                let (read, write) = nix::unistd::pipe().unwrap();
+                offer.receive(mime_type.to_string(), write.as_fd());
+                // Our end of `write` closes here, only the other end writes
+                (mime_type, File::from(read))
+            })
+            .collect::<Vec<_>>();
+        offer.destroy();
+
+        if !pipes.is_empty() {
+            let _ = self.read_selection(pipes, move |state, selection| {
+                state.clipboard_state.history.record(ty, &selection)
+            });
         }
+    }
 
-        let qh = self.queue_handle.clone();
-        let mut reader_buffer = [0; 4096];
-        let mut content = Vec::new();
-        let _ = self
-            .loop_handle
-            .insert_source(read_pipe, move |_, file, state| {
-                let file = unsafe { file.get_mut() };
-                loop {
-                    match file.read(&mut reader_buffer) {
-                        Ok(0) => {
-                            let utf8 = String::from_utf8_lossy(&content);
-                            let content = match utf8 {
-                                Cow::Borrowed(_) => {
-                                    // Don't clone the read data.
-                                    let mut to_send = Vec::new();
-                                    mem::swap(&mut content, &mut to_send);
-                                    String::from_utf8(to_send).unwrap()
-                                }
-                                Cow::Owned(content) => content,
-                            };
-
-                            // Post-process the content according to mime type.
-                            let content = match mime_type {
-                                MimeType::TextPlainUtf8 | MimeType::TextPlain => {
-                                    normalize_to_lf(content)
-                                }
-                                MimeType::Utf8String => content,
-                            };
-
-                            string.borrow_mut().push_str(&content);
-                            state.layout_applauncher();
-                            state
-                                .monitors
-                                .values_mut()
-                                .find(|o| o.selected)
-                                .unwrap()
-                                .output
-                                .frame(&qh);
-                            break PostAction::Remove;
-                        }
-                        Ok(n) => content.extend_from_slice(&reader_buffer[..n]),
-                        Err(err) if err.kind() == ErrorKind::WouldBlock => {
-                            break PostAction::Continue
-                        }
-                        err @ Err(_) => {
-                            err.unwrap();
-                        }
+    /// Reads every pipe into a selection, `done` gets it once the last one
+    /// is read. Types that fail or are too big are left out.
+    fn read_selection(
+        &mut self,
+        pipes: Vec<(MimeType, File)>,
+        done: impl FnOnce(&mut SimpleLayer, Selection) + 'static,
+    ) -> Result<()> {
+        let pending = Rc::new(RefCell::new(PendingSelection {
+            remaining: pipes.len(),
+            offers: Vec::new(),
+            done: Some(Box::new(done)),
+        }));
+
+        for (position, (mime_type, file)) in pipes.into_iter().enumerate() {
+            // Mark FD as non-blocking so we won't block ourselves.
+            unsafe {
+                set_non_blocking(file.as_raw_fd())?;
+            }
+
+            let pending = Rc::clone(&pending);
+            let mut mime_type = Some(mime_type);
+            let mut reader_buffer = [0; 4096];
+            let mut content = Vec::new();
+            let _ = self.loop_handle.insert_source(
+                Generic::new(file, Interest::READ, Mode::Level),
+                move |_, file, state| {
+                    let file = unsafe { file.get_mut() };
+                    let read = loop {
+                        match file.read(&mut reader_buffer) {
+                            Ok(0) => break true,
+                            Ok(_) if content.len() > MAX_PAYLOAD => break false,
+                            Ok(n) => content.extend_from_slice(&reader_buffer[..n]),
+                            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                                return Ok(PostAction::Continue)
+                            }
+                            // The source went away before it was done
+                            Err(_) => break false,
+                        };
                     };
-                }
-            });
+
+                    let mut pending = pending.borrow_mut();
+                    if read {
+                        let content = Rc::from(mem::take(&mut content));
+                        pending
+                            .offers
+                            .push((position, mime_type.take().unwrap(), content));
+                    }
+                    pending.remaining -= 1;
+                    if pending.remaining == 0 {
+                        let mut offers = mem::take(&mut pending.offers);
+                        offers.sort_by_key(|(position, _, _)| *position);
+                        let done = pending.done.take().unwrap();
+                        drop(pending);
+                        done(
+                            state,
+                            Selection {
+                                offers: offers
+                                    .into_iter()
+                                    .map(|(_, mime_type, content)| (mime_type, content))
+                                    .collect(),
+                            },
+                        );
+                    }
+                    Ok(PostAction::Remove)
+                },
+            );
+        }
 
         Ok(())
     }
 
     fn send_request(&mut self, ty: SelectionTarget, write_pipe: WritePipe, mime: String) {
-        // We can only send strings, so don't do anything with the mime-type.
-        if MimeType::find_allowed(&[mime]).is_none() {
+        // Don't access the content on the state directly, since it could change during
+        // the send.
+        let selection = match ty {
+            SelectionTarget::Clipboard => &self.data_selection_content,
+            SelectionTarget::Primary => &self.primary_selection_content,
+        };
+        // Only send what was offered.
+        let Some(contents) = selection.get(&mime).cloned() else {
             return;
-        }
+        };
 
         // Mark FD as non-blocking so we won't block ourselves.
         unsafe {
@@ -247,13 +330,6 @@ impl State {
             }
         }
 
-        // Don't access the content on the state directly, since it could change during
-        // the send.
-        let contents = match ty {
-            SelectionTarget::Clipboard => self.data_selection_content.clone(),
-            SelectionTarget::Primary => self.primary_selection_content.clone(),
-        };
-
         let mut written = 0;
         let _ = self
             .loop_handle
@@ -303,6 +379,13 @@ impl State {
                         .as_ref()
                         .map(|mgr| mgr.get_selection_device(qh, &seat));
                 }
+
+                if self.seat.data_control_device.is_none() {
+                    self.seat.data_control_device = self
+                        .data_control_manager
+                        .as_ref()
+                        .map(|mgr| mgr.get_data_device(&seat, qh, ()));
+                }
             }
             _ => (),
         }
@@ -313,6 +396,9 @@ impl State {
             Capability::Keyboard => {
                 self.seat.data_device = None;
                 self.seat.primary_device = None;
+                if let Some(device) = self.seat.data_control_device.take() {
+                    device.destroy();
+                }
             }
             _ => (),
         }
@@ -452,6 +538,25 @@ impl State {
     }
 }
 
+/// A selection that's still being read, a pipe per mime type.
+struct PendingSelection {
+    remaining: usize,
+    /// What was read with the position it was offered at.
+    offers: Vec<(usize, MimeType, Rc<[u8]>)>,
+    done: Option<Box<dyn FnOnce(&mut SimpleLayer, Selection)>>,
+}
+
+/// The offered mime types worth reading, text only once.
+fn content_mime_types(offered: &[String]) -> Vec<MimeType> {
+    let text = MimeType::find_allowed(offered);
+    offered
+        .iter()
+        .map(|mime_type| MimeType::from(mime_type.as_str()))
+        .filter(|mime_type| mime_type.is_content() && !mime_type.is_text())
+        .chain(text)
+        .collect()
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum SelectionTarget {
     /// The target is clipboard selection.
@@ -461,16 +566,17 @@ pub enum SelectionTarget {
 }
 
 #[derive(Debug, Default)]
-struct ClipboardSeatState {
+pub(super) struct ClipboardSeatState {
     data_device: Option<DataDevice>,
     primary_device: Option<PrimarySelectionDevice>,
+    pub(super) data_control_device: Option<ZwlrDataControlDeviceV1>,
     has_focus: bool,
 
     /// The latest serial used to set the selection content.
     latest_serial: u32,
 }
 
-unsafe fn set_non_blocking(raw_fd: RawFd) -> std::io::Result<()> {
+pub(super) unsafe fn set_non_blocking(raw_fd: RawFd) -> std::io::Result<()> {
     let flags = nix::fcntl::fcntl(raw_fd, FcntlArg::F_GETFL).unwrap();
 
     if flags < 0 {
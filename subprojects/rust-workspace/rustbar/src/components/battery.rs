@@ -3,19 +3,41 @@ use std::{
     fmt::{Debug, Display},
     io::{BufWriter, Write},
     os::unix::net::UnixStream,
-    time::Duration,
+    path::Path,
+    process::Command,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
+use calloop_dbus::SyncDBusSource;
 use dbus::arg::RefArg;
+use iced_tiny_skia::{
+    core::{alignment::Horizontal, Color, Rectangle, Size},
+    Primitive,
+};
+use smithay_client_toolkit::seat::pointer::BTN_LEFT;
+use tiny_skia::{FillRule, Paint, PathBuilder, PixmapMut, Stroke, StrokeDash, Transform};
 
+use super::{info_row, Block, BlockKind, Handles, Response, Segment, ROW_HEIGHT};
 use crate::{
     add_match,
     upower::{
         self, BatteryState, BatteryType, OrgFreedesktopUPower, OrgFreedesktopUPowerDevice,
         WarningLevel,
     },
+    BarSettings,
 };
 
+/// Height of the charge graph in the info popup, it goes below the first line
+const GRAPH_HEIGHT: f32 = 96.0;
+/// How far back the graph goes, in seconds
+const HISTORY_SPAN: u32 = 24 * 60 * 60;
+/// How many points UPower is asked for, about one per pixel of the graph
+const HISTORY_RESOLUTION: u32 = 256;
+/// The popup fetches its details again when they're older than this
+const DETAILS_MAX_AGE: Duration = Duration::from_secs(60);
+/// Where the kernel has the batteries' charge thresholds
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
 macro_rules! match_bat_type {
     ($shared_data:expr) => {
         match $shared_data.bat_type {
@@ -90,12 +112,41 @@ macro_rules! match_battery {
 pub struct BatteryBlock {
     bat_devices: HashMap<dbus::Path<'static>, BatteryDevice>,
     match_handles: [dbus::channel::Token; 3],
-    pub xs_at: Vec<f32>,
-    pub widths: Vec<f32>,
+    system_connection: &'static SyncDBusSource<()>,
+    /// What the popup last showed, UPower is only asked again for another
+    /// device or after [`DETAILS_MAX_AGE`]
+    details: Option<Details>,
+    /// Discharging batteries at or below this percentage are low, even
+    /// when UPower doesn't think so yet
+    pub low: f64,
+    /// The same as `low` for critical
+    pub critical: f64,
+}
+
+/// What `battery threshold` sets for every battery that has charge
+/// thresholds
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ChargeThreshold {
+    /// Turns UPower's thresholds on or off, off without UPower means
+    /// charging to 100% again
+    Enabled(bool),
+    /// Starts charging below `start` and stops at `end`, in percent.
+    /// UPower puts its own back when it turns them on again
+    Custom { start: u32, end: u32 },
+}
+
+/// A battery that just got to a worse warning level
+pub struct BatteryWarning {
+    pub level: WarningLevel,
+    pub percentage: u32,
+    /// Whether it's one of the computer's batteries, not a mouse or the like
+    pub battery: bool,
+    /// What the bar shows for it
+    pub text: String,
 }
 
 impl BatteryBlock {
-    pub fn new(system_connection: &calloop_dbus::SyncDBusSource<()>) -> Self {
+    pub fn new(system_connection: &'static SyncDBusSource<()>, low: f64, critical: f64) -> Self {
         let match_handles = [
             add_match!(system_connection, "org.freedesktop.UPower"),
             add_match!(system_connection, "org.freedesktop.UPower", "DeviceAdded"),
@@ -120,65 +171,20 @@ impl BatteryBlock {
         Self {
             bat_devices: shared_data,
             match_handles,
-            xs_at: Vec::new(),
-            widths: Vec::new(),
+            system_connection,
+            details: None,
+            low,
+            critical,
         }
     }
 
-    pub fn unregister(&self, system_connection: &calloop_dbus::SyncDBusSource<()>) {
-        for t in self.match_handles {
-            system_connection.remove_match(t).unwrap();
-        }
-    }
-
-    pub fn fmt(&'static self) -> impl Iterator<Item = (String, WarningLevel)> {
-        self.bat_devices.values().map(|i| {
-            let mut f = String::new();
-            std::fmt::Write::write_fmt(
-                &mut f,
-                format_args!(
-                    " {}{}{}% ",
-                    match_bat_type!(i),
-                    match_battery!(i),
-                    i.percentage
-                ),
-            )
-            .unwrap();
-            if i.state != BatteryState::Unknown {
-                std::fmt::Write::write_fmt(&mut f, format_args!("{:?}{}", i.time, i.time)).unwrap();
-            }
-            (f, i.warning_level)
-        })
-    }
-
-    pub fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
-        self.bat_devices.values().try_for_each(|i| {
-            write!(
-                f,
-                concat!(include_str!("../table.txt"), "{}%\n"),
-                match match_bat_type!(i) {
-                    "" => match_battery!(i),
-                    t => t,
-                },
-                i.percentage,
-            )?;
-            if i.state != BatteryState::Unknown {
-                write!(
-                    f,
-                    concat!(include_str!("../table.txt"), "{:?}\n"),
-                    i.time, i.time
-                )
-            } else {
-                Ok(())
-            }
-        })
-    }
-
+    /// Returns a warning when the device got low, critical or reached
+    /// UPower's action level with this change
     pub fn query_battery(
         &mut self,
         path: dbus::Path<'static>,
         property: upower::OrgFreedesktopDBusPropertiesPropertiesChanged,
-    ) {
+    ) -> Option<BatteryWarning> {
         if let Some(device) = self.bat_devices.get_mut(&path) {
             if let Some(percentage) = property.changed_properties.get("Percentage") {
                 device.percentage = percentage.as_f64().unwrap().floor() as u32;
@@ -204,7 +210,29 @@ impl BatteryBlock {
             if let Some(warning_level) = property.changed_properties.get("WarningLevel") {
                 device.warning_level = WarningLevel::from(warning_level.as_u64().unwrap() as u32);
             }
+            if let Some(enabled) = property.changed_properties.get("ChargeThresholdEnabled") {
+                device.threshold_enabled = enabled.as_u64().unwrap() != 0;
+                device.threshold = read_threshold(&device.native_path);
+            }
+
+            let level = warning_level(device, self.low, self.critical);
+            let worse = level as u32 > device.warned as u32;
+            device.warned = level;
+            if worse && level as u32 >= WarningLevel::Low as u32 {
+                return Some(BatteryWarning {
+                    level,
+                    percentage: device.percentage,
+                    battery: device.bat_type == BatteryType::Battery,
+                    text: format!(
+                        "{}{}{}% battery left",
+                        match_bat_type!(device),
+                        match_battery!(device),
+                        device.percentage
+                    ),
+                });
+            }
         }
+        None
     }
 
     pub fn device_added(
@@ -225,6 +253,464 @@ impl BatteryBlock {
         let battery: upower::OrgFreedesktopUPowerDeviceRemoved = event.read_all().unwrap();
         self.bat_devices.remove(&battery.device);
     }
+
+    /// The details of the device shown as `segment`, if they were
+    /// fetched for it
+    fn details(&self, segment: usize) -> Option<&Details> {
+        let path = self.bat_devices.keys().nth(segment)?;
+        self.details
+            .as_ref()
+            .filter(|details| details.path == *path)
+    }
+
+    /// Sets the charge thresholds of every battery that has them, with
+    /// UPower when it can and the `charge-threshold` helper otherwise.
+    /// The batteries that fail don't keep the others from being set
+    pub fn set_charge_threshold(&mut self, threshold: ChargeThreshold) -> Result<(), String> {
+        let mut found = false;
+        let mut errors = Vec::new();
+        for (path, device) in self
+            .bat_devices
+            .iter_mut()
+            .filter(|(_, device)| device.has_threshold())
+        {
+            found = true;
+            if let Err(e) = device.set_threshold(self.system_connection, path, threshold) {
+                errors.push(format!("{}: {}", device.native_path, e));
+            }
+        }
+        if !found {
+            Err("no battery has charge thresholds".to_owned())
+        } else if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+/// The start and end charge thresholds of the battery at `native_path`
+/// in sysfs, batteries that only have an end start at 0
+fn read_threshold(native_path: &str) -> Option<(u32, u32)> {
+    let dir = Path::new(POWER_SUPPLY_DIR).join(Path::new(native_path).file_name()?);
+    let read = |name: &str| -> Option<u32> {
+        std::fs::read_to_string(dir.join(name))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    let end = read("charge_control_end_threshold")?;
+    Some((read("charge_control_start_threshold").unwrap_or(0), end))
+}
+
+/// Writes the thresholds with the `charge-threshold` helper, doas lets
+/// wheel run it without a password
+fn write_threshold(native_path: &str, start: u32, end: u32) -> Result<(), String> {
+    let battery = Path::new(native_path)
+        .file_name()
+        .ok_or_else(|| format!("no battery at {}", native_path))?;
+    let status = Command::new("doas")
+        .arg("-n")
+        .arg("charge-threshold")
+        .arg(battery)
+        .arg(start.to_string())
+        .arg(end.to_string())
+        .status()
+        .map_err(|e| format!("couldn't run doas: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("charge-threshold {}", status))
+    }
+}
+
+/// What the popup and the table show for `threshold`
+fn threshold_text(threshold: (u32, u32)) -> String {
+    match threshold {
+        (0, 100) => "Off".to_owned(),
+        (0, end) => format!("Up to {}%", end),
+        (start, end) => format!("{}-{}%", start, end),
+    }
+}
+
+/// What the popup shows about a device besides what's on the bar
+struct Details {
+    path: dbus::Path<'static>,
+    fetched: Instant,
+    /// Time, percentage and state of the charge in the last
+    /// [`HISTORY_SPAN`], the oldest first
+    history: Vec<(u32, f64, u32)>,
+    /// In W
+    energy_rate: f64,
+    /// How much of its design capacity the battery still has, in percent
+    capacity: f64,
+    /// -1 when the battery doesn't tell
+    charge_cycles: i32,
+}
+
+impl Details {
+    fn fetch(proxy: dbus::blocking::Proxy<&SyncDBusSource<()>>) -> Self {
+        let mut history = proxy
+            .get_history("charge", HISTORY_SPAN, HISTORY_RESOLUTION)
+            .unwrap_or_default();
+        history.sort_by_key(|(time, _, _)| *time);
+        Self {
+            history,
+            energy_rate: proxy.energy_rate().unwrap_or_default(),
+            capacity: proxy.capacity().unwrap_or_default(),
+            charge_cycles: proxy.charge_cycles().unwrap_or(-1),
+            fetched: Instant::now(),
+            path: proxy.path.into_static(),
+        }
+    }
+}
+
+/// Where the graph goes in the popup
+fn graph_bounds(bar_settings: &BarSettings, logical_size: Size<f32>) -> Rectangle {
+    Rectangle {
+        x: bar_settings.padding_x,
+        y: bar_settings.padding_y + ROW_HEIGHT,
+        width: logical_size.width - bar_settings.padding_x * 2.0,
+        height: GRAPH_HEIGHT,
+    }
+}
+
+/// Where `percentage` at `time` goes in `bounds`, `now` is on the right
+/// edge and [`HISTORY_SPAN`] before it on the left
+fn graph_point(time: u32, percentage: f64, now: u32, bounds: Rectangle) -> (f32, f32) {
+    let age = now.saturating_sub(time).min(HISTORY_SPAN) as f32 / HISTORY_SPAN as f32;
+    (
+        bounds.x + bounds.width * (1.0 - age),
+        bounds.y + bounds.height * (1.0 - percentage.clamp(0.0, 100.0) as f32 / 100.0),
+    )
+}
+
+/// The points of `history` that are in the graph, see [`graph_point`]
+fn graph_points(history: &[(u32, f64, u32)], now: u32, bounds: Rectangle) -> Vec<(f32, f32)> {
+    history
+        .iter()
+        .filter(|(time, _, _)| now.saturating_sub(*time) <= HISTORY_SPAN)
+        .map(|&(time, percentage, _)| graph_point(time, percentage, now, bounds))
+        .collect()
+}
+
+/// A paint for drawing on the buffer directly, which is BGRA like for
+/// iced
+fn paint(color: Color, alpha: f32) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.anti_alias = true;
+    paint.set_color(
+        tiny_skia::Color::from_rgba(color.b, color.g, color.r, color.a * alpha).unwrap(),
+    );
+    paint
+}
+
+impl Block for BatteryBlock {
+    fn kind(&self) -> BlockKind {
+        BlockKind::Battery
+    }
+
+    fn fmt(&self, _output: &str, segments: &mut Vec<Segment>) {
+        for i in self.bat_devices.values() {
+            let mut f = String::new();
+            std::fmt::Write::write_fmt(
+                &mut f,
+                format_args!(
+                    " {}{}{}% ",
+                    match_bat_type!(i),
+                    match_battery!(i),
+                    i.percentage
+                ),
+            )
+            .unwrap();
+            if i.state != BatteryState::Unknown {
+                std::fmt::Write::write_fmt(&mut f, format_args!("{:?}{}", i.time, i.time)).unwrap();
+            }
+            segments.push(Segment {
+                urgent: matches!(
+                    warning_level(i, self.low, self.critical),
+                    WarningLevel::Low | WarningLevel::Critical | WarningLevel::Action
+                ),
+                ..Segment::new(f)
+            });
+        }
+    }
+
+    fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
+        self.bat_devices.values().try_for_each(|i| {
+            write!(
+                f,
+                concat!(include_str!("../table.txt"), "{}%\n"),
+                match match_bat_type!(i) {
+                    "" => match_battery!(i),
+                    t => t,
+                },
+                i.percentage,
+            )?;
+            if i.state != BatteryState::Unknown {
+                write!(
+                    f,
+                    concat!(include_str!("../table.txt"), "{:?}\n"),
+                    i.time, i.time
+                )?;
+            }
+            if let Some(threshold) = i.threshold {
+                write!(
+                    f,
+                    concat!(include_str!("../table.txt"), "{}\n"),
+                    "󰚥 ",
+                    threshold_text(threshold)
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    fn json(&self) -> serde_json::Value {
+        let mut devices: Vec<_> = self.bat_devices.iter().collect();
+        devices.sort_by(|a, b| a.0.cmp(b.0));
+        serde_json::json!({
+            "devices": devices
+                .into_iter()
+                .map(|(path, i)| {
+                    serde_json::json!({
+                        "path": path.to_string(),
+                        "type": format!("{:?}", i.bat_type),
+                        "percentage": i.percentage,
+                        "state": format!("{:?}", i.state),
+                        "time_to_empty": match i.time {
+                            TimeTo::Empty(seconds) => Some(seconds),
+                            _ => None,
+                        },
+                        "time_to_full": match i.time {
+                            TimeTo::Full(seconds) => Some(seconds),
+                            _ => None,
+                        },
+                        "warning_level": format!("{:?}", i.warning_level),
+                        "charge_threshold": i.threshold.map(|(start, end)| {
+                            serde_json::json!({ "start": start, "end": end })
+                        }),
+                        "charge_threshold_enabled": i.threshold_enabled,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Fetches the details from UPower if they aren't there yet or are
+    /// too old, drawing the popup doesn't wait for UPower
+    fn info_shown(&mut self, segment: usize) {
+        let Some(path) = self.bat_devices.keys().nth(segment) else {
+            return;
+        };
+        let fresh = self
+            .details(segment)
+            .is_some_and(|details| details.fetched.elapsed() <= DETAILS_MAX_AGE);
+        if !fresh {
+            let proxy = self.system_connection.with_proxy(
+                "org.freedesktop.UPower",
+                path.clone(),
+                Duration::from_secs(5),
+            );
+            self.details = Some(Details::fetch(proxy));
+        }
+    }
+
+    fn info_size(&self, segment: usize) -> Option<(u32, u32)> {
+        let device = self.bat_devices.values().nth(segment)?;
+        let rows = if device.has_threshold() { 6.0 } else { 5.0 };
+        Some((256, (ROW_HEIGHT * rows + GRAPH_HEIGHT) as u32))
+    }
+
+    fn info(
+        &self,
+        segment: usize,
+        backend: &iced_tiny_skia::Backend,
+        bar_settings: &BarSettings,
+        logical_size: Size<f32>,
+    ) -> Vec<Primitive> {
+        let (Some(device), Some(details)) = (
+            self.bat_devices.values().nth(segment),
+            self.details(segment),
+        ) else {
+            return Vec::new();
+        };
+        let text = info_row(backend, bar_settings, logical_size);
+        let left = bar_settings.padding_x;
+        let right = logical_size.width - bar_settings.padding_x;
+        let mut y = bar_settings.padding_y + ROW_HEIGHT / 2.0;
+
+        let mut primitives = vec![
+            text(
+                format!(
+                    "{}{}{}% {:?}",
+                    match_bat_type!(device),
+                    match_battery!(device),
+                    device.percentage,
+                    device.state
+                ),
+                left,
+                y,
+                Horizontal::Left,
+            ),
+            text(
+                format!("{:?}", device.time).trim_end().to_owned(),
+                right,
+                y,
+                Horizontal::Right,
+            ),
+        ];
+
+        y += ROW_HEIGHT + GRAPH_HEIGHT;
+        primitives.push(text("24 hours ago".to_owned(), left, y, Horizontal::Left));
+        primitives.push(text("now".to_owned(), right, y, Horizontal::Right));
+
+        let mut rows = vec![
+            ("Energy rate", format!("{:.1} W", details.energy_rate)),
+            ("Health", format!("{:.0}%", details.capacity)),
+            (
+                "Charge cycles",
+                if details.charge_cycles < 0 {
+                    "Unknown".to_owned()
+                } else {
+                    details.charge_cycles.to_string()
+                },
+            ),
+        ];
+        if device.has_threshold() {
+            rows.push((
+                "Charge threshold",
+                device
+                    .threshold
+                    .filter(|_| !device.threshold_supported || device.threshold_enabled)
+                    .map_or("Off".to_owned(), threshold_text),
+            ));
+        }
+        for (name, value) in rows {
+            y += ROW_HEIGHT;
+            primitives.push(text(name.to_owned(), left, y, Horizontal::Left));
+            primitives.push(text(value, right, y, Horizontal::Right));
+        }
+
+        primitives
+    }
+
+    fn info_click(
+        &mut self,
+        segment: usize,
+        position: (f64, f64),
+        button: u32,
+        bar_settings: &BarSettings,
+    ) -> Response {
+        if button != BTN_LEFT {
+            return Response::Ignored;
+        }
+        let Some((path, device)) = self.bat_devices.iter_mut().nth(segment) else {
+            return Response::Ignored;
+        };
+        // The threshold is the last line, UPower turns it on and off
+        let top = bar_settings.padding_y + ROW_HEIGHT * 5.0 + GRAPH_HEIGHT;
+        let y = position.1 as f32;
+        if !device.threshold_supported || !(top..top + ROW_HEIGHT).contains(&y) {
+            return Response::Ignored;
+        }
+        let enabled = !device.threshold_enabled;
+        match device.set_threshold(
+            self.system_connection,
+            path,
+            ChargeThreshold::Enabled(enabled),
+        ) {
+            Ok(()) => Response::Redraw,
+            Err(e) => Response::Notify(format!("Couldn't change the charge threshold: {}", e)),
+        }
+    }
+
+    fn draw_info(
+        &self,
+        segment: usize,
+        pixmap: &mut PixmapMut,
+        transform: Transform,
+        bar_settings: &BarSettings,
+        logical_size: Size<f32>,
+    ) {
+        let (Some(device), Some(details)) = (
+            self.bat_devices.values().nth(segment),
+            self.details(segment),
+        ) else {
+            return;
+        };
+        let color = bar_settings.color_active.0;
+        let bounds = graph_bounds(bar_settings, logical_size);
+
+        if let Some(frame) =
+            tiny_skia::Rect::from_xywh(bounds.x, bounds.y, bounds.width, bounds.height)
+        {
+            let frame = PathBuilder::from_rect(frame);
+            pixmap.stroke_path(
+                &frame,
+                &paint(color, 0.3),
+                &Stroke::default(),
+                transform,
+                None,
+            );
+        }
+
+        let mut low = PathBuilder::new();
+        let (_, low_y) = graph_point(0, self.low, 0, bounds);
+        low.move_to(bounds.x, low_y);
+        low.line_to(bounds.x + bounds.width, low_y);
+        if let Some(low) = low.finish() {
+            let stroke = Stroke {
+                dash: StrokeDash::new(vec![4.0, 4.0], 0.0),
+                ..Stroke::default()
+            };
+            pixmap.stroke_path(&low, &paint(color, 0.5), &stroke, transform, None);
+        }
+
+        // UPower only adds points when the charge changes, the graph goes
+        // on to where it's at now
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as u32;
+        let mut points = graph_points(&details.history, now, bounds);
+        points.push(graph_point(now, device.percentage as f64, now, bounds));
+        let bottom = bounds.y + bounds.height;
+
+        let mut area = PathBuilder::new();
+        area.move_to(points[0].0, bottom);
+        let mut line = PathBuilder::new();
+        line.move_to(points[0].0, points[0].1);
+        for &(x, y) in &points {
+            area.line_to(x, y);
+            line.line_to(x, y);
+        }
+        area.line_to(points[points.len() - 1].0, bottom);
+        area.close();
+        if let Some(area) = area.finish() {
+            pixmap.fill_path(
+                &area,
+                &paint(color, 0.3),
+                FillRule::Winding,
+                transform,
+                None,
+            );
+        }
+        if let Some(line) = line.finish() {
+            let stroke = Stroke {
+                width: 2.0,
+                ..Stroke::default()
+            };
+            pixmap.stroke_path(&line, &paint(color, 1.0), &stroke, transform, None);
+        }
+    }
+
+    fn unregister(&self, handles: &Handles) {
+        for t in self.match_handles {
+            handles.system_connection.remove_match(t).unwrap();
+        }
+    }
 }
 
 pub struct BatteryDevice {
@@ -233,9 +719,79 @@ pub struct BatteryDevice {
     bat_type: BatteryType,
     percentage: u32,
     pub warning_level: WarningLevel,
+    /// The level the device was at last time it changed
+    warned: WarningLevel,
+    /// Its name in sysfs, like `BAT0`
+    native_path: String,
+    /// Start and end charge thresholds from sysfs, see [`read_threshold`]
+    threshold: Option<(u32, u32)>,
+    /// Whether UPower can turn the thresholds on and off
+    threshold_supported: bool,
+    threshold_enabled: bool,
+}
+
+/// UPower's warning level for `device`, made Low or Critical when it's
+/// a battery of the computer that's discharging and at or below `low` or
+/// `critical`. Other devices only have UPower's
+fn warning_level(device: &BatteryDevice, low: f64, critical: f64) -> WarningLevel {
+    let percentage = device.percentage as f64;
+    let level =
+        if device.bat_type != BatteryType::Battery || device.state != BatteryState::Discharging {
+            WarningLevel::None
+        } else if percentage <= critical {
+            WarningLevel::Critical
+        } else if percentage <= low {
+            WarningLevel::Low
+        } else {
+            WarningLevel::None
+        };
+    if level as u32 > device.warning_level as u32 {
+        level
+    } else {
+        device.warning_level
+    }
 }
 
 impl BatteryDevice {
+    fn has_threshold(&self) -> bool {
+        self.threshold.is_some() || self.threshold_supported
+    }
+
+    /// Sets this battery's charge thresholds, see
+    /// [`BatteryBlock::set_charge_threshold`]
+    fn set_threshold(
+        &mut self,
+        system_connection: &SyncDBusSource<()>,
+        path: &dbus::Path<'static>,
+        threshold: ChargeThreshold,
+    ) -> Result<(), String> {
+        match threshold {
+            ChargeThreshold::Enabled(enabled) if self.threshold_supported => {
+                let proxy = system_connection.with_proxy(
+                    "org.freedesktop.UPower",
+                    path.clone(),
+                    Duration::from_secs(5),
+                );
+                proxy
+                    .enable_charge_threshold(enabled)
+                    .map_err(|e| e.to_string())?;
+                self.threshold_enabled = enabled;
+            }
+            ChargeThreshold::Enabled(true) => {
+                return Err(
+                    "UPower has no thresholds for it, set them with `battery threshold <start> <end>`"
+                        .to_owned(),
+                );
+            }
+            ChargeThreshold::Enabled(false) => write_threshold(&self.native_path, 0, 100)?,
+            ChargeThreshold::Custom { start, end } => {
+                write_threshold(&self.native_path, start, end)?
+            }
+        }
+        self.threshold = read_threshold(&self.native_path);
+        Ok(())
+    }
+
     pub fn insert(
         proxy: dbus::blocking::Proxy<&calloop_dbus::SyncDBusSource<()>>,
         shared_data: &mut HashMap<dbus::Path<'static>, BatteryDevice>,
@@ -262,14 +818,20 @@ impl BatteryDevice {
                 }
                 _ => unsafe { core::hint::unreachable_unchecked() },
             }
+            let native_path = proxy.native_path().unwrap_or_default();
             shared_data.insert(
                 proxy.path.into_static(),
                 BatteryDevice {
                     percentage,
                     warning_level,
+                    warned: WarningLevel::None,
                     state,
                     time,
                     bat_type,
+                    threshold: read_threshold(&native_path),
+                    threshold_supported: proxy.charge_threshold_supported().unwrap_or(false),
+                    threshold_enabled: proxy.charge_threshold_enabled().unwrap_or(false),
+                    native_path,
                 },
             );
         }
@@ -315,3 +877,83 @@ impl Debug for TimeTo {
         }
     }
 }
+
+#[test]
+fn test_warning_level() {
+    let device = |state, percentage, warning_level| BatteryDevice {
+        state,
+        time: TimeTo::Unknown,
+        bat_type: BatteryType::Battery,
+        percentage,
+        warning_level,
+        warned: WarningLevel::None,
+        native_path: "BAT0".to_owned(),
+        threshold: None,
+        threshold_supported: false,
+        threshold_enabled: false,
+    };
+    let level = |device| warning_level(&device, 20.0, 5.0);
+    assert_eq!(
+        level(device(
+            BatteryState::Discharging,
+            50,
+            WarningLevel::Discharging
+        )),
+        WarningLevel::Discharging
+    );
+    assert_eq!(
+        level(device(
+            BatteryState::Discharging,
+            20,
+            WarningLevel::Discharging
+        )),
+        WarningLevel::Low
+    );
+    assert_eq!(
+        level(device(BatteryState::Discharging, 5, WarningLevel::Low)),
+        WarningLevel::Critical
+    );
+    assert_eq!(
+        level(device(BatteryState::Charging, 3, WarningLevel::None)),
+        WarningLevel::None
+    );
+    // UPower knows better when it's worse
+    assert_eq!(
+        level(device(BatteryState::Discharging, 15, WarningLevel::Action)),
+        WarningLevel::Action
+    );
+    let mouse = BatteryDevice {
+        bat_type: BatteryType::Mouse,
+        ..device(BatteryState::Discharging, 5, WarningLevel::Discharging)
+    };
+    assert_eq!(level(mouse), WarningLevel::Discharging);
+}
+
+#[test]
+fn test_graph_points() {
+    let bounds = Rectangle {
+        x: 10.0,
+        y: 20.0,
+        width: 200.0,
+        height: 100.0,
+    };
+    let now = 1_000_000;
+    let history = [
+        (now - HISTORY_SPAN - 60, 90.0, 2),
+        (now - HISTORY_SPAN, 100.0, 2),
+        (now - HISTORY_SPAN / 2, 50.0, 2),
+        (now, 0.0, 1),
+    ];
+    assert_eq!(
+        graph_points(&history, now, bounds),
+        [(10.0, 20.0), (110.0, 70.0), (210.0, 120.0)]
+    );
+    assert_eq!(graph_point(now, 150.0, now, bounds), (210.0, 20.0));
+}
+
+#[test]
+fn test_threshold_text() {
+    assert_eq!(threshold_text((0, 100)), "Off");
+    assert_eq!(threshold_text((0, 80)), "Up to 80%");
+    assert_eq!(threshold_text((40, 80)), "40-80%");
+}
@@ -0,0 +1,427 @@
+use std::{
+    collections::BTreeMap,
+    io::{BufWriter, Write},
+    os::unix::net::UnixStream,
+    time::Duration,
+};
+
+use calloop_dbus::SyncDBusSource;
+use dbus::{
+    arg::{self, PropMap},
+    channel::Sender,
+    Message,
+};
+use iced_tiny_skia::{
+    core::{alignment::Horizontal, text::Shaping, Size},
+    Primitive,
+};
+use smithay_client_toolkit::seat::pointer::BTN_LEFT;
+
+use super::{info_row, Block, BlockKind, Handles, Response, Segment, ROW_HEIGHT};
+use crate::{
+    add_match,
+    bluez::{self, OrgBluezAdapter1, OrgFreedesktopDBusObjectManager},
+    upower, BarSettings,
+};
+
+macro_rules! match_device_icon {
+    ($device:expr) => {
+        match $device.icon.as_str() {
+            "audio-headset" => "󰋎 ",
+            "audio-headphones" => "󰋋 ",
+            "audio-card" => "󰓃 ",
+            "input-mouse" => "󰍽 ",
+            "input-keyboard" => "󰌌 ",
+            "input-gaming" => "󰊖 ",
+            "input-tablet" => "󰓶 ",
+            "phone" => "󰄜 ",
+            "computer" => "󰇅 ",
+            "camera-photo" | "camera-video" => "󰄀 ",
+            "printer" => "󰐪 ",
+            _ => "󰂯 ",
+        }
+    };
+}
+
+#[derive(Default)]
+pub struct BluetoothDevice {
+    name: String,
+    icon: String,
+    paired: bool,
+    connected: bool,
+    battery: Option<u8>,
+}
+
+impl BluetoothDevice {
+    fn update(&mut self, properties: &PropMap) {
+        if let Some(name) = arg::prop_cast::<String>(properties, "Alias") {
+            self.name = name.clone();
+        }
+        if let Some(icon) = arg::prop_cast::<String>(properties, "Icon") {
+            self.icon = icon.clone();
+        }
+        if let Some(paired) = arg::prop_cast::<bool>(properties, "Paired") {
+            self.paired = *paired;
+        }
+        if let Some(connected) = arg::prop_cast::<bool>(properties, "Connected") {
+            self.connected = *connected;
+        }
+    }
+}
+
+pub struct BluetoothBlock {
+    adapter: Option<dbus::Path<'static>>,
+    powered: bool,
+    devices: BTreeMap<dbus::Path<'static>, BluetoothDevice>,
+    connection: &'static SyncDBusSource<()>,
+    match_handles: [dbus::channel::Token; 3],
+}
+
+impl BluetoothBlock {
+    pub fn new(system_connection: &'static SyncDBusSource<()>) -> Self {
+        let match_handles = [
+            add_match!(system_connection, "org.bluez"),
+            add_match!(
+                system_connection,
+                "org.bluez",
+                "org.freedesktop.DBus.ObjectManager",
+                "InterfacesAdded"
+            ),
+            add_match!(
+                system_connection,
+                "org.bluez",
+                "org.freedesktop.DBus.ObjectManager",
+                "InterfacesRemoved"
+            ),
+        ];
+
+        let mut block = Self {
+            adapter: None,
+            powered: false,
+            devices: BTreeMap::new(),
+            connection: system_connection,
+            match_handles,
+        };
+
+        // bluetoothd might not be running, the block stays empty until
+        // an adapter shows up
+        if let Ok(objects) = system_connection
+            .with_proxy("org.bluez", "/", Duration::from_secs(5))
+            .get_managed_objects()
+        {
+            for (path, interfaces) in objects {
+                block.add_interfaces(path, interfaces);
+            }
+        }
+
+        block
+    }
+
+    fn add_interfaces(
+        &mut self,
+        path: dbus::Path<'static>,
+        interfaces: std::collections::HashMap<String, PropMap>,
+    ) {
+        for (interface, properties) in interfaces {
+            match interface.as_str() {
+                "org.bluez.Adapter1" => {
+                    if self.adapter.is_none() {
+                        self.powered = arg::prop_cast::<bool>(&properties, "Powered")
+                            .copied()
+                            .unwrap_or_default();
+                        self.adapter = Some(path.clone());
+                    }
+                }
+                "org.bluez.Device1" => {
+                    self.devices
+                        .entry(path.clone())
+                        .or_default()
+                        .update(&properties);
+                }
+                "org.bluez.Battery1" => {
+                    self.devices.entry(path.clone()).or_default().battery =
+                        arg::prop_cast::<u8>(&properties, "Percentage").copied();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn interfaces_added(&mut self, event: Message) {
+        let added: bluez::OrgFreedesktopDBusObjectManagerInterfacesAdded =
+            event.read_all().unwrap();
+        self.add_interfaces(added.object, added.interfaces);
+    }
+
+    pub fn interfaces_removed(&mut self, event: Message) {
+        let removed: bluez::OrgFreedesktopDBusObjectManagerInterfacesRemoved =
+            event.read_all().unwrap();
+        for interface in removed.interfaces {
+            match interface.as_str() {
+                "org.bluez.Adapter1" => {
+                    if self.adapter.as_ref() == Some(&removed.object) {
+                        self.adapter = None;
+                        self.powered = false;
+                    }
+                }
+                "org.bluez.Device1" => {
+                    self.devices.remove(&removed.object);
+                }
+                "org.bluez.Battery1" => {
+                    if let Some(device) = self.devices.get_mut(&removed.object) {
+                        device.battery = None;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns true if the bar needs to be redrawn
+    pub fn query_properties(
+        &mut self,
+        path: dbus::Path<'static>,
+        property: upower::OrgFreedesktopDBusPropertiesPropertiesChanged,
+    ) -> bool {
+        match property.interface_name.as_str() {
+            "org.bluez.Adapter1" if self.adapter.as_ref() == Some(&path) => {
+                if let Some(powered) =
+                    arg::prop_cast::<bool>(&property.changed_properties, "Powered")
+                {
+                    self.powered = *powered;
+                    return true;
+                }
+                false
+            }
+            "org.bluez.Device1" => {
+                if let Some(device) = self.devices.get_mut(&path) {
+                    device.update(&property.changed_properties);
+                    true
+                } else {
+                    false
+                }
+            }
+            "org.bluez.Battery1" => {
+                if let Some(device) = self.devices.get_mut(&path) {
+                    if let Some(percentage) =
+                        arg::prop_cast::<u8>(&property.changed_properties, "Percentage")
+                    {
+                        device.battery = Some(*percentage);
+                        return true;
+                    }
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn toggle_powered(&self) {
+        if let Some(ref adapter) = self.adapter {
+            // Fails when the adapter is blocked by rfkill, there's
+            // nothing to do about it from here
+            let _ = self
+                .connection
+                .with_proxy("org.bluez", adapter, Duration::from_secs(5))
+                .set_powered(!self.powered);
+        }
+    }
+
+    /// Connecting can take a few seconds, so the reply isn't waited for.
+    /// The device's `Connected` property changes once it's done
+    fn toggle_connected(&self, path: &dbus::Path<'static>, device: &BluetoothDevice) {
+        let method = if device.connected {
+            "Disconnect"
+        } else {
+            "Connect"
+        };
+        let message =
+            Message::new_method_call("org.bluez", path, "org.bluez.Device1", method).unwrap();
+        let _ = self.connection.channel().send(message);
+    }
+
+    fn icon(&self) -> &'static str {
+        if !self.powered {
+            "󰂲 "
+        } else if self.devices.values().any(|d| d.connected) {
+            "󰂱 "
+        } else {
+            "󰂯 "
+        }
+    }
+
+    fn paired(&self) -> impl Iterator<Item = (&dbus::Path<'static>, &BluetoothDevice)> {
+        self.devices.iter().filter(|(_, d)| d.paired)
+    }
+}
+
+impl Block for BluetoothBlock {
+    fn kind(&self) -> BlockKind {
+        BlockKind::Bluetooth
+    }
+
+    fn fmt(&self, _output: &str, segments: &mut Vec<Segment>) {
+        if self.adapter.is_none() {
+            return;
+        }
+
+        let mut f = format!(" {}", self.icon());
+        for device in self.devices.values().filter(|d| d.connected) {
+            f.push_str(&device.name);
+            if let Some(battery) = device.battery {
+                std::fmt::Write::write_fmt(&mut f, format_args!(" {}%", battery)).unwrap();
+            }
+            f.push(' ');
+        }
+        segments.push(Segment {
+            shaping: Shaping::Advanced,
+            ..Segment::new(f)
+        });
+    }
+
+    fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
+        if self.adapter.is_none() {
+            return Ok(());
+        }
+
+        write!(
+            f,
+            concat!(include_str!("../table.txt"), "{}\n"),
+            self.icon(),
+            if self.powered { "On" } else { "Off" }
+        )?;
+        self.devices
+            .values()
+            .filter(|d| d.connected)
+            .try_for_each(|device| {
+                write!(
+                    f,
+                    concat!(include_str!("../table.txt"), "{}"),
+                    match_device_icon!(device),
+                    device.name
+                )?;
+                match device.battery {
+                    Some(battery) => writeln!(f, " {}%", battery),
+                    None => writeln!(f),
+                }
+            })
+    }
+
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "adapter": self.adapter.is_some(),
+            "powered": self.powered,
+            "devices": self
+                .devices
+                .values()
+                .map(|device| {
+                    serde_json::json!({
+                        "name": device.name,
+                        "icon": device.icon,
+                        "paired": device.paired,
+                        "connected": device.connected,
+                        "battery": device.battery,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    fn click(&mut self, _output: &str, _segment: usize, button: u32) -> Response {
+        if button == BTN_LEFT {
+            self.toggle_powered();
+        }
+        Response::Ignored
+    }
+
+    fn info_size(&self, _segment: usize) -> Option<(u32, u32)> {
+        Some((
+            256,
+            (ROW_HEIGHT * (self.paired().count() + 1) as f32) as u32,
+        ))
+    }
+
+    fn info(
+        &self,
+        _segment: usize,
+        backend: &iced_tiny_skia::Backend,
+        bar_settings: &BarSettings,
+        logical_size: Size<f32>,
+    ) -> Vec<Primitive> {
+        let text = info_row(backend, bar_settings, logical_size);
+        let left = bar_settings.padding_x;
+        let right = logical_size.width - bar_settings.padding_x;
+        let mut y = bar_settings.padding_y + ROW_HEIGHT / 2.0;
+
+        let mut primitives = vec![
+            text(
+                format!("{}Bluetooth", self.icon()),
+                left,
+                y,
+                Horizontal::Left,
+            ),
+            text(
+                if self.powered { "On" } else { "Off" }.to_owned(),
+                right,
+                y,
+                Horizontal::Right,
+            ),
+        ];
+
+        for (_, device) in self.paired() {
+            y += ROW_HEIGHT;
+            let mut name = format!("{}{}", match_device_icon!(device), device.name);
+            if let Some(battery) = device.battery {
+                std::fmt::Write::write_fmt(&mut name, format_args!(" {}%", battery)).unwrap();
+            }
+            primitives.push(text(name, left, y, Horizontal::Left));
+            primitives.push(text(
+                if device.connected {
+                    "Disconnect"
+                } else {
+                    "Connect"
+                }
+                .to_owned(),
+                right,
+                y,
+                Horizontal::Right,
+            ));
+        }
+
+        primitives
+    }
+
+    fn info_click(
+        &mut self,
+        _segment: usize,
+        position: (f64, f64),
+        button: u32,
+        bar_settings: &BarSettings,
+    ) -> Response {
+        if button != BTN_LEFT {
+            return Response::Ignored;
+        }
+
+        let row = (position.1 as f32 - bar_settings.padding_y) / ROW_HEIGHT;
+        if row < 0.0 {
+            return Response::Ignored;
+        }
+        match row as usize {
+            0 => self.toggle_powered(),
+            row => {
+                if self.powered {
+                    if let Some((path, device)) = self.paired().nth(row - 1) {
+                        self.toggle_connected(path, device);
+                    }
+                }
+            }
+        }
+        Response::Ignored
+    }
+
+    fn unregister(&self, handles: &Handles) {
+        for t in self.match_handles {
+            handles.system_connection.remove_match(t).unwrap();
+        }
+    }
+}
@@ -1,15 +1,21 @@
+use calloop_dbus::SyncDBusSource;
 use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
-use smithay_client_toolkit::reexports::{
-    calloop::{generic::Generic, Interest, LoopHandle, Mode, PostAction, RegistrationToken},
-    client::QueueHandle,
+use smithay_client_toolkit::reexports::calloop::{
+    generic::Generic, Interest, LoopHandle, Mode, PostAction, RegistrationToken,
 };
 use std::{
-    io::{BufWriter, Read, Write},
+    io::{BufWriter, Write},
     os::unix::net::UnixStream,
-    rc::Rc,
+    path::Path,
+    time::Duration,
 };
 
-use crate::SimpleLayer;
+use super::{Block, BlockKind, Handles, Response, Segment};
+use crate::{
+    backlight::{self, Backend, Backlight},
+    logind::OrgFreedesktopLogin1Session,
+    Frontend,
+};
 
 macro_rules! match_brightness {
     ($b:expr) => {
@@ -26,109 +32,157 @@ macro_rules! match_brightness {
 }
 
 pub struct BrightnessBlock {
-    brightness: usize,
-    max_brightness: f32,
-    handle: RegistrationToken,
-    pub x_at: f32,
-    pub width: f32,
+    backlights: Vec<Backlight>,
+    session: dbus::blocking::Proxy<'static, &'static SyncDBusSource<()>>,
+    /// Only registered if there's a backlight in sysfs to watch
+    handle: Option<RegistrationToken>,
 }
 
 impl BrightnessBlock {
-    pub fn new(handle: &LoopHandle<SimpleLayer>, qh: Rc<QueueHandle<SimpleLayer>>) -> Self {
-        let brightness_path = {
-            std::fs::read_dir("/sys/class/backlight")
-                .unwrap()
-                .next()
-                .map(|f| f.unwrap().path())
-        };
-
-        let mut max_brightness = 0.0;
-        let brightness: usize;
-        if let Some(ref brightness_path) = brightness_path {
-            max_brightness = std::fs::read_to_string(brightness_path.join("max_brightness"))
-                .unwrap()
-                .trim()
-                .parse::<usize>()
-                .unwrap() as f32;
-
-            let mut brightness_file =
-                std::fs::File::open(brightness_path.join("brightness")).unwrap();
-
-            let mut br_string = String::new();
-            brightness_file.read_to_string(&mut br_string).unwrap();
-            brightness =
-                ((br_string.trim().parse::<f32>().unwrap() / max_brightness) * 100.0) as usize;
-        } else {
-            brightness = 0;
-        }
+    pub fn new<F: Frontend>(
+        handle: &LoopHandle<'static, F>,
+        system_connection: &'static SyncDBusSource<()>,
+    ) -> Self {
+        let backlights = backlight::enumerate(Path::new("/"));
 
         let notify_instance = Inotify::init(InitFlags::empty()).unwrap();
-
-        if let Some(ref brightness) = brightness_path {
-            let _brightness_watch = notify_instance
-                .add_watch(brightness.as_path(), AddWatchFlags::IN_CLOSE_WRITE)
-                .unwrap();
+        let mut watching = false;
+        for backlight in backlights.iter() {
+            if let Backend::Sysfs { ref path, .. } = backlight.backend {
+                watching |= notify_instance
+                    .add_watch(path.as_path(), AddWatchFlags::IN_CLOSE_WRITE)
+                    .is_ok();
+            }
         }
 
-        let brightness_path = unsafe { brightness_path.unwrap_unchecked() };
-        let handle = handle
-            .insert_source(
-                Generic::new(notify_instance, Interest::BOTH, Mode::Level),
-                move |_, notify, data| unsafe {
-                    for _ in notify.read_events().unwrap() {
-                        let br_string =
-                            std::fs::read_to_string(brightness_path.join("brightness")).unwrap();
-                        data.shared_data
-                            .brightness
-                            .as_mut()
-                            .unwrap_unchecked()
-                            .brightness = ((br_string.trim().parse::<f32>().unwrap()
-                            / data
-                                .shared_data
-                                .brightness
-                                .as_mut()
-                                .unwrap_unchecked()
-                                .max_brightness)
-                            * 100.0) as _;
-
-                        data.write_bar(Rc::clone(&qh).as_ref())
-                    }
-                    Ok(PostAction::Continue)
-                },
-            )
-            .unwrap();
+        let handle = watching.then(|| {
+            handle
+                .insert_source(
+                    Generic::new(notify_instance, Interest::BOTH, Mode::Level),
+                    move |_, notify, data: &mut F| {
+                        if notify.read_events().unwrap().is_empty() {
+                            return Ok(PostAction::Continue);
+                        }
+                        if let Some(block) = data.shared_data().block_mut::<BrightnessBlock>() {
+                            block.backlights.iter_mut().for_each(Backlight::reload);
+                        }
+
+                        data.redraw();
+                        Ok(PostAction::Continue)
+                    },
+                )
+                .unwrap()
+        });
 
         Self {
-            brightness,
-            max_brightness,
+            backlights,
+            session: system_connection.with_proxy(
+                "org.freedesktop.login1",
+                "/org/freedesktop/login1/session/auto",
+                Duration::from_secs(5),
+            ),
             handle,
-            x_at: 0.0,
-            width: 0.0,
         }
     }
 
-    pub fn unregister(&self, handle: &LoopHandle<SimpleLayer>) {
-        handle.remove(self.handle);
+    /// Sets the perceived brightness of the backlight at `index`, from 0
+    /// to 100. logind does the writing for sysfs backlights, so this works
+    /// without write access to sysfs
+    pub fn set_brightness(&mut self, index: usize, brightness: usize) {
+        let raw = self.backlights[index].raw_for(brightness);
+        self.set_raw(index, raw);
+    }
+
+    fn set_raw(&mut self, index: usize, raw: u32) {
+        let backlight = &mut self.backlights[index];
+        let set = match backlight.backend {
+            Backend::Sysfs { ref name, .. } => {
+                self.session.set_brightness("backlight", name, raw).is_ok()
+            }
+            Backend::Ddc { ref device } => backlight::ddc_set_luminance(device, raw as u16).is_ok(),
+        };
+        // A monitor that was unplugged keeps its entry until the next
+        // restart, failing to set it isn't worth crashing over
+        if set {
+            backlight.raw = raw;
+        }
+    }
+
+    /// Steps the perceived brightness of the backlight on `output` by
+    /// `change` percent, returns the new brightness from 0 to 1 for the
+    /// progress bar. `None` if `output` has no backlight
+    pub fn adjust(&mut self, output: &str, change: isize) -> Option<f32> {
+        let index = backlight::for_output(&self.backlights, output)?;
+        let raw = self.backlights[index].stepped(change);
+        self.set_raw(index, raw);
+        Some(self.backlights[index].perceived() as f32 / 100.0)
     }
 
-    pub fn fmt(&self, f: &mut String) {
-        std::fmt::Write::write_fmt(
-            f,
-            format_args!(
+    pub fn icon(&self, output: &str) -> char {
+        let brightness = backlight::for_output(&self.backlights, output)
+            .map_or(100, |index| self.backlights[index].perceived());
+        match_brightness!(brightness).chars().next().unwrap()
+    }
+}
+
+impl Block for BrightnessBlock {
+    fn kind(&self) -> BlockKind {
+        BlockKind::Brightness
+    }
+
+    fn fmt(&self, output: &str, segments: &mut Vec<Segment>) {
+        if let Some(index) = backlight::for_output(&self.backlights, output) {
+            let brightness = self.backlights[index].perceived();
+            segments.push(Segment::new(format!(
                 " {}{}% ",
-                match_brightness!(self.brightness),
-                self.brightness
-            ),
-        )
-        .unwrap()
+                match_brightness!(brightness),
+                brightness
+            )))
+        }
+    }
+
+    fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
+        self.backlights.iter().try_for_each(|backlight| {
+            let brightness = backlight.perceived();
+            write!(
+                f,
+                concat!(include_str!("../table.txt"), "{}% {}\n"),
+                match_brightness!(brightness),
+                brightness,
+                backlight.connector.as_deref().unwrap_or("")
+            )
+        })
+    }
+
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "backlights": self
+                .backlights
+                .iter()
+                .map(|backlight| {
+                    serde_json::json!({
+                        "connector": backlight.connector,
+                        "brightness": backlight.perceived(),
+                        "ddc": matches!(backlight.backend, Backend::Ddc { .. }),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
     }
 
-    pub fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
-        write!(
-            f,
-            concat!(include_str!("../table.txt"), "{}%\n"),
-            match_brightness!(self.brightness),
-            self.brightness
-        )
+    fn scroll(&mut self, output: &str, _segment: usize, delta: f64) -> Response {
+        match self.adjust(output, if delta < 0.0 { 5 } else { -5 }) {
+            Some(percentage) => Response::Progress {
+                percentage,
+                icon: self.icon(output),
+            },
+            None => Response::Ignored,
+        }
+    }
+
+    fn unregister(&self, handles: &Handles) {
+        if let Some(handle) = self.handle {
+            handles.loop_handle.remove(handle);
+        }
     }
 }
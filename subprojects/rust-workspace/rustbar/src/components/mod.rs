@@ -1,6 +1,362 @@
+use std::{any::Any, io::BufWriter, os::unix::net::UnixStream, str::FromStr};
+
+use calloop_dbus::SyncDBusSource;
+use iced_tiny_skia::{
+    core::{
+        alignment::{Horizontal, Vertical},
+        text::{LineHeight, Shaping},
+        Rectangle, Size,
+    },
+    Primitive,
+};
+use smithay_client_toolkit::reexports::calloop::{LoopHandle, RegistrationToken};
+use tiny_skia::{PixmapMut, Transform};
+
+use crate::{BarSettings, NodeKind};
+
 pub mod battery;
+pub mod bluetooth;
 pub mod brightness;
-pub mod connman;
+pub mod network;
 pub mod playback;
 pub mod time;
 pub mod wireplumber;
+
+/// Everything a block may have registered itself with, so that it can
+/// remove itself again in [`Block::unregister`]
+pub struct Handles {
+    pub loop_handle: Box<dyn RemoveSource>,
+    pub user_connection: &'static SyncDBusSource<()>,
+    pub system_connection: &'static SyncDBusSource<()>,
+}
+
+/// A [`LoopHandle`] that doesn't know what the frontend is, blocks only
+/// need it to remove their sources again
+pub trait RemoveSource {
+    fn remove(&self, token: RegistrationToken);
+}
+
+impl<D: 'static> RemoveSource for LoopHandle<'static, D> {
+    fn remove(&self, token: RegistrationToken) {
+        LoopHandle::remove(self, token)
+    }
+}
+
+#[repr(usize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlockKind {
+    Playback,
+    WirePlumber,
+    /// The network block, named after the first backend it had
+    Connman,
+    Bluetooth,
+    Battery,
+    Brightness,
+    Time,
+}
+
+impl BlockKind {
+    pub const ALL: [BlockKind; 7] = [
+        BlockKind::Playback,
+        BlockKind::WirePlumber,
+        BlockKind::Connman,
+        BlockKind::Bluetooth,
+        BlockKind::Battery,
+        BlockKind::Brightness,
+        BlockKind::Time,
+    ];
+
+    /// The dconf key that enables or disables this block
+    pub fn dconf_key(&self) -> &'static str {
+        match self {
+            BlockKind::Playback => "/dotfiles/somebar/media-block",
+            BlockKind::WirePlumber => "/dotfiles/somebar/wireplumber-block",
+            BlockKind::Connman => "/dotfiles/somebar/connman-block",
+            BlockKind::Bluetooth => "/dotfiles/somebar/bluetooth-block",
+            BlockKind::Battery => "/dotfiles/somebar/battery-block",
+            BlockKind::Brightness => "/dotfiles/somebar/brightness-block",
+            BlockKind::Time => "/dotfiles/somebar/time-block",
+        }
+    }
+
+    /// The name used for this block in `/dotfiles/somebar/blocks`
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlockKind::Playback => "media",
+            BlockKind::WirePlumber => "wireplumber",
+            BlockKind::Connman => "connman",
+            BlockKind::Bluetooth => "bluetooth",
+            BlockKind::Battery => "battery",
+            BlockKind::Brightness => "brightness",
+            BlockKind::Time => "time",
+        }
+    }
+}
+
+impl FromStr for BlockKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        BlockKind::ALL.into_iter().find(|k| k.name() == s).ok_or(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Center,
+    Right,
+}
+
+impl FromStr for Side {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "left" => Ok(Side::Left),
+            "center" => Ok(Side::Center),
+            "right" => Ok(Side::Right),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Where every block goes on the bar, from left to right
+pub struct Layout(pub Vec<(BlockKind, Side)>);
+
+impl Layout {
+    /// Parses the `/dotfiles/somebar/blocks` key, entries look like
+    /// `time:right`. Unknown or repeated blocks are skipped, and a block
+    /// without a side goes on the right
+    pub fn new(entries: &[String]) -> Self {
+        let mut layout: Vec<(BlockKind, Side)> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let (name, side) = entry.split_once(':').unwrap_or((entry, "right"));
+            let (Ok(kind), Ok(side)) = (name.trim().parse(), side.trim().parse()) else {
+                continue;
+            };
+            if !layout.iter().any(|(k, _)| *k == kind) {
+                layout.push((kind, side));
+            }
+        }
+        Self(layout)
+    }
+
+    /// Where the block is in the list, `None` if it isn't shown
+    pub fn position(&self, kind: BlockKind) -> Option<usize> {
+        self.0.iter().position(|(k, _)| *k == kind)
+    }
+
+    pub fn side(&self, kind: BlockKind) -> Side {
+        self.0
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map_or(Side::Right, |(_, side)| *side)
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self(vec![
+            (BlockKind::Time, Side::Right),
+            (BlockKind::Brightness, Side::Right),
+            (BlockKind::Battery, Side::Right),
+            (BlockKind::Bluetooth, Side::Right),
+            (BlockKind::Connman, Side::Right),
+            (BlockKind::WirePlumber, Side::Right),
+            (BlockKind::Playback, Side::Right),
+        ])
+    }
+}
+
+impl TryFrom<NodeKind> for BlockKind {
+    type Error = ();
+
+    fn try_from(value: NodeKind) -> Result<Self, Self::Error> {
+        match value {
+            NodeKind::MediaBlock => Ok(BlockKind::Playback),
+            NodeKind::WireplumberBlock => Ok(BlockKind::WirePlumber),
+            NodeKind::ConnmanBlock => Ok(BlockKind::Connman),
+            NodeKind::BluetoothBlock => Ok(BlockKind::Bluetooth),
+            NodeKind::BatteryBlock => Ok(BlockKind::Battery),
+            NodeKind::BrightnessBlock => Ok(BlockKind::Brightness),
+            NodeKind::TimeBlock => Ok(BlockKind::Time),
+            _ => Err(()),
+        }
+    }
+}
+
+/// One piece of text in the status bar. A block can be made up of
+/// more than one of these (one per battery, the time and the date, etc.)
+pub struct Segment {
+    pub content: String,
+    pub shaping: Shaping,
+    /// Draw the segment highlighted, even if it isn't hovered
+    pub urgent: bool,
+}
+
+impl Segment {
+    pub fn new(content: String) -> Self {
+        Self {
+            content,
+            shaping: Shaping::Basic,
+            urgent: false,
+        }
+    }
+}
+
+/// Where a segment ended up on a monitor's bar after the last layout
+#[derive(Debug, Copy, Clone)]
+pub struct HitBox {
+    /// The index of the block in `SharedData::blocks`
+    pub block: usize,
+    pub segment: usize,
+    pub side: Side,
+    pub x_at: f32,
+    pub width: f32,
+}
+
+impl HitBox {
+    pub fn contains(&self, x: f64) -> bool {
+        x >= self.x_at as f64 && x < (self.x_at + self.width) as f64
+    }
+}
+
+/// Height of one line in the info popups
+pub const ROW_HEIGHT: f32 = 24.0;
+
+/// Makes the lines of text in an info popup. `x` is where a line starts
+/// or ends, depending on how it's aligned, and `y` is its middle
+pub fn info_row<'a>(
+    backend: &'a iced_tiny_skia::Backend,
+    bar_settings: &'a BarSettings,
+    logical_size: Size<f32>,
+) -> impl Fn(String, f32, f32, Horizontal) -> Primitive + 'a {
+    move |content, x, y, horizontal_alignment| Primitive::Text {
+        content,
+        bounds: Rectangle {
+            x,
+            y,
+            width: logical_size.width,
+            height: ROW_HEIGHT,
+        },
+        color: bar_settings.color_active.0,
+        size: backend.default_size(),
+        line_height: LineHeight::Relative(1.0),
+        font: bar_settings.default_font,
+        horizontal_alignment,
+        vertical_alignment: Vertical::Center,
+        shaping: Shaping::Advanced,
+    }
+}
+
+/// What the bar does after a block handled a click or a scroll
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {
+    Ignored,
+    Redraw,
+    /// Show the progress bar overlay, the same one the volume and
+    /// brightness keys use
+    Progress {
+        percentage: f32,
+        icon: char,
+    },
+    /// Show the text in place of the bar, the way a launch that failed
+    /// is shown
+    Notify(String),
+}
+
+pub trait AsAny: Any {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+pub trait Block: AsAny {
+    fn kind(&self) -> BlockKind;
+
+    /// Pushes this block's segments for the bar on `output`, from right
+    /// to left. `output` is the name of the monitor, like `eDP-1`
+    fn fmt(&self, output: &str, segments: &mut Vec<Segment>);
+
+    fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()>;
+
+    /// The block's state for `get` and `subscribe` on the socket
+    fn json(&self) -> serde_json::Value;
+
+    fn click(&mut self, _output: &str, _segment: usize, _button: u32) -> Response {
+        Response::Ignored
+    }
+
+    /// `delta` is positive when scrolling down
+    fn scroll(&mut self, _output: &str, _segment: usize, _delta: f64) -> Response {
+        Response::Ignored
+    }
+
+    /// `segment` was hovered, called before [`Block::info_size`]. What the
+    /// popup needs from other processes is fetched here, not while it's
+    /// drawn
+    fn info_shown(&mut self, _segment: usize) {}
+
+    /// The logical size of the popup shown when a segment is hovered,
+    /// or `None` if the segment doesn't have one
+    fn info_size(&self, _segment: usize) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn info(
+        &self,
+        _segment: usize,
+        _backend: &iced_tiny_skia::Backend,
+        _bar_settings: &BarSettings,
+        _logical_size: Size<f32>,
+    ) -> Vec<Primitive> {
+        Vec::new()
+    }
+
+    /// Draws on top of what [`Block::info`] returned with tiny-skia, for
+    /// what primitives can't do. `transform` takes logical coordinates to
+    /// the pixmap's
+    fn draw_info(
+        &self,
+        _segment: usize,
+        _pixmap: &mut PixmapMut,
+        _transform: Transform,
+        _bar_settings: &BarSettings,
+        _logical_size: Size<f32>,
+    ) {
+    }
+
+    /// Called when the popup of `segment` is clicked, `position` is
+    /// logical and relative to the popup
+    fn info_click(
+        &mut self,
+        _segment: usize,
+        _position: (f64, f64),
+        _button: u32,
+        _bar_settings: &BarSettings,
+    ) -> Response {
+        Response::Ignored
+    }
+
+    fn unregister(&self, handles: &Handles);
+}
+
+impl dyn Block {
+    pub fn downcast_ref<T: Block>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    pub fn downcast_mut<T: Block>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
+}
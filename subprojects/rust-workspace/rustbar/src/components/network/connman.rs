@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use calloop_dbus::SyncDBusSource;
+use dbus::arg;
+
+use super::{Backend, LinkType, NetworkState, Status};
+use crate::{add_match, connman::NetConnmanManager};
+
+pub const NAME: &str = "net.connman";
+
+pub struct ConnMan {
+    match_tokens: [dbus::channel::Token; 2],
+}
+
+impl ConnMan {
+    pub fn new(system_connection: &SyncDBusSource<()>) -> Self {
+        Self {
+            match_tokens: [
+                add_match!(
+                    system_connection,
+                    NAME,
+                    "net.connman.Manager",
+                    "PropertyChanged"
+                ),
+                // The connected service's signal strength
+                add_match!(
+                    system_connection,
+                    NAME,
+                    "net.connman.Service",
+                    "PropertyChanged"
+                ),
+            ],
+        }
+    }
+}
+
+fn state(state: &str) -> NetworkState {
+    match state {
+        "idle" => NetworkState::Idle,
+        "ready" => NetworkState::Ready,
+        "online" => NetworkState::Online,
+        _ => NetworkState::Offline,
+    }
+}
+
+fn link(type_: &str) -> LinkType {
+    match type_ {
+        "ethernet" => LinkType::Ethernet,
+        "wifi" => LinkType::Wifi,
+        "cellular" => LinkType::Cellular,
+        "bluetooth" => LinkType::Bluetooth,
+        "vpn" => LinkType::Vpn,
+        _ => LinkType::Unknown,
+    }
+}
+
+impl Backend for ConnMan {
+    fn name(&self) -> &'static str {
+        "connman"
+    }
+
+    fn query(&self, dbus: &SyncDBusSource<()>) -> Status {
+        let proxy = dbus.with_proxy(NAME, "/", Duration::from_secs(5));
+        let state = proxy
+            .get_properties()
+            .ok()
+            .and_then(|properties| arg::prop_cast::<String>(&properties, "State").map(|s| state(s)))
+            .unwrap_or_default();
+        if !matches!(state, NetworkState::Ready | NetworkState::Online) {
+            return Status {
+                state,
+                ..Status::default()
+            };
+        }
+
+        // A VPN only counts as the connection when it's all there is
+        let connected: Vec<_> = proxy
+            .get_services()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(_, properties)| properties)
+            .filter(|properties| {
+                matches!(
+                    arg::prop_cast::<String>(properties, "State").map(String::as_str),
+                    Some("ready" | "online")
+                )
+            })
+            .collect();
+        let is_vpn = |properties: &&arg::PropMap| {
+            arg::prop_cast::<String>(properties, "Type").map(String::as_str) == Some("vpn")
+        };
+        let vpn = connected.iter().find(is_vpn);
+        let Some(properties) = connected
+            .iter()
+            .find(|properties| !is_vpn(properties))
+            .or(vpn)
+        else {
+            return Status {
+                state,
+                ..Status::default()
+            };
+        };
+        Status {
+            state,
+            service: arg::prop_cast::<String>(properties, "Name")
+                .cloned()
+                .unwrap_or_default(),
+            strength: arg::prop_cast::<u8>(properties, "Strength").copied(),
+            link: arg::prop_cast::<String>(properties, "Type")
+                .map_or(LinkType::Unknown, |type_| link(type_)),
+            vpn: vpn
+                .filter(|_| !is_vpn(&properties))
+                .and_then(|vpn| arg::prop_cast::<String>(vpn, "Name").cloned()),
+        }
+    }
+
+    fn unregister(&self, dbus: &SyncDBusSource<()>) {
+        for t in self.match_tokens {
+            dbus.remove_match(t).unwrap();
+        }
+    }
+}
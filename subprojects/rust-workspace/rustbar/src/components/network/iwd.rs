@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use calloop_dbus::SyncDBusSource;
+use dbus::arg;
+
+use super::{Backend, LinkType, NetworkState, Status};
+use crate::{
+    add_match, bluez::OrgFreedesktopDBusObjectManager, iwd::NetConnmanIwdStationDiagnostic,
+};
+
+pub const NAME: &str = "net.connman.iwd";
+
+pub struct Iwd {
+    match_token: dbus::channel::Token,
+}
+
+impl Iwd {
+    pub fn new(system_connection: &SyncDBusSource<()>) -> Self {
+        Self {
+            match_token: add_match!(system_connection, NAME),
+        }
+    }
+}
+
+/// The signal strength in percent for `rssi` in dBm, -50 and better is
+/// full. iwd doesn't signal when it changes, it's read again when the
+/// station changes
+fn strength(rssi: i16) -> u8 {
+    ((rssi as i32 + 100) * 2).clamp(0, 100) as u8
+}
+
+impl Backend for Iwd {
+    fn name(&self) -> &'static str {
+        "iwd"
+    }
+
+    /// iwd doesn't know about the internet, a connected station is only
+    /// ready
+    fn query(&self, dbus: &SyncDBusSource<()>) -> Status {
+        let objects = dbus
+            .with_proxy(NAME, "/", Duration::from_secs(5))
+            .get_managed_objects()
+            .unwrap_or_default();
+        let mut status = Status::default();
+        for (path, interfaces) in &objects {
+            let Some(station) = interfaces.get("net.connman.iwd.Station") else {
+                continue;
+            };
+            status.state = NetworkState::Idle;
+            if arg::prop_cast::<String>(station, "State").map(String::as_str) != Some("connected") {
+                continue;
+            }
+            let network = arg::prop_cast::<dbus::Path<'static>>(station, "ConnectedNetwork")
+                .and_then(|network| objects.get(network))
+                .and_then(|interfaces| interfaces.get("net.connman.iwd.Network"));
+            return Status {
+                state: NetworkState::Ready,
+                service: network
+                    .and_then(|network| arg::prop_cast::<String>(network, "Name"))
+                    .cloned()
+                    .unwrap_or_default(),
+                strength: dbus
+                    .with_proxy(NAME, path.clone(), Duration::from_secs(5))
+                    .get_diagnostics()
+                    .ok()
+                    .and_then(|diagnostics| arg::prop_cast::<i16>(&diagnostics, "RSSI").copied())
+                    .map(strength),
+                link: LinkType::Wifi,
+                vpn: None,
+            };
+        }
+        status
+    }
+
+    fn unregister(&self, dbus: &SyncDBusSource<()>) {
+        dbus.remove_match(self.match_token).unwrap();
+    }
+}
+
+#[test]
+fn test_strength() {
+    assert_eq!(strength(-40), 100);
+    assert_eq!(strength(-50), 100);
+    assert_eq!(strength(-75), 50);
+    assert_eq!(strength(-100), 0);
+    assert_eq!(strength(-120), 0);
+}
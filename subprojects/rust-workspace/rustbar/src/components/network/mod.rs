@@ -0,0 +1,508 @@
+//! The network block, it shows what ConnMan, NetworkManager or iwd is
+//! connected to. It's still called `connman` in the layout and in dconf,
+//! ConnMan was all it spoke at first
+use std::{
+    fmt::Display,
+    io::{BufWriter, Write},
+    os::unix::net::UnixStream,
+    time::{Duration, Instant},
+};
+
+use calloop_dbus::SyncDBusSource;
+use iced_tiny_skia::{
+    core::{alignment::Horizontal, Size},
+    Primitive,
+};
+use smithay_client_toolkit::reexports::calloop::{
+    timer::{TimeoutAction, Timer},
+    LoopHandle, RegistrationToken,
+};
+
+use super::{info_row, Block, BlockKind, Handles, Segment, ROW_HEIGHT};
+use crate::{add_match, BarSettings, Frontend, SelectedBlock};
+
+mod connman;
+mod iwd;
+mod networkmanager;
+
+/// How often `/proc/net/dev` is read for the throughput
+const THROUGHPUT_INTERVAL: Duration = Duration::from_secs(1);
+const VPN_ICON: &str = "󰖂 ";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum NetworkState {
+    #[default]
+    Offline,
+    Idle,
+    Ready,
+    Online,
+}
+
+impl Display for NetworkState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Offline => write!(f, "󰖪 "),
+            Self::Idle => write!(f, "󰅤 "),
+            Self::Ready => write!(f, "󰖩 "),
+            Self::Online => write!(f, "󰅟 "),
+        }
+    }
+}
+
+/// What the connection goes through
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LinkType {
+    #[default]
+    Unknown,
+    Ethernet,
+    Wifi,
+    Cellular,
+    Bluetooth,
+    Vpn,
+}
+
+/// What every backend knows about the connection
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Status {
+    pub state: NetworkState,
+    /// The network's name, empty when there's no connection
+    pub service: String,
+    /// In percent, for wireless links
+    pub strength: Option<u8>,
+    pub link: LinkType,
+    /// The name of a VPN that's up besides the connection
+    pub vpn: Option<String>,
+}
+
+impl Status {
+    /// The icon for the link, Wi-Fi's shows the signal strength
+    fn link_icon(&self) -> &'static str {
+        match self.link {
+            LinkType::Unknown => "",
+            LinkType::Ethernet => "󰈀 ",
+            LinkType::Wifi => match self.strength {
+                Some(0..=19) => "󰤯 ",
+                Some(20..=39) => "󰤟 ",
+                Some(40..=59) => "󰤢 ",
+                Some(60..=79) => "󰤥 ",
+                _ => "󰤨 ",
+            },
+            LinkType::Cellular => "󰒢 ",
+            LinkType::Bluetooth => "󰂯 ",
+            LinkType::Vpn => VPN_ICON,
+        }
+    }
+}
+
+/// How fast every interface is receiving and sending, from the last two
+/// samples of `/proc/net/dev`
+struct Throughput {
+    token: RegistrationToken,
+    /// Bytes every interface received and sent since it came up
+    sample: Vec<(String, u64, u64)>,
+    sampled: Instant,
+    /// Bytes received and sent per second
+    rates: Vec<(String, f64, f64)>,
+}
+
+/// Every interface but loopback that ever received or sent anything,
+/// with the bytes it received and sent
+fn parse_net_dev(net_dev: &str) -> Vec<(String, u64, u64)> {
+    net_dev
+        .lines()
+        .filter_map(|line| {
+            let (name, counters) = line.split_once(':')?;
+            let counters: Vec<u64> = counters
+                .split_whitespace()
+                .map(|c| c.parse().ok())
+                .collect::<Option<_>>()?;
+            // Received bytes come first, sent bytes after the eight
+            // receive counters
+            Some((
+                name.trim().to_owned(),
+                *counters.first()?,
+                *counters.get(8)?,
+            ))
+        })
+        .filter(|(name, received, sent)| name != "lo" && (*received != 0 || *sent != 0))
+        .collect()
+}
+
+/// The rates between two samples that were `seconds` apart, interfaces
+/// that weren't in the old one are left out
+fn rates(
+    old: &[(String, u64, u64)],
+    new: &[(String, u64, u64)],
+    seconds: f64,
+) -> Vec<(String, f64, f64)> {
+    new.iter()
+        .filter_map(|(name, received, sent)| {
+            let (_, old_received, old_sent) = old.iter().find(|(old, _, _)| old == name)?;
+            Some((
+                name.clone(),
+                received.saturating_sub(*old_received) as f64 / seconds,
+                sent.saturating_sub(*old_sent) as f64 / seconds,
+            ))
+        })
+        .collect()
+}
+
+fn format_rate(bytes_per_second: f64) -> String {
+    if bytes_per_second < 1e3 {
+        format!("{:.0} B/s", bytes_per_second)
+    } else if bytes_per_second < 1e6 {
+        format!("{:.1} kB/s", bytes_per_second / 1e3)
+    } else if bytes_per_second < 1e9 {
+        format!("{:.1} MB/s", bytes_per_second / 1e6)
+    } else {
+        format!("{:.1} GB/s", bytes_per_second / 1e9)
+    }
+}
+
+/// A network daemon the block can show
+trait Backend {
+    fn name(&self) -> &'static str;
+
+    /// What the daemon is connected to now. Its signals come in through
+    /// [`NetworkBlock::query_network`], which asks again
+    fn query(&self, dbus: &SyncDBusSource<()>) -> Status;
+
+    fn unregister(&self, dbus: &SyncDBusSource<()>);
+}
+
+/// Whether `name` is owned by someone on the bus
+fn has_owner(dbus: &SyncDBusSource<()>, name: &str) -> bool {
+    dbus.with_proxy(
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        Duration::from_secs(5),
+    )
+    .method_call("org.freedesktop.DBus", "NameHasOwner", (name,))
+    .map_or(false, |(owned,): (bool,)| owned)
+}
+
+/// The first backend that's on the bus. iwd comes last, ConnMan and
+/// NetworkManager can use it for Wi-Fi themselves
+fn detect(dbus: &SyncDBusSource<()>) -> Option<Box<dyn Backend>> {
+    if has_owner(dbus, connman::NAME) {
+        Some(Box::new(connman::ConnMan::new(dbus)))
+    } else if has_owner(dbus, networkmanager::NAME) {
+        Some(Box::new(networkmanager::NetworkManager::new(dbus)))
+    } else if has_owner(dbus, iwd::NAME) {
+        Some(Box::new(iwd::Iwd::new(dbus)))
+    } else {
+        None
+    }
+}
+
+pub struct NetworkBlock {
+    /// `None` when none of them is running, the block stays offline
+    backend: Option<Box<dyn Backend>>,
+    /// NameOwnerChanged, to pick the backend again when the daemons start
+    /// and stop
+    owner_token: dbus::channel::Token,
+    status: Status,
+    /// `None` when `network-throughput` is off
+    throughput: Option<Throughput>,
+}
+
+impl NetworkBlock {
+    pub fn new<F: Frontend>(
+        handle: &LoopHandle<'static, F>,
+        system_connection: &SyncDBusSource<()>,
+        throughput: bool,
+    ) -> Self {
+        let owner_token = add_match!(
+            system_connection,
+            "org.freedesktop.DBus",
+            "org.freedesktop.DBus",
+            "NameOwnerChanged"
+        );
+        let backend = detect(system_connection);
+        let status = backend
+            .as_ref()
+            .map(|backend| backend.query(system_connection))
+            .unwrap_or_default();
+
+        let throughput = throughput.then(|| {
+            let token = handle
+                .insert_source(
+                    Timer::from_duration(THROUGHPUT_INTERVAL),
+                    move |_event, _metadata, data: &mut F| {
+                        let shared_data = data.shared_data();
+                        let Some(network) = shared_data.block_mut::<NetworkBlock>() else {
+                            return TimeoutAction::Drop;
+                        };
+                        network.sample_throughput();
+                        // Only the popup shows it
+                        if matches!(shared_data.selected, SelectedBlock::Block(b, _)
+                            if shared_data.blocks[b].kind() == BlockKind::Connman)
+                        {
+                            data.redraw();
+                        }
+                        TimeoutAction::ToDuration(THROUGHPUT_INTERVAL)
+                    },
+                )
+                .unwrap();
+            Throughput {
+                token,
+                sample: std::fs::read_to_string("/proc/net/dev")
+                    .map(|net_dev| parse_net_dev(&net_dev))
+                    .unwrap_or_default(),
+                sampled: Instant::now(),
+                rates: Vec::new(),
+            }
+        });
+
+        Self {
+            backend,
+            owner_token,
+            status,
+            throughput,
+        }
+    }
+
+    /// Picks the backend again when `name` is one of the daemons, which
+    /// just started or stopped. Returns whether anything changed
+    pub fn name_owner_changed(&mut self, dbus: &SyncDBusSource<()>, name: &str) -> bool {
+        if ![connman::NAME, networkmanager::NAME, iwd::NAME].contains(&name) {
+            return false;
+        }
+        if let Some(backend) = self.backend.take() {
+            backend.unregister(dbus);
+        }
+        self.backend = detect(dbus);
+        let status = self
+            .backend
+            .as_ref()
+            .map(|backend| backend.query(dbus))
+            .unwrap_or_default();
+        if status == self.status {
+            false
+        } else {
+            self.status = status;
+            true
+        }
+    }
+
+    fn sample_throughput(&mut self) {
+        let Some(throughput) = &mut self.throughput else {
+            return;
+        };
+        let Ok(net_dev) = std::fs::read_to_string("/proc/net/dev") else {
+            return;
+        };
+        let sample = parse_net_dev(&net_dev);
+        throughput.rates = rates(
+            &throughput.sample,
+            &sample,
+            throughput.sampled.elapsed().as_secs_f64(),
+        );
+        throughput.sample = sample;
+        throughput.sampled = Instant::now();
+    }
+
+    /// The VPN's name when it isn't the connection itself
+    fn vpn(&self) -> Option<&str> {
+        self.status
+            .vpn
+            .as_deref()
+            .filter(|_| self.is_online() && self.status.link != LinkType::Vpn)
+    }
+
+    /// Whether there's a connection that could be used to sync the time
+    pub fn is_online(&self) -> bool {
+        matches!(
+            self.status.state,
+            NetworkState::Ready | NetworkState::Online
+        )
+    }
+
+    /// Asks the backend again after one of its signals, returns whether
+    /// anything changed
+    pub fn query_network(&mut self, dbus: &SyncDBusSource<()>) -> bool {
+        let Some(backend) = &self.backend else {
+            return false;
+        };
+        let status = backend.query(dbus);
+        if status == self.status {
+            false
+        } else {
+            self.status = status;
+            true
+        }
+    }
+}
+
+impl Block for NetworkBlock {
+    fn kind(&self) -> BlockKind {
+        BlockKind::Connman
+    }
+
+    fn fmt(&self, _output: &str, segments: &mut Vec<Segment>) {
+        segments.push(Segment::new(if self.is_online() {
+            format!(
+                " {}{}{} {}",
+                self.status.state,
+                self.status.link_icon(),
+                self.status.service,
+                if self.vpn().is_some() { VPN_ICON } else { "" },
+            )
+        } else {
+            format!(" {} ", self.status.state)
+        }));
+    }
+
+    fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
+        write!(
+            f,
+            concat!(include_str!("../../table.txt"), "{}{}\n"),
+            self.status.state,
+            self.status.link_icon(),
+            self.status.service,
+        )?;
+        if let Some(strength) = self.status.strength {
+            write!(
+                f,
+                concat!(include_str!("../../table.txt"), "{}%\n"),
+                self.status.link_icon(),
+                strength
+            )?;
+        }
+        if let Some(vpn) = self.vpn() {
+            write!(
+                f,
+                concat!(include_str!("../../table.txt"), "{}\n"),
+                VPN_ICON, vpn
+            )?;
+        }
+        Ok(())
+    }
+
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "backend": self.backend.as_ref().map(|backend| backend.name()),
+            "state": format!("{:?}", self.status.state),
+            "service": self.is_online().then_some(&self.status.service),
+            "strength": self.status.strength,
+            "link": format!("{:?}", self.status.link),
+            "vpn": self.vpn(),
+            "throughput": self.throughput.as_ref().map(|throughput| {
+                throughput
+                    .rates
+                    .iter()
+                    .map(|(name, received, sent)| {
+                        (name.clone(), serde_json::json!({ "received": received, "sent": sent }))
+                    })
+                    .collect::<serde_json::Map<_, _>>()
+            }),
+        })
+    }
+
+    fn info_size(&self, _segment: usize) -> Option<(u32, u32)> {
+        let rows = 1
+            + self.vpn().is_some() as usize
+            + self
+                .throughput
+                .as_ref()
+                .map_or(0, |throughput| throughput.rates.len());
+        Some((256, (ROW_HEIGHT * rows as f32) as u32))
+    }
+
+    fn info(
+        &self,
+        _segment: usize,
+        backend: &iced_tiny_skia::Backend,
+        bar_settings: &BarSettings,
+        logical_size: Size<f32>,
+    ) -> Vec<Primitive> {
+        let text = info_row(backend, bar_settings, logical_size);
+        let left = bar_settings.padding_x;
+        let right = logical_size.width - bar_settings.padding_x;
+        let mut y = bar_settings.padding_y + ROW_HEIGHT / 2.0;
+
+        let mut primitives = vec![text(
+            format!(
+                "{}{}{}",
+                self.status.state,
+                self.status.link_icon(),
+                if self.is_online() {
+                    &self.status.service
+                } else {
+                    ""
+                }
+            ),
+            left,
+            y,
+            Horizontal::Left,
+        )];
+        if let Some(strength) = self.status.strength.filter(|_| self.is_online()) {
+            primitives.push(text(format!("{}%", strength), right, y, Horizontal::Right));
+        }
+
+        if let Some(vpn) = self.vpn() {
+            y += ROW_HEIGHT;
+            primitives.push(text(
+                format!("{}{}", VPN_ICON, vpn),
+                left,
+                y,
+                Horizontal::Left,
+            ));
+            primitives.push(text("VPN".to_owned(), right, y, Horizontal::Right));
+        }
+
+        if let Some(throughput) = &self.throughput {
+            for (name, received, sent) in &throughput.rates {
+                y += ROW_HEIGHT;
+                primitives.push(text(name.clone(), left, y, Horizontal::Left));
+                primitives.push(text(
+                    format!("󰁅 {}  󰁝 {}", format_rate(*received), format_rate(*sent)),
+                    right,
+                    y,
+                    Horizontal::Right,
+                ));
+            }
+        }
+
+        primitives
+    }
+
+    fn unregister(&self, handles: &Handles) {
+        handles
+            .system_connection
+            .remove_match(self.owner_token)
+            .unwrap();
+        if let Some(backend) = &self.backend {
+            backend.unregister(handles.system_connection);
+        }
+        if let Some(throughput) = &self.throughput {
+            handles.loop_handle.remove(throughput.token);
+        }
+    }
+}
+
+#[test]
+fn test_throughput() {
+    let net_dev = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:  123456     100    0    0    0     0          0         0   123456     100    0    0    0     0       0          0
+wlan0: 5000000    4000    0    0    0     0          0         0   200000    1500    0    0    0     0       0          0
+  eth0:       0       0    0    0    0     0          0         0        0       0    0    0    0     0       0          0
+";
+    let old = parse_net_dev(net_dev);
+    assert_eq!(old, [("wlan0".to_owned(), 5000000, 200000)]);
+
+    let new = [
+        ("wlan0".to_owned(), 7000000, 201000),
+        ("wg0".to_owned(), 100, 100),
+    ];
+    assert_eq!(
+        rates(&old, &new, 2.0),
+        [("wlan0".to_owned(), 1000000.0, 500.0)]
+    );
+
+    assert_eq!(format_rate(500.0), "500 B/s");
+    assert_eq!(format_rate(1500.0), "1.5 kB/s");
+    assert_eq!(format_rate(1000000.0), "1.0 MB/s");
+}
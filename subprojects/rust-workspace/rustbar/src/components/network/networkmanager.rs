@@ -0,0 +1,168 @@
+use std::{cell::RefCell, time::Duration};
+
+use calloop_dbus::SyncDBusSource;
+use dbus::message::MatchRule;
+
+use super::{Backend, LinkType, NetworkState, Status};
+use crate::networkmanager::{
+    OrgFreedesktopNetworkManager, OrgFreedesktopNetworkManagerAccessPoint,
+    OrgFreedesktopNetworkManagerConnectionActive, OrgFreedesktopNetworkManagerDeviceWireless,
+};
+
+pub const NAME: &str = "org.freedesktop.NetworkManager";
+const MANAGER_PATH: &str = "/org/freedesktop/NetworkManager";
+/// `NMActiveConnectionState` of a connection that's up
+const ACTIVATED: u32 = 2;
+
+pub struct NetworkManager {
+    /// The objects whose PropertiesChanged are matched, see
+    /// [`NetworkManager::watch`]
+    matches: RefCell<Vec<(dbus::Path<'static>, dbus::channel::Token)>>,
+}
+
+impl NetworkManager {
+    pub fn new(system_connection: &SyncDBusSource<()>) -> Self {
+        let network_manager = Self {
+            matches: RefCell::new(Vec::new()),
+        };
+        network_manager.watch(system_connection, vec![dbus::Path::from(MANAGER_PATH)]);
+        network_manager
+    }
+
+    /// Matches PropertiesChanged from `paths` only. Every access point and
+    /// device sends them all the time while NetworkManager scans
+    fn watch(&self, dbus: &SyncDBusSource<()>, paths: Vec<dbus::Path<'static>>) {
+        let mut matches = self.matches.borrow_mut();
+        matches.retain(|(path, token)| {
+            let keep = paths.contains(path);
+            if !keep {
+                dbus.remove_match(*token).unwrap();
+            }
+            keep
+        });
+        for path in paths {
+            if matches.iter().all(|(watched, _)| *watched != path) {
+                let token = dbus
+                    .add_match::<crate::upower::OrgFreedesktopDBusPropertiesPropertiesChanged, _>(
+                        MatchRule::new_signal(
+                            "org.freedesktop.DBus.Properties",
+                            "PropertiesChanged",
+                        )
+                        .with_sender(NAME)
+                        .with_path(path.clone()),
+                        |_, _, _| true,
+                    )
+                    .unwrap();
+                matches.push((path, token));
+            }
+        }
+    }
+}
+
+/// `NMState`
+fn state(state: u32) -> NetworkState {
+    match state {
+        // Connected globally
+        70 => NetworkState::Online,
+        // Connected locally or to a site
+        50 | 60 => NetworkState::Ready,
+        // Disconnected, disconnecting or connecting
+        20 | 30 | 40 => NetworkState::Idle,
+        // Asleep or unknown
+        _ => NetworkState::Offline,
+    }
+}
+
+/// The `type` of the connection's settings
+fn link(type_: &str) -> LinkType {
+    match type_ {
+        "802-3-ethernet" => LinkType::Ethernet,
+        "802-11-wireless" => LinkType::Wifi,
+        "gsm" | "cdma" => LinkType::Cellular,
+        "bluetooth" => LinkType::Bluetooth,
+        "vpn" | "wireguard" => LinkType::Vpn,
+        _ => LinkType::Unknown,
+    }
+}
+
+impl Backend for NetworkManager {
+    fn name(&self) -> &'static str {
+        "networkmanager"
+    }
+
+    fn query(&self, dbus: &SyncDBusSource<()>) -> Status {
+        // The manager has the state and the primary connection, the
+        // active connections their state and the access point, which has
+        // the strength
+        let mut watched = vec![dbus::Path::from(MANAGER_PATH)];
+        let status = status(dbus, &mut watched);
+        self.watch(dbus, watched);
+        status
+    }
+
+    fn unregister(&self, dbus: &SyncDBusSource<()>) {
+        for (_, token) in self.matches.borrow_mut().drain(..) {
+            dbus.remove_match(token).unwrap();
+        }
+    }
+}
+
+/// What NetworkManager is connected to, the objects it came from are
+/// added to `watched`
+fn status(dbus: &SyncDBusSource<()>, watched: &mut Vec<dbus::Path<'static>>) -> Status {
+    let proxy = |path| dbus.with_proxy(NAME, path, Duration::from_secs(5));
+    let manager = proxy(dbus::Path::from(MANAGER_PATH));
+    let active_connections = manager.active_connections().unwrap_or_default();
+    watched.extend(active_connections.iter().cloned());
+    let state = state(OrgFreedesktopNetworkManager::state(&manager).unwrap_or_default());
+    let primary = manager
+        .primary_connection()
+        .ok()
+        .filter(|path| &**path != "/");
+    let Some(primary) =
+        primary.filter(|_| matches!(state, NetworkState::Ready | NetworkState::Online))
+    else {
+        return Status {
+            state,
+            ..Status::default()
+        };
+    };
+
+    // WireGuard and VPN connections that are up besides the primary
+    // one, which can be a VPN itself
+    let vpn = active_connections
+        .into_iter()
+        .filter(|path| *path != primary)
+        .map(proxy)
+        .filter(|connection| {
+            OrgFreedesktopNetworkManagerConnectionActive::state(connection).ok() == Some(ACTIVATED)
+                && link(&connection.type_().unwrap_or_default()) == LinkType::Vpn
+        })
+        .find_map(|connection| connection.id().ok());
+
+    let connection = proxy(primary);
+    let link = link(&connection.type_().unwrap_or_default());
+    let strength = if link == LinkType::Wifi {
+        connection
+            .devices()
+            .unwrap_or_default()
+            .into_iter()
+            .find_map(|device| {
+                let access_point = proxy(device)
+                    .active_access_point()
+                    .ok()
+                    .filter(|path| &**path != "/")?;
+                watched.push(access_point.clone());
+                proxy(access_point).strength().ok()
+            })
+    } else {
+        None
+    };
+    Status {
+        state,
+        service: connection.id().unwrap_or_default(),
+        strength,
+        link,
+        vpn,
+    }
+}
@@ -7,12 +7,28 @@ use std::{
 };
 
 use base64::Engine;
+use calloop_dbus::SyncDBusSource;
 use dbus::arg::RefArg;
-use iced_tiny_skia::core::image::Handle;
+use iced_tiny_skia::{
+    core::{
+        alignment::{Horizontal, Vertical},
+        image::Handle,
+        text::{LineHeight, Shaping},
+        Rectangle, Size,
+    },
+    graphics::backend::Text,
+    Primitive,
+};
 use pct_str::PctStr;
+use smithay_client_toolkit::seat::pointer::{BTN_LEFT, BTN_MIDDLE, BTN_RIGHT};
 use url::Url;
 
-use crate::{add_match, mpris};
+use super::{Block, BlockKind, Handles, Response, Segment};
+use crate::{
+    add_match,
+    mpris::{self, OrgMprisMediaPlayer2Player},
+    BarSettings,
+};
 
 #[derive(Default)]
 pub struct Metadata {
@@ -20,15 +36,16 @@ pub struct Metadata {
     pub artist: String,
     pub album: String,
     pub album_artist: String,
+    /// In microseconds
+    pub length: i64,
 }
 
 pub struct PlaybackBlock {
     pub playing: PlaybackStatus,
     pub song_metadata: Metadata,
     match_token: dbus::channel::Token,
+    proxy: dbus::blocking::Proxy<'static, &'static SyncDBusSource<()>>,
     pub album_art: Option<Handle>,
-    pub x_at: f32,
-    pub width: f32,
 }
 
 pub fn url_to_handle(art_url: Url) -> Option<Handle> {
@@ -49,9 +66,7 @@ pub fn url_to_handle(art_url: Url) -> Option<Handle> {
 }
 
 impl PlaybackBlock {
-    pub fn new(user_connection: &calloop_dbus::SyncDBusSource<()>) -> Self {
-        use crate::mpris::OrgMprisMediaPlayer2Player;
-
+    pub fn new(user_connection: &'static SyncDBusSource<()>) -> Self {
         let match_token = add_match!(user_connection, "org.mpris.MediaPlayer2.playerctld");
 
         let player_proxy = user_connection.with_proxy(
@@ -94,6 +109,9 @@ impl PlaybackBlock {
                     .unwrap()
                     .to_owned();
             }
+            if let Some(length) = metadata.get("mpris:length") {
+                song_metadata.length = length.as_i64().unwrap_or_default();
+            }
             let art_url = metadata.get("mpris:artUrl");
             if let Some(art) = art_url
                 .and_then(|a| a.as_str())
@@ -107,35 +125,11 @@ impl PlaybackBlock {
             playing,
             song_metadata,
             match_token,
-            x_at: 0.0,
-            width: 0.0,
+            proxy: player_proxy,
             album_art,
         }
     }
 
-    pub fn unregister(&self, user_connection: &calloop_dbus::SyncDBusSource<()>) {
-        user_connection.remove_match(self.match_token).unwrap();
-    }
-
-    pub fn fmt(&self, f: &mut String) {
-        std::fmt::Write::write_fmt(f, format_args!(" {}", self.playing)).unwrap();
-        if self.playing != PlaybackStatus::Stopped {
-            f.push_str(&self.song_metadata.title);
-            // f.push_str(" - ");
-            // f.push_str(&self.song_metadata.1);
-        }
-    }
-
-    pub fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
-        write!(f, include_str!("../table.txt"), self.playing)?;
-        if self.playing != PlaybackStatus::Stopped {
-            f.write_all(self.song_metadata.title.as_bytes())?
-            // f.write_all(b" - ")?;
-            // f.write_all(self.song_metadata.1.as_bytes())
-        }
-        f.write(b"\n").map(|_| ())
-    }
-
     pub fn query_media(
         &mut self,
         property: mpris::OrgFreedesktopDBusPropertiesPropertiesChanged,
@@ -186,6 +180,10 @@ impl PlaybackBlock {
                             .unwrap()
                             .to_owned();
                     }
+                    Some("mpris:length") => {
+                        self.song_metadata.length =
+                            metadata.next().unwrap().as_i64().unwrap_or_default();
+                    }
                     Some("mpris:artUrl") => {
                         let art_url = metadata.next().unwrap().as_str().unwrap();
                         if let Some(art) = Url::parse(art_url).ok() {
@@ -209,6 +207,206 @@ impl PlaybackBlock {
     }
 }
 
+impl Block for PlaybackBlock {
+    fn kind(&self) -> BlockKind {
+        BlockKind::Playback
+    }
+
+    fn fmt(&self, _output: &str, segments: &mut Vec<Segment>) {
+        let mut f = format!(" {}", self.playing);
+        if self.playing != PlaybackStatus::Stopped {
+            f.push_str(&self.song_metadata.title);
+            // f.push_str(" - ");
+            // f.push_str(&self.song_metadata.1);
+        }
+        segments.push(Segment {
+            shaping: Shaping::Advanced,
+            ..Segment::new(f)
+        });
+    }
+
+    fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
+        write!(f, include_str!("../table.txt"), self.playing)?;
+        if self.playing != PlaybackStatus::Stopped {
+            f.write_all(self.song_metadata.title.as_bytes())?
+            // f.write_all(b" - ")?;
+            // f.write_all(self.song_metadata.1.as_bytes())
+        }
+        f.write(b"\n").map(|_| ())
+    }
+
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "status": format!("{:?}", self.playing),
+            "title": self.song_metadata.title,
+            "artist": self.song_metadata.artist,
+            "album": self.song_metadata.album,
+            "album_artist": self.song_metadata.album_artist,
+            "length": self.song_metadata.length,
+        })
+    }
+
+    fn click(&mut self, _output: &str, _segment: usize, button: u32) -> Response {
+        let (result, icon) = match button {
+            BTN_LEFT => (
+                self.proxy.play_pause(),
+                if self.playing == PlaybackStatus::Playing {
+                    '󰏤'
+                } else {
+                    '󰐊'
+                },
+            ),
+            BTN_RIGHT => (self.proxy.next(), '󰒭'),
+            BTN_MIDDLE => (self.proxy.previous(), '󰒮'),
+            _ => return Response::Ignored,
+        };
+        // playerctld errors out when there is no player to control
+        if result.is_err() {
+            return Response::Ignored;
+        }
+
+        let percentage = if button == BTN_LEFT && self.song_metadata.length > 0 {
+            self.proxy.position().unwrap_or_default() as f32 / self.song_metadata.length as f32
+        } else {
+            0.0
+        };
+        Response::Progress { percentage, icon }
+    }
+
+    fn info_size(&self, _segment: usize) -> Option<(u32, u32)> {
+        Some((256, 512))
+    }
+
+    fn info(
+        &self,
+        _segment: usize,
+        backend: &iced_tiny_skia::Backend,
+        bar_settings: &BarSettings,
+        logical_size: Size<f32>,
+    ) -> Vec<Primitive> {
+        let mut y = bar_settings.padding_y;
+        let bounds = Rectangle {
+            x: logical_size.width / 2.0,
+            y,
+            width: logical_size.width,
+            height: logical_size.height,
+        };
+        let title = Primitive::Text {
+            content: self.song_metadata.title.clone(),
+            bounds,
+            color: bar_settings.color_active.0,
+            size: backend.default_size(),
+            line_height: LineHeight::Relative(1.0),
+            font: bar_settings.default_font,
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Top,
+            shaping: Shaping::Advanced,
+        };
+        let measurement = backend.measure(
+            &self.song_metadata.title,
+            backend.default_size(),
+            LineHeight::Relative(1.0),
+            bar_settings.default_font,
+            bounds.size(),
+            Shaping::Advanced,
+        );
+        y += measurement.height + bar_settings.padding_y;
+        let bounds = Rectangle {
+            x: logical_size.width / 2.0,
+            y,
+            width: logical_size.width,
+            height: logical_size.height,
+        };
+        let artist = Primitive::Text {
+            content: self.song_metadata.artist.clone(),
+            bounds,
+            color: bar_settings.color_active.0,
+            size: backend.default_size() / 1.25,
+            line_height: LineHeight::Relative(1.0),
+            font: bar_settings.default_font,
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Top,
+            shaping: Shaping::Advanced,
+        };
+        let measurement = backend.measure(
+            &self.song_metadata.artist,
+            backend.default_size(),
+            LineHeight::Relative(1.0),
+            bar_settings.default_font,
+            bounds.size(),
+            Shaping::Advanced,
+        );
+        y += measurement.height + bar_settings.padding_y;
+        let image = if let Some(handle) = self.album_art.clone() {
+            let width = logical_size.width - (bar_settings.padding_x * 2.0);
+            let bounds = Rectangle {
+                x: bar_settings.padding_x,
+                y,
+                width,
+                height: width,
+            };
+            y += width;
+            Primitive::Image { handle, bounds }
+        } else {
+            Primitive::Group {
+                primitives: Vec::new(),
+            }
+        };
+        y += bar_settings.padding_y * 2.0;
+        let bounds = Rectangle {
+            x: logical_size.width / 2.0,
+            y,
+            width: logical_size.width,
+            height: logical_size.height,
+        };
+        let album = Primitive::Text {
+            content: self.song_metadata.album.clone(),
+            bounds,
+            color: bar_settings.color_active.0,
+            size: backend.default_size(),
+            line_height: LineHeight::Relative(1.0),
+            font: bar_settings.default_font,
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Top,
+            shaping: Shaping::Advanced,
+        };
+        let measurement = backend.measure(
+            &self.song_metadata.album,
+            backend.default_size(),
+            LineHeight::Relative(1.0),
+            bar_settings.default_font,
+            bounds.size(),
+            Shaping::Advanced,
+        );
+        y += measurement.height + bar_settings.padding_y;
+        let album_artist = Primitive::Text {
+            content: self.song_metadata.album_artist.clone(),
+            bounds: Rectangle {
+                x: logical_size.width / 2.0,
+                y,
+                width: logical_size.width,
+                height: logical_size.height,
+            },
+            color: bar_settings.color_active.0,
+            size: backend.default_size() / 1.25,
+            line_height: LineHeight::Relative(1.0),
+            font: bar_settings.default_font,
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Top,
+            shaping: Shaping::Advanced,
+        };
+
+        vec![title, artist, image, album, album_artist]
+    }
+
+    fn unregister(&self, handles: &Handles) {
+        handles
+            .user_connection
+            .remove_match(self.match_token)
+            .unwrap();
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
 pub enum PlaybackStatus {
     /// A track is currently playing.
@@ -2,23 +2,20 @@ use std::{
     io::{BufWriter, Write},
     os::unix::net::UnixStream,
     process::Command,
-    rc::Rc,
     time::{Duration, Instant},
 };
 
 use chrono::{DateTime, Local, Timelike};
 use rand::{rngs::OsRng, seq::SliceRandom};
-use smithay_client_toolkit::reexports::{
-    calloop::{
-        // signals::{Signal, Signals},
-        timer::{TimeoutAction, Timer},
-        LoopHandle,
-        RegistrationToken,
-    },
-    client::QueueHandle,
+use smithay_client_toolkit::reexports::calloop::{
+    // signals::{Signal, Signals},
+    timer::{TimeoutAction, Timer},
+    LoopHandle,
+    RegistrationToken,
 };
 
-use crate::SimpleLayer;
+use super::{Block, BlockKind, Handles, Segment};
+use crate::Frontend;
 
 macro_rules! match_clock {
     ($hour:expr) => {
@@ -82,19 +79,16 @@ pub struct TimeBlock {
     pub now: DateTime<Local>,
     pub time_fmt: String,
     pub date_fmt: String,
-    pub xs_at: [f32; 2],
-    pub widths: [f32; 2],
     handle: RegistrationToken,
 }
 
 impl TimeBlock {
-    pub fn new(
-        handle: &LoopHandle<SimpleLayer>,
+    pub fn new<F: Frontend>(
+        handle: &LoopHandle<'static, F>,
         update_time_ntp: bool,
         time_servers: Vec<String>,
         time_fmt: String,
         date_fmt: String,
-        qh: Rc<QueueHandle<SimpleLayer>>,
     ) -> Self {
         let now_instant = Instant::now();
         let now = chrono::Local::now();
@@ -119,10 +113,11 @@ impl TimeBlock {
         let handle = handle
             .insert_source(
                 Timer::from_deadline(timer_start),
-                move |_event, _metadata, shared_data| unsafe {
-                    shared_data.shared_data.time.as_mut().unwrap_unchecked().now +=
-                        chrono::Duration::minutes(1);
-                    shared_data.write_bar(Rc::clone(&qh).as_ref());
+                move |_event, _metadata, data: &mut F| {
+                    if let Some(block) = data.shared_data().block_mut::<TimeBlock>() {
+                        block.now += chrono::Duration::minutes(1);
+                    }
+                    data.redraw();
                     TimeoutAction::ToDuration(Duration::from_secs(60))
                 },
             )
@@ -136,24 +131,19 @@ impl TimeBlock {
             date_fmt,
             is_time_updated: false,
             handle,
-            xs_at: [0.0; 2],
-            widths: [0.0; 2],
         }
     }
 
-    pub fn unregister(&self, handle: &LoopHandle<SimpleLayer>) {
-        handle.remove(self.handle);
-    }
-
-    pub fn fmt_time(&self, f: &mut String) {
-        f.push_str(match_clock!(self.now.hour()));
-        use std::fmt::Write;
-        write!(f, "{} ", self.now.format(&self.time_fmt),).unwrap();
-    }
-
-    pub fn fmt_date(&self, f: &mut String) {
-        use std::fmt::Write;
-        write!(f, " 󰃶 {} ", self.now.format(&self.date_fmt)).unwrap();
+    /// Called whenever the network comes up or goes down, the time is
+    /// synced once per connection
+    pub fn network_changed(&mut self, online: bool) {
+        if online {
+            if !self.is_time_updated && self.update_time_ntp {
+                self.update_time();
+            }
+        } else {
+            self.is_time_updated = false;
+        }
     }
 
     pub fn fmt_time_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
@@ -184,3 +174,37 @@ impl TimeBlock {
         Command::new("sh").arg("-c").arg(args).spawn().unwrap();
     }
 }
+
+impl Block for TimeBlock {
+    fn kind(&self) -> BlockKind {
+        BlockKind::Time
+    }
+
+    fn fmt(&self, _output: &str, segments: &mut Vec<Segment>) {
+        use std::fmt::Write;
+        let mut date = String::new();
+        write!(date, " 󰃶 {} ", self.now.format(&self.date_fmt)).unwrap();
+        segments.push(Segment::new(date));
+
+        let mut time = String::from(*match_clock!(self.now.hour()));
+        write!(time, "{} ", self.now.format(&self.time_fmt)).unwrap();
+        segments.push(Segment::new(time));
+    }
+
+    fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
+        self.fmt_time_table(f)?;
+        self.fmt_date_table(f)
+    }
+
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "now": self.now.to_rfc3339(),
+            "time": self.now.format(&self.time_fmt).to_string(),
+            "date": self.now.format(&self.date_fmt).to_string(),
+        })
+    }
+
+    fn unregister(&self, handles: &Handles) {
+        handles.loop_handle.remove(self.handle);
+    }
+}
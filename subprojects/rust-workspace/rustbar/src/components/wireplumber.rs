@@ -6,7 +6,9 @@ use std::{
 
 use calloop_dbus::SyncDBusSource;
 use dbus::arg::RefArg;
+use smithay_client_toolkit::seat::pointer::BTN_MIDDLE;
 
+use super::{Block, BlockKind, Handles, Response, Segment};
 use crate::add_match;
 use crate::wireplumber::OrgWireplumberDefaultNode;
 
@@ -17,50 +19,26 @@ pub struct WirePlumberBlock {
     pub volume: f64,
     pub base: f64,
     pub muted: bool,
-    pub x_at: f32,
-    pub width: f32,
 }
 
 impl WirePlumberBlock {
-    pub fn new(user_connection: *mut SyncDBusSource<()>, max_volume: f64) -> Self {
-        unsafe {
-            let match_token = add_match!(&*user_connection, "org.wireplumber.DefaultNode");
-
-            let wireplumber_proxy = (&*user_connection).with_proxy(
-                "org.wireplumber.DefaultNode",
-                "/",
-                Duration::from_secs(5),
-            );
-
-            let volume = wireplumber_proxy.volume().unwrap();
-            let base = volume.get("base").map_or(1.0, |f| f.as_f64().unwrap());
-
-            Self {
-                match_token,
-                volume: volume.get("volume").map_or(1.0, |f| f.as_f64().unwrap()) / base,
-                base,
-                muted: volume.get("mute").map_or(0, |f| f.as_i64().unwrap()) != 0,
-                proxy: wireplumber_proxy,
-                max_volume,
-                x_at: 0.0,
-                width: 0.0,
-            }
-        }
-    }
+    pub fn new(user_connection: &'static SyncDBusSource<()>, max_volume: f64) -> Self {
+        let match_token = add_match!(user_connection, "org.wireplumber.DefaultNode");
 
-    pub fn unregister(&self, user_connection: &SyncDBusSource<()>) {
-        user_connection.remove_match(self.match_token).unwrap();
-    }
+        let wireplumber_proxy =
+            user_connection.with_proxy("org.wireplumber.DefaultNode", "/", Duration::from_secs(5));
 
-    pub fn fmt(&self, f: &mut String) {
-        f.push(' ');
-        f.push_str(self.volume_level());
-        std::fmt::Write::write_fmt(f, format_args!("{:.0}% ", self.volume * 100.0)).unwrap();
-    }
+        let volume = wireplumber_proxy.volume().unwrap();
+        let base = volume.get("base").map_or(1.0, |f| f.as_f64().unwrap());
 
-    pub fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
-        write!(f, include_str!("../table.txt"), self.volume_level())?;
-        f.write_fmt(format_args!("{:.0}%\n", self.volume * 100.0))
+        Self {
+            match_token,
+            volume: volume.get("volume").map_or(1.0, |f| f.as_f64().unwrap()) / base,
+            base,
+            muted: volume.get("mute").map_or(0, |f| f.as_i64().unwrap()) != 0,
+            proxy: wireplumber_proxy,
+            max_volume,
+        }
     }
 
     pub fn adjust_volume(&self, change: f64) {
@@ -69,6 +47,10 @@ impl WirePlumberBlock {
             .unwrap();
     }
 
+    pub fn toggle_mute(&self) {
+        self.proxy.set_mute(!self.muted).unwrap();
+    }
+
     pub fn query_default_node(
         &mut self,
         property: crate::mpris::OrgFreedesktopDBusPropertiesPropertiesChanged,
@@ -108,3 +90,49 @@ impl WirePlumberBlock {
         }
     }
 }
+
+impl Block for WirePlumberBlock {
+    fn kind(&self) -> BlockKind {
+        BlockKind::WirePlumber
+    }
+
+    fn fmt(&self, _output: &str, segments: &mut Vec<Segment>) {
+        segments.push(Segment::new(format!(
+            " {}{:.0}% ",
+            self.volume_level(),
+            self.volume * 100.0
+        )));
+    }
+
+    fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
+        write!(f, include_str!("../table.txt"), self.volume_level())?;
+        f.write_fmt(format_args!("{:.0}%\n", self.volume * 100.0))
+    }
+
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "volume": self.volume,
+            "muted": self.muted,
+        })
+    }
+
+    fn click(&mut self, _output: &str, _segment: usize, button: u32) -> Response {
+        if button == BTN_MIDDLE {
+            self.toggle_mute();
+        }
+        // The overlay is shown once the new volume comes back over dbus
+        Response::Ignored
+    }
+
+    fn scroll(&mut self, _output: &str, _segment: usize, delta: f64) -> Response {
+        self.adjust_volume(if delta < 0.0 { 0.05 } else { -0.05 });
+        Response::Ignored
+    }
+
+    fn unregister(&self, handles: &Handles) {
+        handles
+            .user_connection
+            .remove_match(self.match_token)
+            .unwrap();
+    }
+}
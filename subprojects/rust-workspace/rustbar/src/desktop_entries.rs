@@ -0,0 +1,405 @@
+//! Every desktop entry the launcher can run, read once at startup and
+//! kept up to date with inotify
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use freedesktop_desktop_entry::{default_paths, DesktopEntry};
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, WatchDescriptor};
+use smithay_client_toolkit::reexports::calloop::{
+    generic::Generic, Interest, LoopHandle, Mode, PostAction,
+};
+
+use crate::SimpleLayer;
+
+/// What choosing an entry in the launcher does
+#[derive(Clone)]
+pub enum Target {
+    /// Runs the program and its arguments, inside
+    /// [`rustbar::BarSettings::terminal`] if `terminal`
+    Run { args: Vec<String>, terminal: bool },
+    /// Focuses the dwl client with this id, for the window switcher
+    Client(u32),
+    /// Puts the text on the clipboard, for the glyph picker
+    Copy(String),
+    /// Puts the selection with this id in the clipboard history back on
+    /// the clipboard
+    ClipboardEntry(i64),
+}
+
+#[derive(Clone)]
+pub struct DesktopCommand {
+    pub name: String,
+    pub target: Target,
+    /// The desktop file id, launches of these go in the [`crate::History`]
+    pub id: Option<String>,
+    pub frecency: f32,
+}
+
+impl AsRef<str> for DesktopCommand {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The command that runs `args`, after the terminal setting
+/// `terminal_command` split on whitespace if `terminal`
+pub fn command(args: &[String], terminal: bool, terminal_command: &str) -> Command {
+    let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if terminal {
+        args.splice(0..0, terminal_command.split_whitespace());
+    }
+    let mut command = Command::new(args[0]);
+    command.args(&args[1..]);
+    command
+}
+
+/// Undoes the escapes any string in a desktop entry can have, the
+/// quoting of `Exec` lines comes after this
+fn unescape_string(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            // Left for the quoting rules of `Exec`
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Splits an `Exec` line into arguments and expands its field codes,
+/// following the desktop entry spec. No files or URLs are ever passed,
+/// so `%f`, `%F`, `%u` and `%U` expand to nothing
+pub fn parse_exec(
+    exec: &str,
+    name: &str,
+    icon: Option<&str>,
+    path: &Path,
+) -> Result<Vec<String>, String> {
+    let exec = unescape_string(exec);
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    // `""` is an argument, a `%f` on its own isn't
+    let mut in_arg = false;
+    let mut quoted = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' if !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut arg));
+                    in_arg = false;
+                }
+            }
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            '\\' if quoted => match chars.next() {
+                Some(c @ ('"' | '`' | '$' | '\\')) => arg.push(c),
+                Some(c) => {
+                    arg.push('\\');
+                    arg.push(c);
+                }
+                None => return Err("unterminated quoted argument".to_owned()),
+            },
+            '%' => match chars.next() {
+                Some('%') => {
+                    arg.push('%');
+                    in_arg = true;
+                }
+                Some('f' | 'F' | 'u' | 'U') => {}
+                Some('i') => {
+                    if let Some(icon) = icon {
+                        args.push("--icon".to_owned());
+                        arg.push_str(icon);
+                        in_arg = true;
+                    }
+                }
+                Some('c') => {
+                    arg.push_str(name);
+                    in_arg = true;
+                }
+                Some('k') => {
+                    arg.push_str(&path.to_string_lossy());
+                    in_arg = true;
+                }
+                // Deprecated, they expand to nothing
+                Some('d' | 'D' | 'n' | 'N' | 'v' | 'm') => {}
+                Some(c) => return Err(format!("unknown field code `%{}`", c)),
+                None => return Err("`%` at the end of the line".to_owned()),
+            },
+            c => {
+                arg.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted argument".to_owned());
+    }
+    if in_arg {
+        args.push(arg);
+    }
+    if args.is_empty() {
+        return Err("nothing to run".to_owned());
+    }
+    Ok(args)
+}
+
+/// The entry at `path` and each of its actions, or why they can't be
+/// launched. Entries without a name or an `Exec` line (links, apps
+/// that are only D-Bus activated) have nothing to launch
+pub fn read(path: &Path) -> Vec<Result<DesktopCommand, String>> {
+    let bytes = match std::fs::read_to_string(path) {
+        Ok(bytes) => bytes,
+        Err(e) => return vec![Err(e.to_string())],
+    };
+    let entry = match DesktopEntry::decode(path, &bytes) {
+        Ok(entry) => entry,
+        Err(e) => return vec![Err(e.to_string())],
+    };
+    let Some(name) = entry.name(None) else {
+        return Vec::new();
+    };
+    let icon = entry.icon();
+
+    let mut commands = Vec::new();
+    if let Some(exec) = entry.exec() {
+        commands.push(
+            parse_exec(exec, &name, icon, path).map(|args| DesktopCommand {
+                name: name.to_string(),
+                target: Target::Run {
+                    args,
+                    terminal: entry.terminal(),
+                },
+                id: Some(entry.appid.to_owned()),
+                frecency: 0.0,
+            }),
+        );
+    }
+    // Actions show up as entries of their own, like `Firefox: New
+    // Private Window`
+    for action in entry.actions().unwrap_or_default().split(';') {
+        let (Some(action_name), Some(exec)) =
+            (entry.action_name(action, None), entry.action_exec(action))
+        else {
+            continue;
+        };
+        commands.push(
+            parse_exec(exec, &name, icon, path)
+                .map(|args| DesktopCommand {
+                    name: format!("{}: {}", name, action_name),
+                    target: Target::Run {
+                        args,
+                        terminal: entry.terminal(),
+                    },
+                    id: Some(format!("{}:{}", entry.appid, action)),
+                    frecency: 0.0,
+                })
+                .map_err(|e| format!("action {}: {}", action, e)),
+        );
+    }
+    commands
+}
+
+/// Whether `program` can be run, either a path or something in `$PATH`
+fn in_path(program: &str) -> bool {
+    if program.contains('/') {
+        return Path::new(program).is_file();
+    }
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+/// `rustbar check`, prints every entry the launcher can't run and why.
+/// Returns whether every entry is fine
+pub fn check() -> bool {
+    let mut fine = true;
+    for path in freedesktop_desktop_entry::Iter::new(default_paths()) {
+        for command in read(&path) {
+            let error = match command {
+                Ok(DesktopCommand {
+                    name,
+                    target: Target::Run { args, .. },
+                    ..
+                }) if !in_path(&args[0]) => format!("{}: `{}` isn't in $PATH", name, args[0]),
+                Ok(_) => continue,
+                Err(e) => e,
+            };
+            println!("{}: {}", path.display(), error);
+            fine = false;
+        }
+    }
+    fine
+}
+
+const WATCH_FLAGS: AddWatchFlags = AddWatchFlags::IN_CREATE
+    .union(AddWatchFlags::IN_CLOSE_WRITE)
+    .union(AddWatchFlags::IN_DELETE)
+    .union(AddWatchFlags::IN_MOVED_FROM)
+    .union(AddWatchFlags::IN_MOVED_TO);
+
+pub struct DesktopEntries {
+    /// Keyed by path, so a file that changed replaces its own entries.
+    /// Entries that can't be launched are left out
+    entries: BTreeMap<PathBuf, Vec<DesktopCommand>>,
+    /// The directory every watch is on
+    dirs: HashMap<WatchDescriptor, PathBuf>,
+}
+
+impl DesktopEntries {
+    pub fn new(handle: &LoopHandle<'static, SimpleLayer>) -> Self {
+        let notify_instance = Inotify::init(InitFlags::empty()).unwrap();
+        let mut desktop_entries = Self {
+            entries: BTreeMap::new(),
+            dirs: HashMap::new(),
+        };
+        for dir in default_paths() {
+            desktop_entries.add_dir(&notify_instance, dir);
+        }
+
+        handle
+            .insert_source(
+                Generic::new(notify_instance, Interest::READ, Mode::Level),
+                |_, notify, data| {
+                    data.desktop_entries.update(notify);
+                    Ok(PostAction::Continue)
+                },
+            )
+            .unwrap();
+
+        desktop_entries
+    }
+
+    /// Every entry, with its frecency from `frecencies`
+    pub fn commands(&self, frecencies: &HashMap<String, f32>) -> Vec<DesktopCommand> {
+        self.entries
+            .values()
+            .flatten()
+            .map(|entry| DesktopCommand {
+                frecency: entry
+                    .id
+                    .as_ref()
+                    .and_then(|id| frecencies.get(id))
+                    .copied()
+                    .unwrap_or(0.0),
+                ..entry.clone()
+            })
+            .collect()
+    }
+
+    /// Watches `dir` and every directory in it, and reads the entries
+    /// in them. Directories that don't exist are skipped
+    fn add_dir(&mut self, notify: &Inotify, dir: PathBuf) {
+        let Ok(wd) = notify.add_watch(dir.as_path(), WATCH_FLAGS) else {
+            return;
+        };
+        if let Ok(read_dir) = std::fs::read_dir(&dir) {
+            for path in read_dir.flatten().map(|entry| entry.path()) {
+                if path.is_dir() {
+                    self.add_dir(notify, path);
+                } else if path.extension().is_some_and(|e| e == "desktop") {
+                    let commands = read(&path).into_iter().flatten().collect();
+                    self.entries.insert(path, commands);
+                }
+            }
+        }
+        self.dirs.insert(wd, dir);
+    }
+
+    fn update(&mut self, notify: &Inotify) {
+        let Ok(events) = notify.read_events() else {
+            return;
+        };
+        for event in events {
+            if event.mask.contains(AddWatchFlags::IN_IGNORED) {
+                self.dirs.remove(&event.wd);
+                continue;
+            }
+            let (Some(dir), Some(name)) = (self.dirs.get(&event.wd), event.name) else {
+                continue;
+            };
+            let path = dir.join(name);
+            let gone = event
+                .mask
+                .intersects(AddWatchFlags::IN_DELETE | AddWatchFlags::IN_MOVED_FROM);
+
+            if event.mask.contains(AddWatchFlags::IN_ISDIR) {
+                if gone {
+                    self.entries.retain(|p, _| !p.starts_with(&path));
+                } else if event
+                    .mask
+                    .intersects(AddWatchFlags::IN_CREATE | AddWatchFlags::IN_MOVED_TO)
+                {
+                    self.add_dir(notify, path);
+                }
+            } else if path.extension().is_some_and(|e| e == "desktop") {
+                if gone {
+                    self.entries.remove(&path);
+                } else {
+                    let commands = read(&path).into_iter().flatten().collect();
+                    self.entries.insert(path, commands);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse_exec() {
+    let path = Path::new("/usr/share/applications/app.desktop");
+    let parse = |exec| parse_exec(exec, "App", Some("app"), path);
+
+    assert_eq!(parse("app %U").unwrap(), ["app"]);
+    assert_eq!(parse("app --file=%f -x").unwrap(), ["app", "--file=", "-x"]);
+    assert_eq!(
+        parse(r#"sh -c "echo \"a b\" \\$HOME" """#).unwrap(),
+        ["sh", "-c", r#"echo "a b" $HOME"#, ""]
+    );
+    assert_eq!(parse(r"app\sname 100%%").unwrap(), ["app name", "100%"]);
+    assert_eq!(
+        parse("app %i --title %c %k").unwrap(),
+        [
+            "app",
+            "--icon",
+            "app",
+            "--title",
+            "App",
+            path.to_str().unwrap()
+        ]
+    );
+    assert_eq!(parse_exec("app %i", "App", None, path).unwrap(), ["app"]);
+
+    assert!(parse(r#"app "unterminated"#).is_err());
+    assert!(parse("app %z").is_err());
+    assert!(parse("%U").is_err());
+}
+
+#[test]
+fn test_command() {
+    let args = ["htop", "-d", "a b"].map(String::from);
+    let in_terminal = command(&args, true, " alacritty  -e ");
+    assert_eq!(in_terminal.get_program(), "alacritty");
+    assert_eq!(
+        in_terminal.get_args().collect::<Vec<_>>(),
+        ["-e", "htop", "-d", "a b"]
+    );
+    assert_eq!(command(&args, false, "foot").get_program(), "htop");
+}
@@ -0,0 +1,31 @@
+//! The glyph picker's table, emoji and the Nerd Font glyphs the bar
+//! uses, one `<glyph>\t<name>` per line
+use crate::desktop_entries::{DesktopCommand, Target};
+
+const TABLE: &str = include_str!("glyphs.txt");
+
+/// Every glyph, named after itself and its name. Choosing one copies it
+pub fn entries() -> Vec<DesktopCommand> {
+    TABLE
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(glyph, name)| DesktopCommand {
+            name: format!("{} {}", glyph, name),
+            target: Target::Copy(glyph.to_owned()),
+            id: None,
+            frecency: 0.0,
+        })
+        .collect()
+}
+
+#[test]
+fn test_table() {
+    for line in TABLE.lines() {
+        let (glyph, name) = line.split_once('\t').unwrap();
+        assert_eq!(glyph.chars().count(), 1, "{}", line);
+        assert!(!name.is_empty() && name.trim() == name, "{}", line);
+    }
+    assert!(entries()
+        .iter()
+        .any(|entry| entry.name == "󰕾 nf-md-volume_high"));
+}
@@ -0,0 +1,125 @@
+//! Remembers which desktop entries were launched and when, so the
+//! launcher can put the apps that are used the most first
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::Connection;
+
+/// A launch is worth half as much after this many seconds (two weeks)
+const HALF_LIFE: f64 = 14.0 * 24.0 * 60.0 * 60.0;
+/// Launches older than this (90 days) are forgotten
+const MAX_AGE: i64 = 90 * 24 * 60 * 60;
+/// How much a frecency of 1 is worth next to a `nucleo_matcher` score,
+/// about as much as two more matching characters
+const WEIGHT: f32 = 32.0;
+
+pub struct History {
+    conn: Connection,
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+impl History {
+    /// Opens `$XDG_DATA_HOME/rustbar/history.sqlite`, the history is only
+    /// kept in memory if that doesn't work out
+    pub fn open() -> Self {
+        let conn = dirs::data_dir()
+            .and_then(|dir| {
+                let dir = dir.join("rustbar");
+                std::fs::create_dir_all(&dir).ok()?;
+                Connection::open(dir.join("history.sqlite")).ok()
+            })
+            .unwrap_or_else(|| Connection::open_in_memory().unwrap());
+        Self::with_connection(conn, now())
+    }
+
+    fn with_connection(conn: Connection, now: i64) -> Self {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS launches ( \
+                id TEXT NOT NULL, \
+                time INTEGER NOT NULL \
+            ); \
+            CREATE INDEX IF NOT EXISTS launches_id ON launches (id);",
+        )
+        .unwrap();
+        let _ = conn.execute("DELETE FROM launches WHERE time < ?1", [now - MAX_AGE]);
+        Self { conn }
+    }
+
+    /// Remembers that the desktop entry `id` was just launched
+    pub fn record(&self, id: &str) {
+        self.record_at(id, now());
+    }
+
+    fn record_at(&self, id: &str, time: i64) {
+        let _ = self.conn.execute(
+            "INSERT INTO launches (id, time) VALUES (?1, ?2)",
+            rusqlite::params![id, time],
+        );
+    }
+
+    /// The frecency of every desktop entry that was launched before,
+    /// every launch counts for 1 and halves in worth every [`HALF_LIFE`]
+    pub fn frecencies(&self) -> HashMap<String, f32> {
+        self.frecencies_at(now())
+    }
+
+    fn frecencies_at(&self, now: i64) -> HashMap<String, f32> {
+        let mut frecencies = HashMap::new();
+        let Ok(mut stmt) = self.conn.prepare("SELECT id, time FROM launches") else {
+            return frecencies;
+        };
+        let Ok(rows) = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        }) else {
+            return frecencies;
+        };
+        for (id, time) in rows.flatten() {
+            let age = (now - time).max(0) as f64;
+            *frecencies.entry(id).or_insert(0.0) += 0.5f64.powf(age / HALF_LIFE) as f32;
+        }
+        frecencies
+    }
+}
+
+/// Where an entry goes in the launcher, the `nucleo_matcher` score with
+/// the frecency blended in. Frecency counts less the more it grows, so
+/// a good match still beats an app that's launched all the time
+pub fn rank(score: u32, frecency: f32) -> f32 {
+    score as f32 + WEIGHT * frecency.ln_1p()
+}
+
+#[test]
+fn test_frecency() {
+    let history = History::with_connection(Connection::open_in_memory().unwrap(), 0);
+    let day = 24 * 60 * 60;
+    history.record_at("old.desktop", 0);
+    history.record_at("old.desktop", 0);
+    history.record_at("new.desktop", 27 * day);
+    history.record_at("new.desktop", 28 * day);
+
+    let frecencies = history.frecencies_at(28 * day);
+    // Two launches four weeks ago are worth half a launch today
+    assert!((frecencies["old.desktop"] - 0.5).abs() < 0.001);
+    assert!(frecencies["new.desktop"] > 1.9);
+    assert!(!frecencies.contains_key("never.desktop"));
+
+    // Forgotten after `MAX_AGE`
+    let history = History::with_connection(history.conn, 100 * day);
+    assert!(!history.frecencies_at(100 * day).contains_key("old.desktop"));
+}
+
+#[test]
+fn test_rank() {
+    assert_eq!(rank(0, 0.0), 0.0);
+    assert!(rank(0, 3.0) > rank(0, 1.0));
+    // A clearly better match isn't buried by an app used all the time
+    assert!(rank(200, 0.0) > rank(100, 10.0));
+}
@@ -1,25 +1,210 @@
-use std::io::{stdout, BufWriter, Write};
+//! `rustbar-i3`, the blocks of rustbar as a status command for i3bar
+//! and swaybar. Only the blocks are drawn here, everything else the
+//! Wayland bar does is left to the compositor
+use std::{
+    fs::File,
+    io::{stdout, BufWriter, ErrorKind, Read, StdoutLock, Write},
+    os::fd::FromRawFd,
+};
 
-use calloop::EventLoop;
 use dconf_sys::dconf_client_new;
-use rustbar::{SharedData, SharedDataTransparent};
+use iced_tiny_skia::core::Color;
+use rustbar::{
+    color_active, color_inactive,
+    components::{Response, Segment},
+    ipc::Command,
+    set_charge_threshold, Frontend, NodeKind, SharedData,
+};
+use serde::Deserialize;
+use serde_json::json;
+use smithay_client_toolkit::{
+    reexports::calloop::{generic::Generic, EventLoop, Interest, Mode, PostAction},
+    seat::pointer::{BTN_LEFT, BTN_MIDDLE, BTN_RIGHT},
+};
+
+/// A click event from i3bar, the fields rustbar doesn't need are ignored
+#[derive(Deserialize)]
+struct ClickEvent {
+    name: String,
+    instance: String,
+    button: u32,
+}
+
+struct I3Bar {
+    shared_data: SharedData<I3Bar>,
+    stdout: BufWriter<StdoutLock<'static>>,
+    /// The output blocks like brightness show themselves for
+    output: String,
+    color_active: (Color, Color),
+    color_inactive: (Color, Color),
+    /// Whatever was read from stdin that isn't a full line yet
+    stdin: Vec<u8>,
+}
+
+fn hex_color(color: Color) -> String {
+    let [r, g, b, _] = color.into_rgba8();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+impl I3Bar {
+    fn click(&mut self, event: ClickEvent) {
+        let Some(b) = self
+            .shared_data
+            .blocks
+            .iter()
+            .position(|block| block.kind().name() == event.name)
+        else {
+            return;
+        };
+        let Ok(s) = event.instance.parse() else {
+            return;
+        };
+        let block = &mut self.shared_data.blocks[b];
+        let response = match event.button {
+            1 => block.click(&self.output, s, BTN_LEFT),
+            2 => block.click(&self.output, s, BTN_MIDDLE),
+            3 => block.click(&self.output, s, BTN_RIGHT),
+            4 => block.scroll(&self.output, s, -1.0),
+            5 => block.scroll(&self.output, s, 1.0),
+            _ => Response::Ignored,
+        };
+        // There's no overlay to show the progress on, the block
+        // itself shows the new value
+        if response != Response::Ignored {
+            self.redraw();
+        }
+    }
+
+    /// Handles every full line read from stdin so far
+    fn read_clicks(&mut self) {
+        while let Some(end) = self.stdin.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.stdin.drain(..=end).collect();
+            // The click events are one infinite array, every event but
+            // the first starts with a comma
+            let line = line.trim_ascii();
+            let line = line
+                .strip_prefix(b"[")
+                .or_else(|| line.strip_prefix(b","))
+                .unwrap_or(line);
+            if let Ok(event) = serde_json::from_slice::<ClickEvent>(line) {
+                self.click(event);
+            }
+        }
+    }
+}
+
+impl Frontend for I3Bar {
+    fn shared_data(&mut self) -> &mut SharedData<Self> {
+        &mut self.shared_data
+    }
+
+    fn redraw(&mut self) {
+        let mut line = Vec::new();
+        for block in self.shared_data.blocks.iter() {
+            let mut segments: Vec<Segment> = Vec::new();
+            block.fmt(&self.output, &mut segments);
+            // The Wayland bar lays segments out from right to left
+            for (s, segment) in segments.iter().enumerate().rev() {
+                let color = if segment.urgent {
+                    self.color_active.0
+                } else {
+                    self.color_inactive.0
+                };
+                line.push(json!({
+                    "full_text": segment.content.trim(),
+                    "name": block.kind().name(),
+                    "instance": s.to_string(),
+                    "color": hex_color(color),
+                    "urgent": segment.urgent,
+                }));
+            }
+        }
+        self.stdout.write_all(b",").unwrap();
+        serde_json::to_writer(&mut self.stdout, &line).unwrap();
+        self.stdout.write_all(b"\n").unwrap();
+        self.stdout.flush().unwrap();
+        self.shared_data.notify_subscribers();
+    }
+
+    fn setting_changed(&mut self, kind: NodeKind) {
+        match kind {
+            NodeKind::ColorActive => {
+                self.color_active = color_active(self.shared_data.dconf);
+                self.redraw();
+            }
+            NodeKind::ColorInactive => {
+                self.color_inactive = color_inactive(self.shared_data.dconf);
+                self.redraw();
+            }
+            _ => {}
+        }
+    }
+
+    fn command(&mut self, command: Command) -> Result<(), String> {
+        match command {
+            Command::Reload => {
+                self.shared_data.reload();
+                self.redraw();
+                Ok(())
+            }
+            Command::ChargeThreshold(threshold) => set_charge_threshold(self, threshold),
+            _ => Err("rustbar-i3 only does reload and battery".to_owned()),
+        }
+    }
+}
 
 fn main() {
-    let mut event_loop: EventLoop<_> = EventLoop::try_new().unwrap();
+    let mut output = String::from("eDP-1");
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = args.next().expect("--output needs a connector name"),
+            _ => {
+                eprintln!("usage: rustbar-i3 [--output <connector>]");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let mut event_loop: EventLoop<'static, I3Bar> = EventLoop::try_new().unwrap();
+    let dconf = unsafe { dconf_client_new() };
+
     let mut stdout = BufWriter::new(stdout().lock());
-    stdout.write_all(b"{ \"version\": 1 }\n[\n[]").unwrap();
-    let mut shared_data = SharedDataTransparent(SharedData::new(
-        event_loop.get_signal(),
-        move |string| {
-            stdout.write_all(b",[{\"full_text\":\"").unwrap();
-            stdout.write_all(&string).unwrap();
-            stdout.write_all(b"\"}]").unwrap();
-            stdout.flush().unwrap();
-        },
-        &event_loop.handle(),
-        unsafe { dconf_client_new() },
-    ));
-    rustbar::insert_into_loop(&mut event_loop, &mut shared_data);
-
-    event_loop.run(None, &mut shared_data, |_| {}).unwrap();
+    stdout
+        .write_all(b"{\"version\":1,\"click_events\":true}\n[\n[]\n")
+        .unwrap();
+
+    // stdin isn't read through std, its buffer would hide lines from
+    // the event loop
+    let stdin = unsafe { File::from_raw_fd(0) };
+    event_loop
+        .handle()
+        .insert_source(
+            Generic::new(stdin, Interest::READ, Mode::Level),
+            |_, stdin, data| {
+                let mut buf = [0; 1024];
+                match (&**stdin).read(&mut buf) {
+                    // i3bar went away
+                    Ok(0) => return Ok(PostAction::Remove),
+                    Ok(n) => data.stdin.extend_from_slice(&buf[..n]),
+                    Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(_) => return Ok(PostAction::Remove),
+                }
+                data.read_clicks();
+                Ok(PostAction::Continue)
+            },
+        )
+        .unwrap();
+
+    let mut i3bar = I3Bar {
+        shared_data: SharedData::new(event_loop.handle(), dconf),
+        stdout,
+        output,
+        color_active: color_active(dconf),
+        color_inactive: color_inactive(dconf),
+        stdin: Vec::new(),
+    };
+    i3bar.redraw();
+
+    event_loop.run(None, &mut i3bar, |_| {}).unwrap();
 }
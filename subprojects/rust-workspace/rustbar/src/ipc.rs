@@ -0,0 +1,430 @@
+//! The protocol spoken on `$XDG_RUNTIME_DIR/rustbar-0`. A client sends
+//! one JSON request on a line, like `{"version":1,"request":"get"}`, and
+//! gets JSON back. A client that closes its end without sending anything
+//! gets the table `cat_sock` has always printed.
+//!
+//! `{"version":1,"request":"command","command":"launcher run"}` drives the
+//! bar the way dwl's `exec_wob_command` does, see [`Command`] for the
+//! commands. Frontends that can't do a command answer with an error
+use std::{
+    io::{BufWriter, ErrorKind, Read, Write},
+    os::unix::net::UnixStream,
+    time::Duration,
+};
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use smithay_client_toolkit::reexports::calloop::{
+    generic::Generic, Interest, LoopHandle, Mode, PostAction,
+};
+
+use crate::{
+    components::{battery::ChargeThreshold, Block},
+    Frontend,
+};
+
+pub const VERSION: u64 = 1;
+
+/// Requests longer than this are cut off with an error
+const MAX_REQUEST: usize = 64 * 1024;
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "request", rename_all = "lowercase")]
+pub enum Request {
+    /// Every block's state, once
+    Get,
+    /// Every block's state, then one line per change
+    Subscribe,
+    /// Does what dwl's `exec_wob_command` would, and a bit more
+    Command { command: Command },
+}
+
+/// A command as it's written on the command line, like `progress 42 󰕾`
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Command {
+    /// `launcher run`
+    LaunchApp,
+    /// `launcher browser`
+    LaunchBrowser,
+    /// `launcher windows`
+    WindowSwitcher,
+    /// `launcher calc`
+    Calculator,
+    /// `launcher glyphs`
+    GlyphPicker,
+    /// `launcher clipboard`
+    ClipboardHistory,
+    /// `progress <percent> <icon>`
+    Progress { percentage: f32, icon: char },
+    /// `notify <text>`
+    Notify(String),
+    /// `overlay toggle`
+    OverlayToggle,
+    /// `battery threshold on|off|<start> <end>`
+    ChargeThreshold(ChargeThreshold),
+    /// `reload`, creates every block again and reads the layout from
+    /// dconf
+    Reload,
+}
+
+impl TryFrom<String> for Command {
+    type Error = String;
+
+    fn try_from(command: String) -> Result<Self, Self::Error> {
+        let command = command.trim();
+        let (name, args) = command.split_once(' ').unwrap_or((command, ""));
+        let args = args.trim_start();
+        match (name, args) {
+            ("launcher", "run") => Ok(Command::LaunchApp),
+            ("launcher", "browser") => Ok(Command::LaunchBrowser),
+            ("launcher", "windows") => Ok(Command::WindowSwitcher),
+            ("launcher", "calc") => Ok(Command::Calculator),
+            ("launcher", "glyphs") => Ok(Command::GlyphPicker),
+            ("launcher", "clipboard") => Ok(Command::ClipboardHistory),
+            ("launcher", _) => {
+                Err("usage: launcher run|browser|windows|calc|glyphs|clipboard".to_owned())
+            }
+            ("progress", args) => {
+                let mut args = args.split_whitespace();
+                let percentage = args
+                    .next()
+                    .and_then(|p| p.parse::<f32>().ok())
+                    .filter(|p| (0.0..=100.0).contains(p));
+                let mut icon = args.next().unwrap_or_default().chars();
+                match (percentage, icon.next(), icon.next(), args.next()) {
+                    (Some(percentage), Some(icon), None, None) => Ok(Command::Progress {
+                        percentage: percentage / 100.0,
+                        icon,
+                    }),
+                    _ => Err("usage: progress <0-100> <icon>".to_owned()),
+                }
+            }
+            ("notify", "") => Err("usage: notify <text>".to_owned()),
+            ("notify", text) => Ok(Command::Notify(text.to_owned())),
+            ("overlay", "toggle") => Ok(Command::OverlayToggle),
+            ("overlay", _) => Err("usage: overlay toggle".to_owned()),
+            ("battery", args) => {
+                let usage = || "usage: battery threshold on|off|<start> <end>".to_owned();
+                let args: Vec<&str> = args.split_whitespace().collect();
+                match args[..] {
+                    ["threshold", "on"] => {
+                        Ok(Command::ChargeThreshold(ChargeThreshold::Enabled(true)))
+                    }
+                    ["threshold", "off"] => {
+                        Ok(Command::ChargeThreshold(ChargeThreshold::Enabled(false)))
+                    }
+                    ["threshold", start, end] => match (start.parse(), end.parse()) {
+                        (Ok(start), Ok(end)) if start < end && end <= 100 => {
+                            Ok(Command::ChargeThreshold(ChargeThreshold::Custom {
+                                start,
+                                end,
+                            }))
+                        }
+                        _ => Err(usage()),
+                    },
+                    _ => Err(usage()),
+                }
+            }
+            ("reload", "") => Ok(Command::Reload),
+            _ => Err(format!("unknown command `{}`", command)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Version {
+    version: u64,
+}
+
+pub fn parse_request(line: &str) -> Result<Request, String> {
+    let value: Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+    let Version { version } = Version::deserialize(&value).map_err(|e| e.to_string())?;
+    if version != VERSION {
+        return Err(format!(
+            "unsupported protocol version {}, rustbar speaks {}",
+            version, VERSION
+        ));
+    }
+    Request::deserialize(value).map_err(|e| e.to_string())
+}
+
+/// Every block's state, keyed by the block's name in
+/// `/dotfiles/somebar/blocks`
+pub fn blocks_json(blocks: &[Box<dyn Block>]) -> Map<String, Value> {
+    blocks
+        .iter()
+        .map(|block| (block.kind().name().to_owned(), block.json()))
+        .collect()
+}
+
+fn write_line(mut stream: &UnixStream, value: &Value) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    stream.write_all(&line)
+}
+
+/// Clients that sent `subscribe`
+#[derive(Default)]
+pub struct Subscribers {
+    streams: Vec<UnixStream>,
+    /// What the subscribers were last told about
+    last: Map<String, Value>,
+}
+
+impl Subscribers {
+    pub fn add(&mut self, stream: UnixStream, blocks: &[Box<dyn Block>]) {
+        if self.streams.is_empty() {
+            self.last = blocks_json(blocks);
+        }
+        let state = json!({
+            "version": VERSION,
+            "event": "state",
+            "blocks": self.last,
+        });
+        if write_line(&stream, &state).is_ok() {
+            self.streams.push(stream);
+        }
+    }
+
+    /// Tells every subscriber about the blocks that changed since the
+    /// last call. Subscribers that went away or stopped reading are
+    /// dropped
+    pub fn notify(&mut self, blocks: &[Box<dyn Block>]) {
+        if self.streams.is_empty() {
+            return;
+        }
+
+        let current = blocks_json(blocks);
+        let mut events = Vec::new();
+        for (name, state) in current.iter() {
+            if self.last.get(name) != Some(state) {
+                events.push(json!({
+                    "version": VERSION,
+                    "event": "changed",
+                    "block": name,
+                    "state": state,
+                }));
+            }
+        }
+        for name in self.last.keys() {
+            if !current.contains_key(name) {
+                events.push(json!({
+                    "version": VERSION,
+                    "event": "removed",
+                    "block": name,
+                }));
+            }
+        }
+        self.last = current;
+
+        self.streams
+            .retain(|stream| events.iter().all(|event| write_line(stream, event).is_ok()));
+    }
+}
+
+/// Waits for the client's request without blocking the bar
+pub fn read_request<F: Frontend>(stream: UnixStream, loop_handle: &LoopHandle<'static, F>) {
+    if stream.set_nonblocking(true).is_err() {
+        return;
+    }
+    let mut request = Vec::new();
+    let _ = loop_handle.insert_source(
+        Generic::new(stream, Interest::READ, Mode::Level),
+        move |_, stream, data: &mut F| {
+            let stream: &UnixStream = stream;
+            let mut buf = [0; 1024];
+            loop {
+                match (&*stream).read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        request.extend_from_slice(&buf[..n]);
+                        if request.contains(&b'\n') || request.len() > MAX_REQUEST {
+                            break;
+                        }
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        return Ok(PostAction::Continue);
+                    }
+                    Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(_) => return Ok(PostAction::Remove),
+                }
+            }
+
+            // The answer is written in one go, a client that doesn't read
+            // it in time is given up on
+            let _ = stream.set_nonblocking(false);
+            let _ = stream.set_write_timeout(Some(Duration::from_millis(100)));
+            let line = request.split(|&b| b == b'\n').next().unwrap_or_default();
+            respond(line, stream, data);
+            Ok(PostAction::Remove)
+        },
+    );
+}
+
+fn respond<F: Frontend>(line: &[u8], stream: &UnixStream, data: &mut F) {
+    if line.iter().all(u8::is_ascii_whitespace) {
+        if let Ok(stream) = stream.try_clone() {
+            let _ = data.shared_data().fmt_table(&mut BufWriter::new(stream));
+        }
+        return;
+    }
+
+    let request = std::str::from_utf8(line)
+        .map_err(|e| e.to_string())
+        .and_then(parse_request);
+    match request {
+        Ok(Request::Get) => {
+            let _ = write_line(
+                stream,
+                &json!({
+                    "version": VERSION,
+                    "blocks": blocks_json(&data.shared_data().blocks),
+                }),
+            );
+        }
+        Ok(Request::Subscribe) => {
+            if let Ok(stream) = stream.try_clone() {
+                let shared_data = data.shared_data();
+                shared_data.subscribers.add(stream, &shared_data.blocks);
+            }
+        }
+        Ok(Request::Command { command }) => {
+            let reply = match data.command(command) {
+                Ok(()) => json!({ "version": VERSION, "ok": true }),
+                Err(error) => json!({ "version": VERSION, "error": error }),
+            };
+            let _ = write_line(stream, &reply);
+        }
+        Err(error) => {
+            let _ = write_line(
+                stream,
+                &json!({
+                    "version": VERSION,
+                    "error": error,
+                }),
+            );
+        }
+    }
+}
+
+#[test]
+fn test_parse_request() {
+    assert_eq!(
+        parse_request(r#"{"version":1,"request":"get"}"#),
+        Ok(Request::Get)
+    );
+    assert_eq!(
+        parse_request(r#"{"request":"subscribe","version":1}"#),
+        Ok(Request::Subscribe)
+    );
+    assert!(parse_request(r#"{"version":2,"request":"get"}"#)
+        .unwrap_err()
+        .contains("version"));
+    assert!(parse_request(r#"{"request":"get"}"#).is_err());
+    assert!(parse_request(r#"{"version":1,"request":"explode"}"#).is_err());
+    assert!(parse_request("get").is_err());
+}
+
+#[test]
+fn test_parse_command() {
+    let command = |command: &str| {
+        parse_request(
+            &json!({ "version": 1, "request": "command", "command": command }).to_string(),
+        )
+    };
+    assert_eq!(
+        command("launcher run"),
+        Ok(Request::Command {
+            command: Command::LaunchApp
+        })
+    );
+    assert_eq!(
+        command("launcher browser"),
+        Ok(Request::Command {
+            command: Command::LaunchBrowser
+        })
+    );
+    assert_eq!(
+        command("launcher windows"),
+        Ok(Request::Command {
+            command: Command::WindowSwitcher
+        })
+    );
+    assert_eq!(
+        command("launcher calc"),
+        Ok(Request::Command {
+            command: Command::Calculator
+        })
+    );
+    assert_eq!(
+        command("launcher glyphs"),
+        Ok(Request::Command {
+            command: Command::GlyphPicker
+        })
+    );
+    assert_eq!(
+        command("launcher clipboard"),
+        Ok(Request::Command {
+            command: Command::ClipboardHistory
+        })
+    );
+    assert_eq!(
+        command("progress 42 󰕾"),
+        Ok(Request::Command {
+            command: Command::Progress {
+                percentage: 0.42,
+                icon: '󰕾'
+            }
+        })
+    );
+    assert_eq!(
+        command("notify  backup done"),
+        Ok(Request::Command {
+            command: Command::Notify("backup done".to_owned())
+        })
+    );
+    assert_eq!(
+        command("overlay toggle"),
+        Ok(Request::Command {
+            command: Command::OverlayToggle
+        })
+    );
+    assert_eq!(
+        command("battery threshold 40 80"),
+        Ok(Request::Command {
+            command: Command::ChargeThreshold(ChargeThreshold::Custom { start: 40, end: 80 })
+        })
+    );
+    assert_eq!(
+        command("battery threshold off"),
+        Ok(Request::Command {
+            command: Command::ChargeThreshold(ChargeThreshold::Enabled(false))
+        })
+    );
+    assert_eq!(
+        command("reload"),
+        Ok(Request::Command {
+            command: Command::Reload
+        })
+    );
+    for bad in [
+        "",
+        "launcher",
+        "launcher ssh",
+        "progress",
+        "progress 142 x",
+        "progress 42",
+        "progress 42 xy",
+        "notify",
+        "overlay on",
+        "battery",
+        "battery threshold",
+        "battery threshold 80 40",
+        "battery threshold 40 101",
+        "battery threshold -1 80",
+        "reload now",
+    ] {
+        assert!(command(bad).is_err(), "{}", bad);
+    }
+}
@@ -0,0 +1,19 @@
+// This code was autogenerated with `dbus-codegen-rust -s -g -m None -d net.connman.iwd --interfaces net.connman.iwd.StationDiagnostic`, see https://github.com/diwic/dbus-rs
+// Stations and networks are read with `GetManagedObjects`, see `crate::bluez`
+use dbus;
+#[allow(unused_imports)]
+use dbus::arg;
+use dbus::blocking;
+
+pub trait NetConnmanIwdStationDiagnostic {
+    fn get_diagnostics(&self) -> Result<arg::PropMap, dbus::Error>;
+}
+
+impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>>
+    NetConnmanIwdStationDiagnostic for blocking::Proxy<'a, C>
+{
+    fn get_diagnostics(&self) -> Result<arg::PropMap, dbus::Error> {
+        self.method_call("net.connman.iwd.StationDiagnostic", "GetDiagnostics", ())
+            .and_then(|r: (arg::PropMap,)| Ok(r.0))
+    }
+}
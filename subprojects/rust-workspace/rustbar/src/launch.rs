@@ -0,0 +1,44 @@
+//! Starting apps from the launcher without tying them to rustbar
+use std::{
+    fs::File,
+    os::unix::process::CommandExt,
+    process::{Command, Stdio},
+};
+
+use nix::unistd::{fork, setsid, ForkResult};
+
+/// `$XDG_STATE_HOME/rustbar/logs/<name>.log`, emptied every time `name`
+/// is launched
+fn log_file(name: &str) -> Option<File> {
+    let dir = dirs::state_dir()?.join("rustbar").join("logs");
+    std::fs::create_dir_all(&dir).ok()?;
+    File::create(dir.join(format!("{}.log", name.replace('/', "_")))).ok()
+}
+
+/// Runs `command` in a session of its own with its output going to the
+/// log for `name`. The app is forked off twice, so it's adopted by init
+/// and rustbar never has to reap it. Failing to run the program is
+/// still reported here, the error comes back before the exec
+pub fn spawn(mut command: Command, name: &str) -> std::io::Result<()> {
+    command.stdin(Stdio::null());
+    match log_file(name) {
+        Some(log) => {
+            command.stdout(log.try_clone()?).stderr(log);
+        }
+        None => {
+            command.stdout(Stdio::null()).stderr(Stdio::null());
+        }
+    }
+    unsafe {
+        command.pre_exec(|| {
+            setsid()?;
+            match fork()? {
+                ForkResult::Parent { .. } => nix::libc::_exit(0),
+                ForkResult::Child => Ok(()),
+            }
+        });
+    }
+    // Only the child in between is waited for, it exits straight away
+    command.spawn()?.wait()?;
+    Ok(())
+}
@@ -0,0 +1,795 @@
+//! The blocks of the status bar and everything that keeps them up to date.
+//! The Wayland bar (`rustbar`) and the i3bar protocol frontend
+//! (`rustbar-i3`) are both built on top of [`SharedData`]
+use std::ffi::CString;
+use std::io::BufWriter;
+use std::io::Write;
+use std::os::unix::net::UnixListener;
+use std::os::unix::net::UnixStream;
+use std::process::Command;
+use std::ptr::NonNull;
+use std::time::Duration;
+
+use calloop_dbus::SyncDBusSource;
+use color::DefaultColorParser;
+use components::{
+    battery::{BatteryBlock, BatteryWarning, ChargeThreshold},
+    bluetooth::BluetoothBlock,
+    brightness::BrightnessBlock,
+    network::NetworkBlock,
+    playback::PlaybackBlock,
+    time::{TimeBlock, NTP_SERVERS},
+    wireplumber::WirePlumberBlock,
+    Block, BlockKind, Handles, Layout,
+};
+use cssparser::{Parser, ParserInput};
+use dbus::message::MatchRule;
+use dconf_sys::dconf_client_read;
+use dconf_sys::DConfClient;
+use glib::variant::FromVariant;
+use iced_tiny_skia::core::{Color, Font};
+use logind::OrgFreedesktopLogin1Manager;
+use palette::IntoColor;
+use smithay_client_toolkit::reexports::calloop::{self, generic::Generic, Interest, LoopHandle};
+
+mod backlight;
+mod bluez;
+mod connman;
+mod dconf;
+mod iwd;
+mod logind;
+mod mpris;
+mod networkmanager;
+mod upower;
+mod wireplumber;
+
+pub mod components;
+pub mod ipc;
+
+include!(concat!(env!("OUT_DIR"), "/kinds.rs"));
+
+#[macro_export]
+macro_rules! add_match {
+    ($bus:expr,$sender:expr) => {
+        $bus.add_match::<$crate::upower::OrgFreedesktopDBusPropertiesPropertiesChanged, _>(
+            dbus::message::MatchRule::new_signal(
+                "org.freedesktop.DBus.Properties",
+                "PropertiesChanged",
+            )
+            .with_sender($sender),
+            |_, _, _| true,
+        )
+        .unwrap()
+    };
+    ($bus:expr,$sender:expr,$signal:expr) => {
+        $bus.add_match::<$crate::upower::OrgFreedesktopDBusPropertiesPropertiesChanged, _>(
+            dbus::message::MatchRule::new_signal($sender, $signal).with_sender($sender),
+            |_, _, _| true,
+        )
+        .unwrap()
+    };
+    ($bus:expr,$sender:expr,$interface:expr,$signal:expr) => {
+        $bus.add_match::<$crate::upower::OrgFreedesktopDBusPropertiesPropertiesChanged, _>(
+            dbus::message::MatchRule::new_signal($interface, $signal).with_sender($sender),
+            |_, _, _| true,
+        )
+        .unwrap()
+    };
+}
+
+/// What shows the blocks. The event loop's data is the frontend, and
+/// the blocks reach their own state through [`Frontend::shared_data`]
+pub trait Frontend: Sized + 'static {
+    fn shared_data(&mut self) -> &mut SharedData<Self>;
+
+    /// Something a block shows changed
+    fn redraw(&mut self);
+
+    /// The volume was changed from outside of the bar, the Wayland bar
+    /// shows its progress bar for this
+    fn progress(&mut self, _percentage: f32, _icon: char) {}
+
+    /// Something that needs attention right away, like a battery that's
+    /// about to run out. The Wayland bar shows it over everything
+    fn notify(&mut self, _text: String) {}
+
+    /// A key in `/dotfiles/somebar` changed. Keys that belong to the
+    /// blocks have already been handled when this is called
+    fn setting_changed(&mut self, _kind: NodeKind) {}
+
+    /// A command sent on the socket, the error is sent back to the client
+    fn command(&mut self, command: ipc::Command) -> Result<(), String>;
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SelectedBlock {
+    /// The index of the block in `SharedData::blocks`, and the segment
+    /// within that block
+    Block(usize, usize),
+    None,
+}
+
+pub struct SharedData<F: 'static> {
+    /// Every enabled block, in the same order as `layout`
+    pub blocks: Vec<Box<dyn Block>>,
+    pub layout: Layout,
+    pub handles: Handles,
+    loop_handle: LoopHandle<'static, F>,
+    pub dconf: *mut DConfClient,
+    pub selected: SelectedBlock,
+    /// Clients of the socket that want to know when a block changes
+    pub subscribers: ipc::Subscribers,
+    settings_parser: tree_sitter::Parser,
+}
+
+impl<F: Frontend> SharedData<F> {
+    pub fn new(handle: LoopHandle<'static, F>, dconf: *mut DConfClient) -> Self {
+        unsafe {
+            let (user_connection, _): (calloop_dbus::SyncDBusSource<()>, _) =
+                calloop_dbus::SyncDBusSource::new_session().unwrap();
+            let (system_connection, _): (calloop_dbus::SyncDBusSource<()>, _) =
+                calloop_dbus::SyncDBusSource::new_system().unwrap();
+
+            let user_connection_ptr = Box::into_raw(Box::new(user_connection));
+            let system_connection_ptr = Box::into_raw(Box::new(system_connection));
+
+            let user_connection: &'static mut SyncDBusSource<()> = &mut *user_connection_ptr;
+            let system_connection: &'static mut SyncDBusSource<()> = &mut *system_connection_ptr;
+
+            user_connection
+                .add_match::<upower::OrgFreedesktopDBusPropertiesPropertiesChanged, _>(
+                    MatchRule::new_signal("ca.desrt.dconf.Writer", "Notify"),
+                    |_, _, _| true,
+                )
+                .unwrap();
+
+            let handles = Handles {
+                loop_handle: Box::new(handle.clone()),
+                user_connection: &*user_connection_ptr,
+                system_connection: &*system_connection_ptr,
+            };
+
+            system_connection
+                .add_match(
+                    MatchRule::new_signal("org.freedesktop.login1.Manager", "PrepareForSleep"),
+                    |_: (), _, _| true,
+                )
+                .unwrap();
+
+            handle
+                .insert_source(user_connection, move |event, _, data: &mut F| {
+                    let Some(member) = event.member() else {
+                        return None;
+                    };
+                    if &*member == "PropertiesChanged" {
+                        let properties_changed: mpris::OrgFreedesktopDBusPropertiesPropertiesChanged = event.read_all().unwrap();
+                        if properties_changed.interface_name == "org.wireplumber.DefaultNode"
+                        {
+                            if let Some(wireplumber) = data.shared_data().block_mut::<WirePlumberBlock>() {
+                                wireplumber.query_default_node(properties_changed);
+                                let percentage = wireplumber.volume as f32;
+                                let icon = wireplumber.volume_level().chars().next().unwrap();
+                                data.progress(percentage, icon);
+                            }
+                            data.redraw();
+                        } else if let Some(media) = data.shared_data().block_mut::<PlaybackBlock>() {
+                            if media.query_media(properties_changed) {
+                                data.redraw();
+                            }
+                        }
+                    } else if &*member == "Notify" {
+                        let property: dconf::CaDesrtDconfWriterNotify = event.read_all().unwrap();
+
+                        for p in property.changes {
+                            let mut new_prop = property.prefix.clone();
+                            new_prop.push_str(&p);
+                            let Some(kind) = data
+                                .shared_data()
+                                .settings_parser
+                                .parse(new_prop, None)
+                                .as_ref()
+                                .and_then(|t| t.root_node().child(0))
+                                .map(|n| NodeKind::from(n.kind_id()))
+                            else {
+                                continue;
+                            };
+                            setting_changed(data, kind);
+                            data.setting_changed(kind);
+                        }
+                    }
+                    None
+                })
+                .unwrap();
+
+            let system_connection: &'static mut SyncDBusSource<()> = &mut *system_connection_ptr;
+
+            handle
+                .insert_source(system_connection, move |event, dbus, data: &mut F| {
+                    let Some(member) = event.member() else {
+                        return None;
+                    };
+                    let shared_data = data.shared_data();
+                    if &*member == "PropertiesChanged" {
+                        let property: upower::OrgFreedesktopDBusPropertiesPropertiesChanged =
+                            event.read_all().unwrap();
+                        if property.interface_name.starts_with("org.bluez.") {
+                            if let Some(bluetooth) = shared_data.block_mut::<BluetoothBlock>() {
+                                if bluetooth
+                                    .query_properties(event.path().unwrap().into_static(), property)
+                                {
+                                    data.redraw();
+                                }
+                            }
+                        } else if property
+                            .interface_name
+                            .starts_with("org.freedesktop.NetworkManager")
+                            || property.interface_name.starts_with("net.connman.iwd")
+                        {
+                            if let Some(network) = shared_data.block_mut::<NetworkBlock>() {
+                                if network.query_network(dbus) {
+                                    shared_data.sync_network_time();
+                                    data.redraw();
+                                }
+                            }
+                        } else if let Some(bat_block) = shared_data.block_mut::<BatteryBlock>() {
+                            let warning = bat_block
+                                .query_battery(event.path().unwrap().into_static(), property);
+                            if let Some(warning) = warning {
+                                warn_battery(data, warning);
+                            }
+                            data.redraw();
+                        }
+                    } else if &*member == "InterfacesAdded" {
+                        if let Some(bluetooth) = shared_data.block_mut::<BluetoothBlock>() {
+                            bluetooth.interfaces_added(event);
+
+                            data.redraw();
+                        }
+                    } else if &*member == "InterfacesRemoved" {
+                        if let Some(bluetooth) = shared_data.block_mut::<BluetoothBlock>() {
+                            bluetooth.interfaces_removed(event);
+
+                            data.redraw();
+                        }
+                    } else if &*member == "PropertyChanged" {
+                        if let Some(network) = shared_data.block_mut::<NetworkBlock>() {
+                            if network.query_network(dbus) {
+                                shared_data.sync_network_time();
+                                data.redraw();
+                            }
+                        }
+                    } else if &*member == "NameOwnerChanged" {
+                        if let (Some(network), Ok(name)) = (
+                            shared_data.block_mut::<NetworkBlock>(),
+                            event.read1::<&str>(),
+                        ) {
+                            if network.name_owner_changed(dbus, name) {
+                                shared_data.sync_network_time();
+                                data.redraw();
+                            }
+                        }
+                    } else if &*member == "DeviceAdded" {
+                        if let Some(bat_block) = shared_data.block_mut::<BatteryBlock>() {
+                            bat_block.device_added(event, dbus);
+
+                            data.redraw();
+                        }
+                    } else if &*member == "DeviceRemoved" {
+                        if let Some(bat_block) = shared_data.block_mut::<BatteryBlock>() {
+                            bat_block.device_removed(event);
+
+                            data.redraw();
+                        }
+                    } else if &*member == "PrepareForSleep" {
+                        let prepare: logind::OrgFreedesktopLogin1ManagerPrepareForSleep =
+                            event.read_all().unwrap();
+
+                        if !prepare.start && shared_data.block::<TimeBlock>().is_some() {
+                            let block = shared_data.new_block(BlockKind::Time);
+                            shared_data.insert_block(block);
+
+                            data.redraw();
+                        }
+                    }
+                    None
+                })
+                .unwrap();
+
+            let socket_file = dirs::runtime_dir().unwrap().join("rustbar-0");
+            let _ = std::fs::remove_file(&socket_file);
+            let socket = UnixListener::bind(&socket_file).unwrap();
+
+            handle
+                .insert_source(
+                    Generic::new(socket, Interest::READ, calloop::Mode::Level),
+                    move |_event, socket, data: &mut F| {
+                        let (file, _) = socket.accept().unwrap();
+                        ipc::read_request(file, &data.shared_data().loop_handle);
+
+                        Ok(calloop::PostAction::Continue)
+                    },
+                )
+                .unwrap();
+
+            let mut shared_data = Self {
+                blocks: Vec::new(),
+                layout: Layout(Vec::new()),
+                handles,
+                loop_handle: handle,
+                dconf,
+                selected: SelectedBlock::None,
+                subscribers: ipc::Subscribers::default(),
+                settings_parser: {
+                    let mut parser = tree_sitter::Parser::new();
+                    parser
+                        .set_language(&tree_sitter_dconfsomebar::language())
+                        .unwrap();
+                    parser.set_timeout_micros(500_000);
+                    parser
+                },
+            };
+
+            shared_data.set_layout(layout_from_dconf(dconf));
+
+            shared_data
+        }
+    }
+
+    fn new_block(&self, kind: BlockKind) -> Box<dyn Block> {
+        let handles = &self.handles;
+        let dconf = self.dconf;
+        match kind {
+            BlockKind::Playback => Box::new(PlaybackBlock::new(handles.user_connection)),
+            BlockKind::WirePlumber => Box::new(WirePlumberBlock::new(
+                handles.user_connection,
+                dconf_read_variant(dconf, "/dotfiles/somebar/wireplumber-max-volume")
+                    .unwrap_or(100.0)
+                    / 100.0,
+            )),
+            BlockKind::Connman => Box::new(NetworkBlock::new(
+                &self.loop_handle,
+                handles.system_connection,
+                dconf_read_variant(dconf, "/dotfiles/somebar/network-throughput").unwrap_or(true),
+            )),
+            BlockKind::Bluetooth => Box::new(BluetoothBlock::new(handles.system_connection)),
+            BlockKind::Battery => Box::new(BatteryBlock::new(
+                handles.system_connection,
+                dconf_read_variant(dconf, "/dotfiles/somebar/battery-low").unwrap_or(20.0),
+                dconf_read_variant(dconf, "/dotfiles/somebar/battery-critical").unwrap_or(5.0),
+            )),
+            BlockKind::Brightness => Box::new(BrightnessBlock::new(
+                &self.loop_handle,
+                handles.system_connection,
+            )),
+            BlockKind::Time => Box::new(TimeBlock::new(
+                &self.loop_handle,
+                dconf_read_variant(dconf, "/dotfiles/somebar/update-time-ntp").unwrap_or(true),
+                dconf_read_variant(dconf, "/dotfiles/somebar/time-servers")
+                    .unwrap_or(NTP_SERVERS.into_iter().map(|s| s.to_string()).collect()),
+                dconf_read_variant(dconf, "/dotfiles/somebar/time-fmt")
+                    .unwrap_or("%I:%M".to_owned()),
+                dconf_read_variant(dconf, "/dotfiles/somebar/date-fmt")
+                    .unwrap_or("%m/%d/%y %A".to_owned()),
+            )),
+        }
+    }
+
+    pub fn block<T: Block>(&self) -> Option<&T> {
+        self.blocks.iter().find_map(|b| b.downcast_ref())
+    }
+
+    pub fn block_mut<T: Block>(&mut self) -> Option<&mut T> {
+        self.blocks.iter_mut().find_map(|b| b.downcast_mut())
+    }
+
+    /// Adds a block in its place on the bar, replacing the block of the
+    /// same kind if there already is one. Blocks that aren't in the
+    /// layout are unregistered straight away
+    pub fn insert_block(&mut self, block: Box<dyn Block>) {
+        self.selected = SelectedBlock::None;
+        let kind = block.kind();
+        let Some(position) = self.layout.position(kind) else {
+            block.unregister(&self.handles);
+            return;
+        };
+        match self
+            .blocks
+            .iter()
+            .position(|b| self.layout.position(b.kind()) >= Some(position))
+        {
+            Some(i) if self.blocks[i].kind() == kind => {
+                std::mem::replace(&mut self.blocks[i], block).unregister(&self.handles);
+            }
+            Some(i) => self.blocks.insert(i, block),
+            None => self.blocks.push(block),
+        }
+    }
+
+    /// Rearranges the blocks, creating the enabled blocks that weren't
+    /// in the old layout and removing the ones that aren't in the new one
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.selected = SelectedBlock::None;
+        self.layout = layout;
+
+        let mut i = 0;
+        while i < self.blocks.len() {
+            if self.layout.position(self.blocks[i].kind()).is_some() {
+                i += 1;
+            } else {
+                self.blocks.remove(i).unregister(&self.handles);
+            }
+        }
+        let layout = &self.layout;
+        self.blocks
+            .sort_by_key(|b| layout.position(b.kind()).unwrap());
+
+        for (kind, _) in self.layout.0.clone() {
+            if self.blocks.iter().all(|b| b.kind() != kind)
+                && dconf_read_variant(self.dconf, kind.dconf_key()).unwrap_or(true)
+            {
+                let block = self.new_block(kind);
+                self.insert_block(block);
+            }
+        }
+        self.sync_network_time();
+    }
+
+    /// Reads the layout from dconf and creates every block again, for
+    /// things blocks only look at once, like which backlights there are
+    pub fn reload(&mut self) {
+        for block in self.blocks.drain(..) {
+            block.unregister(&self.handles);
+        }
+        self.set_layout(layout_from_dconf(self.dconf));
+    }
+
+    pub fn remove_block(&mut self, kind: BlockKind) {
+        self.selected = SelectedBlock::None;
+        if let Some(i) = self.blocks.iter().position(|b| b.kind() == kind) {
+            self.blocks.remove(i).unregister(&self.handles);
+        }
+    }
+
+    /// Lets the time block know whether the network block is online, so
+    /// the time can be synced
+    pub fn sync_network_time(&mut self) {
+        let Some(online) = self.block::<NetworkBlock>().map(|c| c.is_online()) else {
+            return;
+        };
+        if let Some(time) = self.block_mut::<TimeBlock>() {
+            time.network_changed(online);
+        }
+    }
+
+    /// Tells the clients that sent `subscribe` about the blocks that
+    /// changed, frontends call this whenever they redraw
+    pub fn notify_subscribers(&mut self) {
+        self.subscribers.notify(&self.blocks);
+    }
+
+    pub fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
+        f.write_all(b"\n")?;
+        self.blocks
+            .iter()
+            .rev()
+            .try_for_each(|block| block.fmt_table(f))
+    }
+}
+
+/// Handles the dconf keys that belong to the blocks, and redraws if
+/// `kind` was one of them
+fn setting_changed<F: Frontend>(data: &mut F, kind: NodeKind) {
+    let shared_data = data.shared_data();
+    let dconf = shared_data.dconf;
+    match kind {
+        NodeKind::TimeBlock
+        | NodeKind::BrightnessBlock
+        | NodeKind::BatteryBlock
+        | NodeKind::ConnmanBlock
+        | NodeKind::BluetoothBlock
+        | NodeKind::MediaBlock
+        | NodeKind::WireplumberBlock => {
+            let kind = BlockKind::try_from(kind).unwrap();
+            if shared_data.layout.position(kind).is_some()
+                && dconf_read_variant(dconf, kind.dconf_key()).unwrap_or(true)
+            {
+                let block = shared_data.new_block(kind);
+                shared_data.insert_block(block);
+                if kind == BlockKind::Connman {
+                    shared_data.sync_network_time();
+                }
+            } else {
+                shared_data.remove_block(kind);
+            }
+        }
+        NodeKind::Blocks => shared_data.set_layout(layout_from_dconf(dconf)),
+        NodeKind::DateFmt => {
+            if let Some(time) = shared_data.block_mut::<TimeBlock>() {
+                time.date_fmt = dconf_read_variant(dconf, "/dotfiles/somebar/date-fmt")
+                    .unwrap_or_else(|| "%m/%d/%y %A".to_owned());
+            }
+        }
+        NodeKind::TimeFmt => {
+            if let Some(time) = shared_data.block_mut::<TimeBlock>() {
+                time.time_fmt = dconf_read_variant(dconf, "/dotfiles/somebar/time-fmt")
+                    .unwrap_or_else(|| "%I:%M".to_owned());
+            }
+        }
+        NodeKind::UpdateTimeNtp => {
+            if let Some(time) = shared_data.block_mut::<TimeBlock>() {
+                time.update_time_ntp =
+                    dconf_read_variant(dconf, "/dotfiles/somebar/update-time-ntp").unwrap_or(true);
+            }
+        }
+        NodeKind::WireplumberMaxVolume => {
+            if let Some(wireplumber) = shared_data.block_mut::<WirePlumberBlock>() {
+                wireplumber.max_volume =
+                    dconf_read_variant(dconf, "/dotfiles/somebar/wireplumber-max-volume")
+                        .unwrap_or(100.0)
+                        / 100.0;
+            }
+        }
+        NodeKind::BatteryLow | NodeKind::BatteryCritical => {
+            if let Some(battery) = shared_data.block_mut::<BatteryBlock>() {
+                battery.low =
+                    dconf_read_variant(dconf, "/dotfiles/somebar/battery-low").unwrap_or(20.0);
+                battery.critical =
+                    dconf_read_variant(dconf, "/dotfiles/somebar/battery-critical").unwrap_or(5.0);
+            }
+        }
+        NodeKind::NetworkThroughput => {
+            if shared_data.block::<NetworkBlock>().is_some() {
+                let block = shared_data.new_block(BlockKind::Connman);
+                shared_data.insert_block(block);
+            }
+        }
+        NodeKind::TimeServers => {
+            if let Some(time) = shared_data.block_mut::<TimeBlock>() {
+                time.time_servers = dconf_read_variant(dconf, "/dotfiles/somebar/time-servers")
+                    .unwrap_or(NTP_SERVERS.into_iter().map(|s| s.to_string()).collect());
+                time.update_time();
+            }
+        }
+        _ => return,
+    }
+    data.redraw();
+}
+
+/// Does `battery threshold` for the frontends
+pub fn set_charge_threshold<F: Frontend>(
+    data: &mut F,
+    threshold: ChargeThreshold,
+) -> Result<(), String> {
+    data.shared_data()
+        .block_mut::<BatteryBlock>()
+        .ok_or_else(|| "the battery block isn't on the bar".to_owned())?
+        .set_charge_threshold(threshold)?;
+    data.redraw();
+    Ok(())
+}
+
+/// Shows `warning`. For the computer's batteries it also runs
+/// `battery-hook` and does what `battery-action` says at UPower's action
+/// level, which is next to UPower's own action
+fn warn_battery<F: Frontend>(data: &mut F, warning: BatteryWarning) {
+    let shared_data = data.shared_data();
+    let dconf = shared_data.dconf;
+    let system_connection = shared_data.handles.system_connection;
+    let level = match warning.level {
+        upower::WarningLevel::Low => "low",
+        upower::WarningLevel::Critical => "critical",
+        _ => "action",
+    };
+
+    if !warning.battery {
+        data.notify(warning.text);
+        return;
+    }
+
+    let hook: String =
+        dconf_read_variant(dconf, "/dotfiles/somebar/battery-hook").unwrap_or_default();
+    if !hook.is_empty() {
+        if let Ok(mut child) = Command::new("sh")
+            .arg("-c")
+            .arg(hook)
+            .env("BATTERY_LEVEL", level)
+            .env("BATTERY_PERCENTAGE", warning.percentage.to_string())
+            .spawn()
+        {
+            std::thread::spawn(move || child.wait());
+        }
+    }
+
+    if warning.level != upower::WarningLevel::Action {
+        data.notify(warning.text);
+        return;
+    }
+    let action: String = dconf_read_variant(dconf, "/dotfiles/somebar/battery-action")
+        .unwrap_or_else(|| "nothing".to_owned());
+    let manager = system_connection.with_proxy(
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1",
+        Duration::from_secs(5),
+    );
+    let result = match action.as_str() {
+        "suspend" => manager.suspend(false),
+        "hibernate" => manager.hibernate(false),
+        _ => {
+            data.notify(warning.text);
+            return;
+        }
+    };
+    data.notify(match result {
+        Ok(()) => format!("{}, going to {}", warning.text, action),
+        Err(e) => format!(
+            "{}, couldn't {}: {}",
+            warning.text,
+            action,
+            e.message().unwrap_or_default()
+        ),
+    });
+}
+
+pub struct BarSettings {
+    pub color_active: (Color, Color),
+    pub color_inactive: (Color, Color),
+    pub default_font_fallback_name: String,
+    pub default_font_name: String,
+    pub default_font: Font,
+    pub padding_x: f32,
+    pub padding_y: f32,
+    pub bar_show_time: u64,
+    pub browser_path: String,
+    pub browser: String,
+    /// What the browser launcher searches with, `%s` is the query
+    pub search_engine: String,
+    /// Launcher entries with `Terminal=true` are appended to this
+    pub terminal: String,
+    /// Selections offering one of these MIME types stay out of the
+    /// clipboard history
+    pub clipboard_exclude: Vec<String>,
+    pub divider: char,
+    pub top_bar: bool,
+}
+
+fn parse_color(
+    color_input: &mut [palette::Srgba; 2],
+    dconf_path: &str,
+    dconf_client: *mut DConfClient,
+) {
+    if let Some((color_one, color_two)) =
+        dconf_read_variant::<(String, String)>(dconf_client, dconf_path)
+    {
+        if let Ok((_, color)) = color::parse_color_with(
+            &mut DefaultColorParser::new(Some(&mut color::Color::LinSrgb(
+                color_input[0].into_linear(),
+            ))),
+            &mut Parser::new(&mut ParserInput::new(&color_one)),
+        ) {
+            let color: palette::LinSrgba = color.into_color();
+            color_input[0] = palette::Srgba::from_linear(color);
+        }
+
+        if let Ok((_, color)) = color::parse_color_with(
+            &mut DefaultColorParser::new(Some(&mut color::Color::LinSrgb(
+                color_input[1].into_linear(),
+            ))),
+            &mut Parser::new(&mut ParserInput::new(&color_two)),
+        ) {
+            let color: palette::LinSrgba = color.into_color();
+            color_input[1] = palette::Srgba::from_linear(color);
+        }
+    }
+}
+
+/// The text and background colors in `dconf_path`, `colors` are the
+/// defaults
+fn read_colors(
+    dconf_client: *mut DConfClient,
+    dconf_path: &str,
+    mut colors: [palette::Srgba; 2],
+) -> (Color, Color) {
+    parse_color(&mut colors, dconf_path, dconf_client);
+    let [text, background] =
+        colors.map(|color| Color::from_rgba(color.red, color.green, color.blue, color.alpha));
+    (text, background)
+}
+
+/// The colors of the bar on the selected monitor, and of urgent and
+/// hovered segments
+pub fn color_active(dconf: *mut DConfClient) -> (Color, Color) {
+    read_colors(
+        dconf,
+        "/dotfiles/somebar/color-active",
+        [
+            palette::Srgba::from_components((1.0, 0.56, 0.25, 1.0)),
+            palette::Srgba::from_components((0.2, 0.227, 0.25, 1.0)),
+        ],
+    )
+}
+
+pub fn color_inactive(dconf: *mut DConfClient) -> (Color, Color) {
+    read_colors(
+        dconf,
+        "/dotfiles/somebar/color-inactive",
+        [
+            palette::Srgba::from_components((0.701, 0.694, 0.678, 1.0)),
+            palette::Srgba::from_components((0.039, 0.054, 0.078, 1.0)),
+        ],
+    )
+}
+
+impl BarSettings {
+    pub fn new(
+        default_font_fallback_name: String,
+        default_font_name: String,
+        default_font: Font,
+        dconf: *mut DConfClient,
+        divider: char,
+    ) -> BarSettings {
+        BarSettings {
+            color_active: color_active(dconf),
+            color_inactive: color_inactive(dconf),
+            default_font_fallback_name,
+            default_font,
+            default_font_name,
+            padding_x: dconf_read_variant::<f64>(dconf, "/dotfiles/somebar/padding-x")
+                .unwrap_or(10.0) as f32,
+            padding_y: dconf_read_variant::<f64>(dconf, "/dotfiles/somebar/padding-y")
+                .unwrap_or(3.0) as f32,
+            bar_show_time: dconf_read_variant(dconf, "/dotfiles/somebar/bar-show-time")
+                .unwrap_or(500),
+            top_bar: dconf_read_variant(dconf, "/dotfiles/somebar/top-bar").unwrap_or(true),
+            browser_path: dconf_read_variant(dconf, "/dotfiles/somebar/browser-path")
+                .unwrap_or_else(|| ".firedragon".to_owned()),
+            browser: format!(
+                "{} ",
+                dconf_read_variant(dconf, "/dotfiles/somebar/browser")
+                    .unwrap_or_else(|| "firedragon".to_owned())
+            ),
+            search_engine: dconf_read_variant(dconf, "/dotfiles/somebar/search-engine")
+                .unwrap_or_else(|| "https://duckduckgo.com/?q=%s".to_owned()),
+            terminal: dconf_read_variant(dconf, "/dotfiles/somebar/terminal")
+                .unwrap_or_else(|| "foot".to_owned()),
+            clipboard_exclude: clipboard_exclude(dconf),
+            divider,
+        }
+    }
+
+    pub fn update_color_active(&mut self, dconf: *mut DConfClient) {
+        self.color_active = color_active(dconf);
+    }
+
+    pub fn update_color_inactive(&mut self, dconf: *mut DConfClient) {
+        self.color_inactive = color_inactive(dconf);
+    }
+}
+
+/// MIME types in `/dotfiles/somebar/clipboard-exclude`
+pub fn clipboard_exclude(dconf: *mut DConfClient) -> Vec<String> {
+    dconf_read_variant(dconf, "/dotfiles/somebar/clipboard-exclude")
+        .unwrap_or_else(|| vec!["x-kde-passwordManagerHint".to_owned()])
+}
+
+/// The layout in `/dotfiles/somebar/blocks`
+pub fn layout_from_dconf(dconf: *mut DConfClient) -> Layout {
+    dconf_read_variant::<Vec<String>>(dconf, "/dotfiles/somebar/blocks")
+        .map_or_else(Layout::default, |b| Layout::new(&b))
+}
+
+/// Always `None` if `dconf_client` is null, which is how `rustbar render`
+/// gets the default settings
+pub fn dconf_read_variant<T: FromVariant>(dconf_client: *mut DConfClient, path: &str) -> Option<T> {
+    if dconf_client.is_null() {
+        return None;
+    }
+    let value: Option<glib::variant::Variant> = {
+        let key = CString::new(path).unwrap();
+
+        unsafe {
+            NonNull::new(dconf_client_read(dconf_client, key.as_ptr()))
+                .map(|nn| std::mem::transmute(nn))
+        }
+    };
+    value.and_then(|v| v.get())
+}
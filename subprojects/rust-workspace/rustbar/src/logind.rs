@@ -1330,3 +1330,29 @@ impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>> OrgFreed
         )
     }
 }
+
+pub trait OrgFreedesktopLogin1Session {
+    fn set_brightness(
+        &self,
+        subsystem: &str,
+        name: &str,
+        brightness: u32,
+    ) -> Result<(), dbus::Error>;
+}
+
+impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>> OrgFreedesktopLogin1Session
+    for blocking::Proxy<'a, C>
+{
+    fn set_brightness(
+        &self,
+        subsystem: &str,
+        name: &str,
+        brightness: u32,
+    ) -> Result<(), dbus::Error> {
+        self.method_call(
+            "org.freedesktop.login1.Session",
+            "SetBrightness",
+            (subsystem, name, brightness),
+        )
+    }
+}
@@ -1,47 +1,35 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::ffi::CString;
-use std::io::BufWriter;
 use std::io::Write;
 use std::ops::AddAssign;
+use std::ops::Range;
 use std::ops::SubAssign;
-use std::os::unix::net::UnixListener;
-use std::os::unix::net::UnixStream;
-use std::ptr::NonNull;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::time::Duration;
 
+use clipboard::mime::Selection;
 use clipboard::state::SelectionTarget;
-use color::DefaultColorParser;
-use components::wireplumber::WirePlumberBlock;
-use components::{
-    battery::BatteryBlock,
-    brightness::BrightnessBlock,
-    connman::ConnmanBlock,
-    playback::PlaybackBlock,
-    time::{TimeBlock, NTP_SERVERS},
+use rustbar::{
+    clipboard_exclude,
+    components::{
+        brightness::BrightnessBlock, wireplumber::WirePlumberBlock, Block, HitBox, Layout,
+        Response, Segment, Side,
+    },
+    dconf_read_variant, ipc, set_charge_threshold, BarSettings, Frontend, NodeKind, SelectedBlock,
+    SharedData,
 };
 
-use calloop::generic::Generic;
 use calloop::EventLoop;
-use calloop::Interest;
 use calloop::LoopHandle;
 use calloop::LoopSignal;
-use calloop_dbus::SyncDBusSource;
 use client::{
     globals::registry_queue_init,
     protocol::{wl_output, wl_pointer, wl_seat, wl_shm, wl_surface},
     Connection, Proxy, QueueHandle,
 };
-use cssparser::{Parser, ParserInput};
-use dbus::message::MatchRule;
 use dconf_sys::dconf_client_new;
-use dconf_sys::dconf_client_read;
 use dconf_sys::DConfClient;
-use freedesktop_desktop_entry::default_paths;
-use freedesktop_desktop_entry::DesktopEntry;
-use glib::variant::FromVariant;
 use iced_tiny_skia::core::font::Family;
 use iced_tiny_skia::core::Background;
 use iced_tiny_skia::{
@@ -53,11 +41,8 @@ use iced_tiny_skia::{
     graphics::{backend::Text, Viewport},
     Primitive,
 };
-use memchr::memchr;
 use nucleo_matcher::pattern::Pattern;
-use palette::IntoColor;
 use push_str::PushString;
-use rusqlite::OpenFlags;
 use smithay_client_toolkit::delegate_data_device;
 use smithay_client_toolkit::delegate_keyboard;
 use smithay_client_toolkit::delegate_primary_selection;
@@ -105,27 +90,29 @@ use smithay_client_toolkit::{
         Shm, ShmHandler,
     },
 };
+use calc::{Calculator, Evaluation};
+use desktop_entries::{DesktopCommand, DesktopEntries, Target};
+use history::History;
 use tags::Tags;
-use tiny_skia::{Mask, PixmapMut};
+use tiny_skia::{Mask, PixmapMut, Transform};
 use yoke::Yokeable;
 use znet_dwl::znet_tapesoftware_dwl_wm_monitor_v1::ZnetTapesoftwareDwlWmMonitorV1;
 use znet_dwl::znet_tapesoftware_dwl_wm_v1::WobCommand;
 use znet_dwl::znet_tapesoftware_dwl_wm_v1::ZnetTapesoftwareDwlWmV1;
-use cxx::UniquePtr;
-
-mod connman;
-mod dconf;
-mod logind;
-mod mpris;
-mod upower;
-mod wireplumber;
 
+mod browser;
+mod calc;
 mod clipboard;
-mod components;
+mod desktop_entries;
+mod glyphs;
+mod history;
+mod launch;
 mod push_str;
+mod render;
 mod tags;
 
-include!(concat!(env!("OUT_DIR"), "/kinds.rs"));
+/// How long warnings from the blocks, like a low battery, stay over the bar
+const WARNING_SHOW_TIME: Duration = Duration::from_secs(5);
 
 #[allow(non_camel_case_types)]
 pub mod znet_dwl {
@@ -157,48 +144,9 @@ pub mod ffi {
         #[namespace = "giac"]
         type context;
 
-        unsafe fn eval(expr: *const c_char, ctx: *const context) -> String;
+        unsafe fn eval(expr: *const c_char, ctx: *const context) -> Result<String>;
         fn new_ctx() -> UniquePtr<context>;
-    }
-}
-
-#[macro_export]
-macro_rules! add_match {
-    ($bus:expr,$sender:expr) => {
-        $bus.add_match::<crate::upower::OrgFreedesktopDBusPropertiesPropertiesChanged, _>(
-            dbus::message::MatchRule::new_signal(
-                "org.freedesktop.DBus.Properties",
-                "PropertiesChanged",
-            )
-            .with_sender($sender),
-            |_, _, _| true,
-        )
-        .unwrap()
-    };
-    ($bus:expr,$sender:expr,$signal:expr) => {
-        $bus.add_match::<crate::upower::OrgFreedesktopDBusPropertiesPropertiesChanged, _>(
-            dbus::message::MatchRule::new_signal($sender, $signal).with_sender($sender),
-            |_, _, _| true,
-        )
-        .unwrap()
-    };
-    ($bus:expr,$sender:expr,$interface:expr,$signal:expr) => {
-        $bus.add_match::<crate::upower::OrgFreedesktopDBusPropertiesPropertiesChanged, _>(
-            dbus::message::MatchRule::new_signal($interface, $signal).with_sender($sender),
-            |_, _, _| true,
-        )
-        .unwrap()
-    };
-}
-
-struct DesktopCommand {
-    name: String,
-    command: String,
-}
-
-impl AsRef<str> for DesktopCommand {
-    fn as_ref(&self) -> &str {
-        &self.name
+        fn interrupt();
     }
 }
 
@@ -211,6 +159,10 @@ enum BarState {
         percentage: f32,
         icon: char,
     },
+    /// Text sent with `notify` on the socket
+    Notification {
+        text: String,
+    },
     AppLauncher {
         apps: yoke::Yoke<Commands<'static>, Vec<DesktopCommand>>,
         layout: Vec<Primitive>,
@@ -223,693 +175,44 @@ enum BarState {
 
 impl BarState {}
 
-impl From<WobCommand> for char {
-    fn from(value: WobCommand) -> char {
-        match value {
-            WobCommand::VolumeUp | WobCommand::VolumeDown => '󰕾',
-            WobCommand::LightUp | WobCommand::LightDown => '󰃠',
-            _ => ' ',
-        }
-    }
-}
-
-#[derive(PartialEq, Eq, Clone, Copy)]
-pub enum SelectedBlock {
-    Time(usize),
-    Brightness,
-    Battery(usize),
-    Connman,
-    WirePlumber,
-    Playback,
-    None,
+macro_rules! selected_block_selected {
+    ($shared_data:expr,$block:expr) => {
+        $block == $shared_data.selected // || $shared_data.other_selected.iter().any(|&b| $block == b)
+    };
 }
 
-pub struct SharedData {
-    time: Option<TimeBlock>,
-    brightness: Option<BrightnessBlock>,
-    bat_block: Option<BatteryBlock>,
-    connman: Option<ConnmanBlock>,
-    playback: Option<PlaybackBlock>,
-    wireplumber: Option<WirePlumberBlock>,
-    time_handle: RegistrationToken,
+/// The part of [`SharedData`] that's needed to lay out the blocks, so
+/// the bar can also be laid out without a Wayland or dbus connection
+pub struct BarBlocks<'a> {
+    pub blocks: &'a [Box<dyn Block>],
+    pub layout: &'a Layout,
     pub selected: SelectedBlock,
 }
 
-impl SharedData {
-    pub fn new(
-        handle: &LoopHandle<SimpleLayer>,
-        qh: Rc<QueueHandle<SimpleLayer>>,
-        dconf: *mut DConfClient,
-    ) -> Self {
-        unsafe {
-            let loop_handle: LoopHandle<'static, SimpleLayer> = std::mem::transmute(handle.clone());
-            let sys_handle: LoopHandle<'static, SimpleLayer> = std::mem::transmute(handle.clone());
-
-            let (user_connection, _): (calloop_dbus::SyncDBusSource<()>, _) =
-                calloop_dbus::SyncDBusSource::new_session().unwrap();
-            let (system_connection, _): (calloop_dbus::SyncDBusSource<()>, _) =
-                calloop_dbus::SyncDBusSource::new_system().unwrap();
-
-            let user_connection_ptr = Box::into_raw(Box::new(user_connection));
-            let system_connection_ptr = Box::into_raw(Box::new(system_connection));
-
-            let user_connection: &'static mut SyncDBusSource<()> = &mut *user_connection_ptr;
-            let system_connection: &'static mut SyncDBusSource<()> = &mut *system_connection_ptr;
-
-            user_connection
-                .add_match::<upower::OrgFreedesktopDBusPropertiesPropertiesChanged, _>(
-                    MatchRule::new_signal("ca.desrt.dconf.Writer", "Notify"),
-                    |_, _, _| true,
-                )
-                .unwrap();
-            let mut time = None;
-            if dconf_read_variant(dconf, "/dotfiles/somebar/time-block").unwrap_or(true) {
-                time = Some(TimeBlock::new(
-                    handle,
-                    dconf_read_variant(dconf, "/dotfiles/somebar/update-time-ntp").unwrap_or(true),
-                    dconf_read_variant(dconf, "/dotfiles/somebar/time-servers")
-                        .unwrap_or(NTP_SERVERS.into_iter().map(|s| s.to_string()).collect()),
-                    dconf_read_variant(dconf, "/dotfiles/somebar/time-fmt")
-                        .unwrap_or("%I:%M".to_owned()),
-                    dconf_read_variant(dconf, "/dotfiles/somebar/date-fmt")
-                        .unwrap_or("%m/%d/%y %A".to_owned()),
-                    Rc::clone(&qh),
-                ));
-            }
-
-            let mut brightness = None;
-            if dconf_read_variant(dconf, "/dotfiles/somebar/brightness-block").unwrap_or(true) {
-                brightness = Some(BrightnessBlock::new(handle, Rc::clone(&qh)))
-            }
-
-            let mut battery = None;
-            if dconf_read_variant(dconf, "/dotfiles/somebar/battery-block").unwrap_or(true) {
-                battery = Some(BatteryBlock::new(system_connection))
-            }
-
-            let mut playback = None;
-            if dconf_read_variant(dconf, "/dotfiles/somebar/media-block").unwrap_or(true) {
-                playback = Some(PlaybackBlock::new(user_connection))
-            }
-
-            let mut connman = None;
-            if dconf_read_variant(dconf, "/dotfiles/somebar/connman-block").unwrap_or(true) {
-                connman = Some(ConnmanBlock::new(system_connection, time.as_mut()))
-            }
-
-            let mut wireplumber = None;
-            if dconf_read_variant(dconf, "/dotfiles/somebar/wireplumber-block").unwrap_or(true) {
-                wireplumber = Some(WirePlumberBlock::new(
-                    user_connection_ptr,
-                    dconf_read_variant(dconf, "/dotfiles/somebar/wireplumber-max-volume")
-                        .unwrap_or(100.0)
-                        / 100.0,
-                ));
-            }
-
-            let sys_qh = Rc::clone(&qh);
-
-            system_connection
-                .add_match(
-                    MatchRule::new_signal("org.freedesktop.login1.Manager", "PrepareForSleep"),
-                    |_: (), _, _| true,
-                )
-                .unwrap();
-
-            handle
-                .insert_source(user_connection, move |event, user_con, shared_data| {
-                    let qh = Rc::clone(&qh);
-                    let Some(member) = event.member() else {
-                        return None;
-                    };
-                    if &*member == "PropertiesChanged" {
-                        let properties_changed: mpris::OrgFreedesktopDBusPropertiesPropertiesChanged = event.read_all().unwrap();
-                        if properties_changed.interface_name == "org.wireplumber.DefaultNode"
-                        {
-                            if let Some(ref mut wireplumber) = shared_data.shared_data.wireplumber {
-                                wireplumber.query_default_node(properties_changed);
-                                let monitor = shared_data.monitors.values_mut().find(|o| o.selected).unwrap();
-                                if !monitor.is_in_overlay {
-                                    monitor.output.layer_surface.set_layer(Layer::Overlay);
-                                }
-                                shared_data.loop_handle.remove(shared_data.shared_data.time_handle);
-                                monitor.is_in_overlay = true;
-                                monitor.bar_state = BarState::ProgressBar {
-                                    percentage: wireplumber.volume as f32,
-                                    icon: wireplumber.volume_level().chars().next().unwrap(),
-                                };
-                                monitor.output.frame(&qh);
-                                let time_qh = Rc::clone(&qh);
-                                shared_data.shared_data.time_handle = shared_data
-                                    .loop_handle
-                                    .insert_source(
-                                        Timer::from_duration(Duration::from_millis(
-                                            shared_data.bar_settings.bar_show_time,
-                                        )),
-                                        move |_, _, data| {
-                                            let monitor =
-                                                data.monitors.values_mut().find(|o| o.selected).unwrap();
-                                            monitor.bar_state = BarState::Normal;
-                                            if monitor.is_in_overlay {
-                                                monitor.output.layer_surface.set_layer(Layer::Bottom);
-                                                monitor.is_in_overlay = false;
-                                            }
-                                            monitor.output.frame(&time_qh);
-                                            TimeoutAction::Drop
-                                        },
-                                    )
-                                    .unwrap();
-                            }
-                            shared_data.write_bar(&qh);
-                        } else {
-                            if let Some(ref mut media) = shared_data.shared_data.playback {
-                                if media.query_media(properties_changed) {
-                                    shared_data.write_bar(&qh);
-                                }
-                            }
-                        }
-                    } else if &*member == "Notify" {
-                        let property: dconf::CaDesrtDconfWriterNotify = event.read_all().unwrap();
-
-                        for p in property.changes {
-                            let mut new_prop = property.prefix.clone();
-                            new_prop.push_str(&p);
-                            match shared_data
-                                .settings_parser
-                                .parse(new_prop, None)
-                                .as_ref()
-                                .and_then(|t| t.root_node().child(0))
-                                .map(|n| NodeKind::from(n.kind_id()))
-                            {
-                                Some(NodeKind::Font) => {
-                                    let new_font: String = dconf_read_variant(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/font",
-                                    )
-                                    .unwrap_or(String::from("FiraCode Nerd Font 14"));
-
-                                    let split = new_font.rsplit_once(' ').unwrap();
-
-                                    let font = split.0;
-                                    let font_size = split.1.parse().unwrap();
-
-                                    shared_data.iced =
-                                        iced_tiny_skia::Backend::new(iced_tiny_skia::Settings {
-                                            default_font: shared_data.iced.default_font(),
-                                            default_text_size: font_size,
-                                        });
-                                    shared_data.monitors.iter_mut().for_each(|(_, m)| {
-                                        m.info_iced = 
-                                            iced_tiny_skia::Backend::new(iced_tiny_skia::Settings {
-                                                default_font: shared_data.iced.default_font(),
-                                                default_text_size: font_size,
-                                            });
-                                        if let Some(ref mut i) = m.info_output {
-                                            i.frame(qh.as_ref());
-                                        }
-                                    });
-                                    shared_data.bar_settings.default_font = Font {
-                                        family: Family::Name(std::mem::transmute(font)),
-                                        ..Default::default()
-                                    };
-
-                                    shared_data.bar_settings.default_font_name = new_font;
-                                    let mut tmp = [0; 4];
-                                    shared_data.ascii_font_width = shared_data
-                                        .iced
-                                        .measure(
-                                            shared_data.bar_settings.divider.encode_utf8(&mut tmp),
-                                            shared_data.iced.default_size(),
-                                            LineHeight::Relative(1.0),
-                                            shared_data.bar_settings.default_font,
-                                            Size {
-                                                width: f32::INFINITY,
-                                                height: f32::INFINITY,
-                                            },
-                                            Shaping::Basic,
-                                        )
-                                        .width;
-
-                                    shared_data.relayout(Rc::clone(&qh));
-                                }
-                                Some(NodeKind::FontFallback) => {
-                                    let new_font: String = dconf_read_variant(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/font-fallback",
-                                    )
-                                    .unwrap_or(String::from("Noto Sans"));
-
-                                    shared_data.iced =
-                                            iced_tiny_skia::Backend::new(iced_tiny_skia::Settings {
-                                                default_font: Font {
-                                                    family: Family::Name(std::mem::transmute(
-                                                        new_font.as_str(),
-                                                    )),
-                                                    ..Default::default()
-                                                },
-                                                default_text_size: shared_data.iced.default_size()
-                                            });
-                                    shared_data.monitors.iter_mut().for_each(|(_, m)| {
-                                        m.info_iced = 
-                                            iced_tiny_skia::Backend::new(iced_tiny_skia::Settings {
-                                                default_font: Font {
-                                                    family: Family::Name(std::mem::transmute(
-                                                        new_font.as_str(),
-                                                    )),
-                                                    ..Default::default()
-                                                },
-                                                default_text_size: shared_data.iced.default_size()
-                                            });
-                                        if let Some(ref mut i) = m.info_output {
-                                            i.frame(qh.as_ref());
-                                        }
-                                    });
-                                    shared_data.bar_settings.default_font_fallback_name = new_font;
-                                    shared_data.relayout(Rc::clone(&qh));
-                                }
-                                Some(NodeKind::TimeBlock) => {
-                                    if dconf_read_variant(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/time-block",
-                                    )
-                                    .unwrap_or(true)
-                                    {
-                                        shared_data.shared_data.time = Some(TimeBlock::new(
-                                            &loop_handle,
-                                            dconf_read_variant(
-                                                dconf,
-                                                "/dotfiles/somebar/update-time-ntp",
-                                            )
-                                            .unwrap_or(true),
-                                            dconf_read_variant(
-                                                dconf,
-                                                "/dotfiles/somebar/time-servers",
-                                            )
-                                            .unwrap_or(
-                                                NTP_SERVERS
-                                                    .into_iter()
-                                                    .map(|s| s.to_string())
-                                                    .collect(),
-                                            ),
-                                            dconf_read_variant(dconf, "/dotfiles/somebar/time-fmt")
-                                                .unwrap_or("%I:%M".to_owned()),
-                                            dconf_read_variant(dconf, "/dotfiles/somebar/date-fmt")
-                                                .unwrap_or("%m/%d/%y %A".to_owned()),
-                                            Rc::clone(&qh),
-                                        ));
-                                    } else {
-                                        if let Some(time) = shared_data.shared_data.time.take() {
-                                            time.unregister(&loop_handle);
-                                        }
-                                    }
-                                    shared_data.write_bar(&qh);
-                                }
-                                Some(NodeKind::Divider) => {
-                                    shared_data.bar_settings.divider =
-                                        dconf_read_variant::<String>(
-                                            shared_data.dconf,
-                                            "/dotfiles/somebar/divider",
-                                        )
-                                        .and_then(|d| d.chars().next())
-                                        .unwrap_or('');
-                                    shared_data.write_bar(&qh);
-                                }
-                                Some(NodeKind::DateFmt) => {
-                                    if let Some(ref mut time) = shared_data.shared_data.time {
-                                        time.date_fmt = dconf_read_variant(
-                                            shared_data.dconf,
-                                            "/dotfiles/somebar/date-fmt",
-                                        )
-                                        .unwrap_or_else(|| "%m/%d/%y %A".to_owned());
-                                        shared_data.write_bar(&qh);
-                                    }
-                                }
-                                Some(NodeKind::BrowserPath) => {
-                                    shared_data.bar_settings.browser_path = dconf_read_variant(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/browser-path",
-                                    )
-                                    .unwrap_or_else(|| ".firedragon".to_owned());
-                                }
-                                Some(NodeKind::Browser) => {
-                                    shared_data.bar_settings.browser = format!(
-                                        "{} ",
-                                        dconf_read_variant(
-                                            shared_data.dconf,
-                                            "/dotfiles/somebar/browser",
-                                        )
-                                        .unwrap_or_else(|| "firedragon".to_owned())
-                                    );
-                                }
-                                Some(NodeKind::TimeFmt) => {
-                                    if let Some(ref mut time) = shared_data.shared_data.time {
-                                        time.time_fmt = dconf_read_variant(
-                                            shared_data.dconf,
-                                            "/dotfiles/somebar/time-fmt",
-                                        )
-                                        .unwrap_or_else(|| "%I:%M".to_owned());
-                                        shared_data.write_bar(&qh);
-                                    }
-                                }
-                                Some(NodeKind::UpdateTimeNtp) => {
-                                    if let Some(ref mut time) = shared_data.shared_data.time {
-                                        time.update_time_ntp = dconf_read_variant(
-                                            shared_data.dconf,
-                                            "/dotfiles/somebar/update-time-ntp",
-                                        )
-                                        .unwrap_or(true);
-                                        shared_data.write_bar(&qh);
-                                    }
-                                }
-                                Some(NodeKind::BrightnessBlock) => {
-                                    if dconf_read_variant(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/brightness-block",
-                                    )
-                                    .unwrap_or(true)
-                                    {
-                                        shared_data.shared_data.brightness = Some(
-                                            BrightnessBlock::new(&loop_handle, Rc::clone(&qh)),
-                                        );
-                                    } else {
-                                        if let Some(brightness) =
-                                            shared_data.shared_data.brightness.take()
-                                        {
-                                            brightness.unregister(&loop_handle);
-                                        }
-                                    }
-                                    shared_data.write_bar(&qh);
-                                }
-                                Some(NodeKind::BatteryBlock) => {
-                                    if dconf_read_variant(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/battery-block",
-                                    )
-                                    .unwrap_or(true)
-                                    {
-                                        shared_data.shared_data.bat_block =
-                                            Some(BatteryBlock::new(system_connection));
-                                    } else {
-                                        if let Some(bat_block) =
-                                            shared_data.shared_data.bat_block.take()
-                                        {
-                                            bat_block.unregister(system_connection);
-                                        }
-                                    }
-                                    shared_data.write_bar(&qh);
-                                }
-                                Some(NodeKind::ConnmanBlock) => {
-                                    if dconf_read_variant(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/connman-block",
-                                    )
-                                    .unwrap_or(true)
-                                    {
-                                        shared_data.shared_data.connman = Some(ConnmanBlock::new(
-                                            system_connection,
-                                            shared_data.shared_data.time.as_mut(),
-                                        ));
-                                    } else {
-                                        if let Some(connman) =
-                                            shared_data.shared_data.connman.take()
-                                        {
-                                            connman.unregister(system_connection);
-                                        }
-                                    }
-                                    shared_data.write_bar(&qh);
-                                }
-                                Some(NodeKind::MediaBlock) => {
-                                    if dconf_read_variant(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/media-block",
-                                    )
-                                    .unwrap_or(true)
-                                    {
-                                        shared_data.shared_data.playback =
-                                            Some(PlaybackBlock::new(user_con));
-                                    } else {
-                                        if let Some(media) = shared_data.shared_data.playback.take()
-                                        {
-                                            media.unregister(user_con);
-                                        }
-                                    }
-                                    shared_data.write_bar(&qh);
-                                }
-                                Some(NodeKind::WireplumberBlock) => {
-                                    if dconf_read_variant(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/wireplumber-block",
-                                    )
-                                    .unwrap_or(true)
-                                    {
-                                        shared_data.shared_data.wireplumber =
-                                            Some(WirePlumberBlock::new(user_connection_ptr,
-                                            dconf_read_variant(
-                                            shared_data.dconf,
-                                            "/dotfiles/somebar/wireplumber-max-volume",
-                                        )
-                                        .unwrap_or(100.0) / 100.0));
-                                    } else {
-                                        if let Some(wireplumber) =
-                                            shared_data.shared_data.wireplumber.take()
-                                        {
-                                            wireplumber.unregister(user_con);
-                                        }
-                                    }
-                                    shared_data.write_bar(&qh);
-                                }
-                                Some(NodeKind::WireplumberMaxVolume) => {
-                                    if let Some(ref mut wireplumber) =
-                                        shared_data.shared_data.wireplumber
-                                    {
-                                        wireplumber.max_volume = dconf_read_variant(
-                                            shared_data.dconf,
-                                            "/dotfiles/somebar/wireplumber-max-volume",
-                                        )
-                                        .unwrap_or(100.0) / 100.0;
-                                    }
-                                }
-                                Some(NodeKind::ColorActive) => {
-                                    shared_data
-                                        .bar_settings
-                                        .update_color_active(shared_data.dconf);
-                                    shared_data.relayout(Rc::clone(&qh));
-                                }
-                                Some(NodeKind::ColorInactive) => {
-                                    shared_data
-                                        .bar_settings
-                                        .update_color_inactive(shared_data.dconf);
-                                    shared_data.relayout(Rc::clone(&qh));
-                                }
-                                Some(NodeKind::PaddingX) => {
-                                    shared_data.bar_settings.padding_x = dconf_read_variant::<f64>(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/padding-x",
-                                    )
-                                    .unwrap_or(10.0)
-                                        as f32;
-
-                                    shared_data.relayout(Rc::clone(&qh));
-                                }
-                                Some(NodeKind::PaddingY) => {
-                                    shared_data.bar_settings.padding_y = dconf_read_variant::<f64>(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/padding-y",
-                                    )
-                                    .unwrap_or(3.0)
-                                        as f32;
-
-                                    shared_data.relayout(Rc::clone(&qh));
-                                }
-                                Some(NodeKind::TopBar) => {
-                                    shared_data.bar_settings.top_bar = dconf_read_variant(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/top-bar",
-                                    )
-                                    .unwrap_or(true);
-                                    for monitor in shared_data.monitors.values_mut() {
-                                        monitor.output.layer_surface.set_anchor(
-                                            if shared_data.bar_settings.top_bar {
-                                                Anchor::TOP
-                                            } else {
-                                                Anchor::BOTTOM
-                                            } | Anchor::LEFT
-                                                | Anchor::RIGHT,
-                                        );
-                                        monitor.output.layer_surface.commit();
-                                    }
-                                }
-                                Some(NodeKind::TimeServers) => {
-                                    if let Some(ref mut time) = shared_data.shared_data.time {
-                                        time.time_servers = dconf_read_variant(
-                                            dconf,
-                                            "/dotfiles/somebar/time-servers",
-                                        )
-                                        .unwrap_or(
-                                            NTP_SERVERS
-                                                .into_iter()
-                                                .map(|s| s.to_string())
-                                                .collect(),
-                                        );
-                                        time.update_time();
-                                        shared_data.write_bar(&qh);
-                                    }
-                                }
-                                Some(NodeKind::BarShowTime) => {
-                                    shared_data.bar_settings.bar_show_time = dconf_read_variant(
-                                        shared_data.dconf,
-                                        "/dotfiles/somebar/bar-show-time",
-                                    )
-                                    .unwrap_or(500);
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                    None
-                })
-                .unwrap();
-
-            let system_connection: &'static mut SyncDBusSource<()> = &mut *system_connection_ptr;
-
-            handle
-                .insert_source(system_connection, move |event, dbus, shared_data| {
-                    let Some(member) = event.member() else {
-                        return None;
-                    };
-                    if &*member == "PropertiesChanged" {
-                        if let Some(ref mut bat_block) = shared_data.shared_data.bat_block {
-                            let property: upower::OrgFreedesktopDBusPropertiesPropertiesChanged =
-                                event.read_all().unwrap();
-                            bat_block.query_battery(event.path().unwrap().into_static(), property);
-                            shared_data.write_bar(&sys_qh);
-                        }
-                    } else if &*member == "PropertyChanged" {
-                        if let Some(ref mut connman) = shared_data.shared_data.connman {
-                            if connman.query_connman(
-                                event,
-                                dbus,
-                                shared_data.shared_data.time.as_mut(),
-                            ) {
-                                shared_data.write_bar(&sys_qh);
-                            }
-                        }
-                    } else if &*member == "DeviceAdded" {
-                        if let Some(ref mut bat_block) = shared_data.shared_data.bat_block {
-                            bat_block.device_added(event, dbus);
-
-                            shared_data.write_bar(&sys_qh);
-                        }
-                    } else if &*member == "DeviceRemoved" {
-                        if let Some(ref mut bat_block) = shared_data.shared_data.bat_block {
-                            bat_block.device_removed(event);
-
-                            shared_data.write_bar(&sys_qh);
-                        }
-                    } else if &*member == "PrepareForSleep" {
-                        let prepare: logind::OrgFreedesktopLogin1ManagerPrepareForSleep =
-                            event.read_all().unwrap();
-
-                        if !prepare.start {
-                            if let Some(ref mut time) = shared_data.shared_data.time {
-                                time.unregister(&sys_handle);
-                                *time = TimeBlock::new(
-                                    &sys_handle,
-                                    dconf_read_variant(dconf, "/dotfiles/somebar/update-time-ntp")
-                                        .unwrap_or(true),
-                                    dconf_read_variant(dconf, "/dotfiles/somebar/time-servers")
-                                        .unwrap_or(
-                                            NTP_SERVERS
-                                                .into_iter()
-                                                .map(|s| s.to_string())
-                                                .collect(),
-                                        ),
-                                    dconf_read_variant(dconf, "/dotfiles/somebar/time-fmt")
-                                        .unwrap_or("%I:%M".to_owned()),
-                                    dconf_read_variant(dconf, "/dotfiles/somebar/date-fmt")
-                                        .unwrap_or("%m/%d/%y %A".to_owned()),
-                                    Rc::clone(&sys_qh),
-                                );
-
-                                shared_data.write_bar(&sys_qh);
-                            }
-                        }
-                    }
-                    None
-                })
-                .unwrap();
-
-            let socket_file = dirs::runtime_dir().unwrap().join("rustbar-0");
-            let _ = std::fs::remove_file(&socket_file);
-            let socket = UnixListener::bind(&socket_file).unwrap();
-
-            handle
-                .insert_source(
-                    Generic::new(socket, Interest::READ, calloop::Mode::Level),
-                    move |_event, socket, shared_data| {
-                        let (file, _) = socket.accept().unwrap();
-                        let mut file = BufWriter::new(file);
-                        shared_data.shared_data.fmt_table(&mut file).unwrap();
-
-                        Ok(calloop::PostAction::Continue)
-                    },
-                )
-                .unwrap();
-
-            // handle
-            //     .insert_source(
-            //         Signals::new(&[Signal::SIGINT, Signal::SIGTERM]).unwrap(),
-            //         move |signal, _, data| match signal.signal() {
-            //             Signal::SIGINT | Signal::SIGTERM => {
-            //                 std::fs::remove_file(&socket_file).unwrap();
-            //                 data.exit.stop();
-            //             }
-            //             _ => unreachable!(),
-            //         },
-            //     )
-            //     .unwrap();
-
-            let time_handle = handle
-                .insert_source(
-                    Timer::from_duration(Duration::from_secs(31_536_000)),
-                    |_, _, _| {
-                        // hmmmmm
-                        TimeoutAction::Drop
-                    },
-                )
-                .unwrap();
-
-            Self {
-                time,
-                time_handle,
-                brightness,
-                bat_block: battery,
-                playback,
-                wireplumber,
-                connman,
-                selected: SelectedBlock::None,
-            }
+impl<'a> BarBlocks<'a> {
+    fn new<F: Frontend>(shared_data: &'a SharedData<F>) -> Self {
+        BarBlocks {
+            blocks: &shared_data.blocks,
+            layout: &shared_data.layout,
+            selected: shared_data.selected,
         }
     }
-}
-
-macro_rules! selected_block_selected {
-    ($shared_data:expr,$block:expr) => {
-        $block == $shared_data.selected // || $shared_data.other_selected.iter().any(|&b| $block == b)
-    };
-}
 
-impl SharedData {
+    /// Lays out the blocks for the bar on `output`, the monitor's
+    /// `hit_boxes` are replaced with where every segment ended up
     fn fmt(
-        &mut self,
+        &self,
+        output: &str,
+        hit_boxes: &mut Vec<HitBox>,
         colors: (Color, (Color, Color)),
         backend: &iced_tiny_skia::Backend,
         font: Font,
         logical_size: Size<f32>,
+        left_x: f32,
         padding_x: f32,
         padding_y: f32,
         divider: char,
-    ) -> (Primitive, Size<f32>) {
+    ) -> (Primitive, Size<f32>, Range<f32>) {
         let mut divider_tmp: [u8; 4] = [0; 4];
         let divider = divider.encode_utf8(&mut divider_tmp);
         let mut divider_hard_tmp: [u8; 4] = [0; 4];
@@ -930,7 +233,7 @@ impl SharedData {
                     background: Background::Color(colors.0),
                     border_radius: [0.0, 0.0, 0.0, 0.0],
                     border_width: 0.0,
-                    border_color: Color::TRANSPARENT
+                    border_color: Color::TRANSPARENT,
                 }
             };
         }
@@ -951,389 +254,94 @@ impl SharedData {
                     colors.0
                 }
             };
-        }
-        macro_rules! select_divider {
-            ($selected:expr) => {
-                if $selected {
-                    divider_hard.to_owned()
-                } else {
-                    divider.to_owned()
-                }
-            };
-        }
-        macro_rules! set_full_divider_if_selected {
-            ($selected:expr,$primitives:expr) => {
-                if $selected {
-                    match $primitives.last_mut() {
-                        Some(Primitive::Text { content, color, .. }) => {
-                            content.clear();
-                            content.push_str(divider_hard);
-                            *color = colors.0;
-                        }
-                        _ => unreachable!(),
-                    }
-                }
-            };
-        }
-        let divider_measurement = backend.measure(
-            divider,
-            backend.default_size() + padding_y,
-            LineHeight::Relative(1.0),
-            font,
-            Size::INFINITY,
-            Shaping::Basic,
-        );
-        let mut primitives = Vec::new();
-        let mut x = logical_size.width - padding_x;
-
-        if let Some(ref mut media) = self.playback {
-            let selected = selected_block_selected!(self, SelectedBlock::Playback);
-            let l = primitives.len();
-            primitives.push(Primitive::Group {
-                primitives: Vec::new(),
-            });
-            let mut content = String::new();
-            media.fmt(&mut content);
-            let measurement = backend
-                .measure(
-                    &content,
-                    backend.default_size(),
-                    LineHeight::Relative(1.0),
-                    font,
-                    Size::INFINITY,
-                    Shaping::Advanced,
-                )
-                .width;
-            x -= measurement;
-            let bounds = Rectangle {
-                x,
-                y: logical_size.height / 2.0,
-                width: logical_size.width,
-                height: logical_size.height / 2.0,
-            };
-            primitives.push(Primitive::Text {
-                content,
-                bounds,
-                color: select_color!(selected),
-                size: backend.default_size(),
-                line_height: LineHeight::Relative(1.0),
-                font,
-                horizontal_alignment: Horizontal::Left,
-                vertical_alignment: Vertical::Center,
-                shaping: Shaping::Advanced,
-            });
-            x -= divider_measurement.width;
-            let bounds = Rectangle {
-                x,
-                y: logical_size.height / 2.0,
-                width: logical_size.width,
-                height: logical_size.height,
-            };
-            primitives.push(Primitive::Text {
-                content: select_divider!(selected),
-                bounds,
-                color: select_divider_color!(selected),
-                size: backend.default_size() + padding_y * 2.0,
-                line_height: LineHeight::Relative(1.0),
-                font,
-                horizontal_alignment: Horizontal::Left,
-                vertical_alignment: Vertical::Center,
-                shaping: Shaping::Advanced,
-            });
-            media.x_at = x;
-            media.width = measurement + divider_measurement.width + padding_x;
-            if selected {
-                primitives[l] = status_bar_bg!(media.x_at, media.width, logical_size.height);
-            }
-        }
-
-        if let Some(ref mut wireplumber) = self.wireplumber {
-            let selected = selected_block_selected!(self, SelectedBlock::WirePlumber);
-            set_full_divider_if_selected!(selected, primitives);
-            let l = primitives.len();
-            primitives.push(Primitive::Group {
-                primitives: Vec::new(),
-            });
-            let mut content = String::new();
-            wireplumber.fmt(&mut content);
-            let measurement = backend
-                .measure(
-                    &content,
-                    backend.default_size(),
-                    LineHeight::Relative(1.0),
-                    font,
-                    Size::INFINITY,
-                    Shaping::Basic,
-                )
-                .width;
-            x -= measurement;
-            let bounds = Rectangle {
-                x,
-                y: logical_size.height / 2.0,
-                width: logical_size.width,
-                height: logical_size.height / 2.0,
-            };
-            primitives.push(Primitive::Text {
-                content,
-                bounds,
-                color: select_color!(selected),
-                size: backend.default_size(),
-                line_height: LineHeight::Relative(1.0),
-                font,
-                horizontal_alignment: Horizontal::Left,
-                vertical_alignment: Vertical::Center,
-                shaping: Shaping::Basic,
-            });
-            x -= divider_measurement.width;
-            let bounds = Rectangle {
-                x,
-                y: logical_size.height / 2.0,
-                width: logical_size.width,
-                height: logical_size.height,
-            };
-            primitives.push(Primitive::Text {
-                content: select_divider!(selected),
-                bounds,
-                color: select_divider_color!(selected),
-                size: backend.default_size() + padding_y * 2.0,
-                line_height: LineHeight::Relative(1.0),
-                font,
-                horizontal_alignment: Horizontal::Left,
-                vertical_alignment: Vertical::Center,
-                shaping: Shaping::Basic,
-            });
-
-            wireplumber.x_at = x;
-            wireplumber.width = measurement + divider_measurement.width;
-            if selected {
-                primitives[l] =
-                    status_bar_bg!(wireplumber.x_at, wireplumber.width, logical_size.height);
-            }
-        }
-
-        if let Some(ref mut connman) = self.connman {
-            let selected = selected_block_selected!(self, SelectedBlock::Connman);
-            set_full_divider_if_selected!(selected, primitives);
-            let l = primitives.len();
-            primitives.push(Primitive::Group {
-                primitives: Vec::new(),
-            });
-            let mut content = String::new();
-            connman.fmt(&mut content);
-            let measurement = backend
-                .measure(
-                    &content,
-                    backend.default_size(),
-                    LineHeight::Relative(1.0),
-                    font,
-                    Size::INFINITY,
-                    Shaping::Basic,
-                )
-                .width;
-            x -= measurement;
-            primitives.push(Primitive::Text {
-                content,
-                bounds: Rectangle {
-                    x,
-                    y: logical_size.height / 2.0,
-                    width: logical_size.width,
-                    height: logical_size.height / 2.0,
-                },
-                color: select_color!(selected),
-                size: backend.default_size(),
-                line_height: LineHeight::Relative(1.0),
-                font,
-                horizontal_alignment: Horizontal::Left,
-                vertical_alignment: Vertical::Center,
-                shaping: Shaping::Basic,
-            });
-            x -= divider_measurement.width;
-            primitives.push(Primitive::Text {
-                content: select_divider!(selected),
-                bounds: Rectangle {
-                    x,
-                    y: logical_size.height / 2.0,
-                    width: logical_size.width,
-                    height: logical_size.height,
-                },
-                color: select_divider_color!(selected),
-                size: backend.default_size() + padding_y * 2.0,
-                line_height: LineHeight::Relative(1.0),
-                font,
-                horizontal_alignment: Horizontal::Left,
-                vertical_alignment: Vertical::Center,
-                shaping: Shaping::Basic,
-            });
-
-            connman.x_at = x;
-            connman.width = measurement + divider_measurement.width;
-            if selected {
-                primitives[l] = status_bar_bg!(connman.x_at, connman.width, logical_size.height);
-            }
-        }
-
-        if let Some(ref mut bat_block) = self.bat_block {
-            bat_block.xs_at.clear();
-            bat_block.widths.clear();
-            for (i, (content, warning_level)) in
-                unsafe { std::mem::transmute::<&BatteryBlock, &'static BatteryBlock>(&bat_block) }
-                    .fmt()
-                    .enumerate()
-            {
-                let selected = selected_block_selected!(self, SelectedBlock::Battery(i))
-                    || matches!(
-                        warning_level,
-                        upower::WarningLevel::Low
-                            | upower::WarningLevel::Critical
-                            | upower::WarningLevel::Action
-                    );
-                set_full_divider_if_selected!(selected, primitives);
-                let l = primitives.len();
-                primitives.push(Primitive::Group {
-                    primitives: Vec::new(),
-                });
-                let current_measurement = backend
-                    .measure(
-                        &content,
-                        backend.default_size(),
-                        LineHeight::Relative(1.0),
-                        font,
-                        Size::INFINITY,
-                        Shaping::Basic,
-                    )
-                    .width;
-
-                x -= current_measurement;
-                primitives.push(Primitive::Text {
-                    content,
-                    bounds: Rectangle {
-                        x,
-                        y: logical_size.height / 2.0,
-                        width: logical_size.width,
-                        height: logical_size.height / 2.0,
-                    },
-                    color: select_color!(selected),
-                    size: backend.default_size(),
-                    line_height: LineHeight::Relative(1.0),
-                    font,
-                    horizontal_alignment: Horizontal::Left,
-                    vertical_alignment: Vertical::Center,
-                    shaping: Shaping::Basic,
-                });
-                x -= divider_measurement.width;
-                primitives.push(Primitive::Text {
-                    content: select_divider!(selected),
-                    bounds: Rectangle {
-                        x,
-                        y: logical_size.height / 2.0,
-                        width: logical_size.width,
-                        height: logical_size.height,
-                    },
-                    color: select_divider_color!(selected),
-                    size: backend.default_size() + padding_y * 2.0,
-                    line_height: LineHeight::Relative(1.0),
-                    font,
-                    horizontal_alignment: Horizontal::Left,
-                    vertical_alignment: Vertical::Center,
-                    shaping: Shaping::Basic,
-                });
-
-                bat_block.xs_at.push(x);
-                let width = current_measurement + divider_measurement.width;
-                bat_block.widths.push(width);
-                if selected {
-                    primitives[l] = status_bar_bg!(x, width, logical_size.height);
+        }
+        macro_rules! select_divider {
+            ($selected:expr) => {
+                if $selected {
+                    divider_hard.to_owned()
+                } else {
+                    divider.to_owned()
                 }
-            }
+            };
         }
-
-        if let Some(ref mut brightness) = self.brightness {
-            let selected = selected_block_selected!(self, SelectedBlock::Brightness);
-            set_full_divider_if_selected!(selected, primitives);
-            let l = primitives.len();
-            primitives.push(Primitive::Group {
-                primitives: Vec::new(),
-            });
-            let mut content = String::new();
-            brightness.fmt(&mut content);
-            let measurement = backend
-                .measure(
-                    &content,
+        macro_rules! set_full_divider_if_selected {
+            ($selected:expr,$primitives:expr) => {
+                if $selected {
+                    match $primitives.last_mut() {
+                        Some(Primitive::Text { content, color, .. }) => {
+                            content.clear();
+                            content.push_str(divider_hard);
+                            *color = colors.0;
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+            };
+        }
+        let divider_measurement = backend.measure(
+            divider,
+            backend.default_size() + padding_y,
+            LineHeight::Relative(1.0),
+            font,
+            Size::INFINITY,
+            Shaping::Basic,
+        );
+        let mut primitives = Vec::new();
+        let mut height: f32 = 0.0;
+
+        // Every segment on each side of the bar, from right to left
+        let mut sides: [Vec<(usize, usize, Segment, f32)>; 3] = Default::default();
+        hit_boxes.clear();
+        for (b, block) in self.blocks.iter().enumerate().rev() {
+            let mut segments = Vec::new();
+            block.fmt(output, &mut segments);
+            let side = self.layout.side(block.kind());
+            for (s, segment) in segments.into_iter().enumerate() {
+                let measurement = backend.measure(
+                    &segment.content,
                     backend.default_size(),
                     LineHeight::Relative(1.0),
                     font,
                     Size::INFINITY,
-                    Shaping::Basic,
-                )
-                .width;
-            x -= measurement;
-            primitives.push(Primitive::Text {
-                content,
-                bounds: Rectangle {
-                    x,
-                    y: logical_size.height / 2.0,
-                    width: logical_size.width,
-                    height: logical_size.height / 2.0,
-                },
-                color: select_color!(selected),
-                size: backend.default_size(),
-                line_height: LineHeight::Relative(1.0),
-                font,
-                horizontal_alignment: Horizontal::Left,
-                vertical_alignment: Vertical::Center,
-                shaping: Shaping::Basic,
-            });
-            x -= divider_measurement.width;
-            primitives.push(Primitive::Text {
-                content: select_divider!(selected),
-                bounds: Rectangle {
-                    x,
-                    y: logical_size.height / 2.0,
-                    width: logical_size.width,
-                    height: logical_size.height,
-                },
-                color: select_divider_color!(selected),
-                size: backend.default_size() + padding_y * 2.0,
-                line_height: LineHeight::Relative(1.0),
-                font,
-                horizontal_alignment: Horizontal::Left,
-                vertical_alignment: Vertical::Center,
-                shaping: Shaping::Basic,
-            });
-
-            brightness.x_at = x;
-            brightness.width = measurement + divider_measurement.width;
-            if selected {
-                primitives[l] =
-                    status_bar_bg!(brightness.x_at, brightness.width, logical_size.height);
+                    segment.shaping,
+                );
+                height = height.max(measurement.height);
+                sides[side as usize].push((b, s, segment, measurement.width));
             }
         }
 
-        let mut height = 0.0;
+        let mut left_width = 0.0;
+        let mut center_x = f32::INFINITY;
+        let mut right_x = logical_size.width - padding_x;
+        for side in [Side::Left, Side::Center, Side::Right] {
+            let segments = std::mem::take(&mut sides[side as usize]);
+            if segments.is_empty() {
+                continue;
+            }
+            let segment_count = segments.len();
+            let span = segments.iter().map(|s| s.3).sum::<f32>()
+                + divider_measurement.width * (segment_count - 1) as f32
+                + padding_x;
+            let mut x = match side {
+                Side::Left => left_x + span,
+                Side::Center => (logical_size.width + span) / 2.0,
+                Side::Right => logical_size.width - padding_x,
+            };
 
-        if let Some(ref mut time) = self.time {
-            {
-                let selected = selected_block_selected!(self, SelectedBlock::Time(0));
-                set_full_divider_if_selected!(selected, primitives);
+            for (n, (b, s, segment, measurement)) in segments.into_iter().enumerate() {
+                let selected =
+                    selected_block_selected!(self, SelectedBlock::Block(b, s)) || segment.urgent;
+                if n > 0 {
+                    set_full_divider_if_selected!(selected, primitives);
+                }
                 let l = primitives.len();
                 primitives.push(Primitive::Group {
                     primitives: Vec::new(),
                 });
-                let mut content = String::new();
-                time.fmt_date(&mut content);
-                let measurement = backend.measure(
-                    &content,
-                    backend.default_size(),
-                    LineHeight::Relative(1.0),
-                    font,
-                    Size::INFINITY,
-                    Shaping::Basic,
-                );
-                height = measurement.height;
-                let measurement = measurement.width;
+                let mut width = measurement;
                 x -= measurement;
                 primitives.push(Primitive::Text {
-                    content,
+                    content: segment.content,
                     bounds: Rectangle {
                         x,
                         y: logical_size.height / 2.0,
@@ -1346,161 +354,77 @@ impl SharedData {
                     font,
                     horizontal_alignment: Horizontal::Left,
                     vertical_alignment: Vertical::Center,
-                    shaping: Shaping::Basic,
-                });
-                x -= divider_measurement.width;
-                primitives.push(Primitive::Text {
-                    content: select_divider!(selected),
-                    bounds: Rectangle {
-                        x,
-                        y: logical_size.height / 2.0,
-                        width: logical_size.width,
-                        height: logical_size.height,
-                    },
-                    color: select_divider_color!(selected),
-                    size: backend.default_size() + padding_y * 2.0,
-                    line_height: LineHeight::Relative(1.0),
-                    font,
-                    horizontal_alignment: Horizontal::Left,
-                    vertical_alignment: Vertical::Center,
-                    shaping: Shaping::Basic,
+                    shaping: segment.shaping,
                 });
 
-                time.xs_at[0] = x;
-                time.widths[0] = measurement + padding_x;
-                if selected {
-                    primitives[l] =
-                        status_bar_bg!(time.xs_at[0], time.widths[0], logical_size.height);
+                // The leftmost segment gets padding instead of a divider
+                if n + 1 == segment_count {
+                    x -= padding_x;
+                    width += padding_x;
+                } else {
+                    x -= divider_measurement.width;
+                    width += divider_measurement.width;
+                    primitives.push(Primitive::Text {
+                        content: select_divider!(selected),
+                        bounds: Rectangle {
+                            x,
+                            y: logical_size.height / 2.0,
+                            width: logical_size.width,
+                            height: logical_size.height,
+                        },
+                        color: select_divider_color!(selected),
+                        size: backend.default_size() + padding_y * 2.0,
+                        line_height: LineHeight::Relative(1.0),
+                        font,
+                        horizontal_alignment: Horizontal::Left,
+                        vertical_alignment: Vertical::Center,
+                        shaping: segment.shaping,
+                    });
+                }
+                if n == 0 && side == Side::Right {
+                    width += padding_x;
                 }
-            }
 
-            {
-                let selected = selected_block_selected!(self, SelectedBlock::Time(1));
-                let l = primitives.len();
-                set_full_divider_if_selected!(selected, primitives);
-                primitives.push(Primitive::Group {
-                    primitives: Vec::new(),
-                });
-                let mut content = String::new();
-                time.fmt_time(&mut content);
-                let measurement = backend.measure(
-                    &content,
-                    backend.default_size(),
-                    LineHeight::Relative(1.0),
-                    font,
-                    Size::INFINITY,
-                    Shaping::Basic,
-                );
-                let measurement = measurement.width;
-                x -= measurement;
-                primitives.push(Primitive::Text {
-                    content,
-                    bounds: Rectangle {
-                        x,
-                        y: logical_size.height / 2.0,
-                        width: logical_size.width,
-                        height: logical_size.height / 2.0,
-                    },
-                    color: select_color!(selected),
-                    size: backend.default_size(),
-                    line_height: LineHeight::Relative(1.0),
-                    font,
-                    horizontal_alignment: Horizontal::Left,
-                    vertical_alignment: Vertical::Center,
-                    shaping: Shaping::Basic,
+                hit_boxes.push(HitBox {
+                    block: b,
+                    segment: s,
+                    side,
+                    x_at: x,
+                    width,
                 });
-                x -= padding_x;
-
-                time.xs_at[1] = x;
-                time.widths[1] = measurement + padding_x;
                 if selected {
-                    primitives[l] =
-                        status_bar_bg!(time.xs_at[1], time.widths[1], logical_size.height);
+                    primitives[l] = status_bar_bg!(x, width, logical_size.height);
                 }
             }
+
+            match side {
+                Side::Left => left_width = span,
+                Side::Center => center_x = x,
+                Side::Right => right_x = x,
+            }
         }
 
+        let title_x = left_x + left_width + padding_x;
         (
             Primitive::Group { primitives },
             Size {
-                width: (logical_size.width - x) + (padding_x * 2.0),
+                width: (logical_size.width - right_x) + (padding_x * 2.0),
                 height,
             },
+            title_x..center_x.min(right_x).max(title_x),
         )
     }
 }
 
-impl SharedData {
-    fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
-        f.write_all(b"\n")?;
-        if let Some(ref time) = self.time {
-            time.fmt_time_table(f)?;
-            time.fmt_date_table(f)?;
-        }
-
-        if let Some(ref brightness) = self.brightness {
-            brightness.fmt_table(f)?;
-        }
-
-        if let Some(ref bat_block) = self.bat_block {
-            bat_block.fmt_table(f)?;
-        }
-
-        if let Some(ref connman) = self.connman {
-            connman.fmt_table(f)?;
-        }
-
-        if let Some(ref wireplumber) = self.wireplumber {
-            wireplumber.fmt_table(f)?;
-        }
-
-        if let Some(ref media) = self.playback {
-            media.fmt_table(f)
-        } else {
-            Ok(())
-        }
-    }
-}
-
-fn dconf_read_variant<T: FromVariant>(dconf_client: *mut DConfClient, path: &str) -> Option<T> {
-    let value: Option<glib::variant::Variant> = {
-        let key = CString::new(path).unwrap();
-
-        unsafe {
-            NonNull::new(dconf_client_read(dconf_client, key.as_ptr()))
-                .map(|nn| std::mem::transmute(nn))
-        }
-    };
-    value.and_then(|v| v.get())
-}
-
 fn main() {
+    if std::env::args().nth(1).map(|a| a == "render") == Some(true) {
+        render::main(std::env::args().skip(2));
+        return;
+    }
     if std::env::args().nth(1).map(|a| a == "check") == Some(true) {
-        freedesktop_desktop_entry::Iter::new(default_paths()).for_each(|entry| {
-            if let Ok(bytes) = std::fs::read_to_string(&entry) {
-                if let Ok(entry) = DesktopEntry::decode(&entry, &bytes) {
-                    if let Some(exec) = entry.exec() {
-                        let mut command = exec.to_owned();
-                        while let Some(index) = memchr(b'%', command.as_bytes()) {
-                            if index + 1 == command.len() {
-                                command.pop();
-                                command.pop();
-                            } else {
-                                command.remove(index + 1);
-                                command.remove(index);
-                            }
-                        }
-                        println!("{}", entry.path.display());
-                        let _ = std::process::Command::new("sh")
-                            .args([
-                                "-c",
-                                &format!("which {}", command.split(' ').next().unwrap()),
-                            ])
-                            .status();
-                    }
-                }
-            }
-        });
+        if !desktop_entries::check() {
+            std::process::exit(1);
+        }
         return;
     }
 
@@ -1513,7 +437,7 @@ fn main() {
 
     let layer_shell = LayerShell::bind(&globals, &qh).unwrap();
 
-    let dwl: ZnetTapesoftwareDwlWmV1 = globals.bind(&qh, 1..=1, GlobalData).unwrap();
+    let dwl: ZnetTapesoftwareDwlWmV1 = globals.bind(&qh, 1..=2, GlobalData).unwrap();
     let cursor_shape_manager: CursorShapeManager = CursorShapeManager::bind(&globals, &qh).unwrap();
 
     let fractional_scale: WpFractionalScaleManagerV1 =
@@ -1577,9 +501,8 @@ fn main() {
     let pool = SlotPool::new(1920 * bar_size.height as usize * 4, &shm).unwrap();
 
     let mut event_loop = EventLoop::try_new().unwrap();
-    let handle = event_loop.handle();
 
-    let shared_data = SharedData::new(&handle, Rc::clone(&qh), dconf);
+    let shared_data = SharedData::new(unsafe { std::mem::transmute(event_loop.handle()) }, dconf);
 
     let seat_state = SeatState::new(&globals, &qh);
     let clipboard_state =
@@ -1604,6 +527,7 @@ fn main() {
         fractional_scale,
         viewporter,
         clipboard_state,
+        Rc::clone(&qh),
     );
 
     WaylandSource::new(conn, event_queue)
@@ -1628,7 +552,12 @@ pub struct Output {
 
 pub struct Monitor {
     output: Output,
+    /// The name of the connector, like `eDP-1`
+    output_name: String,
     info_output: Option<Output>,
+    /// The pointer is over the info popup, so it is kept open after the
+    /// pointer leaves the bar
+    info_hovered: bool,
     info_iced: iced_tiny_skia::Backend,
     wl_output: wl_output::WlOutput,
     is_in_overlay: bool,
@@ -1640,38 +569,57 @@ pub struct Monitor {
     selected: bool,
     status_bar_primitives: Arc<Primitive>,
     bar_size: Size<f32>,
+    /// The space between the blocks that's left for the window title
+    window_title_bounds: Range<f32>,
+    /// Where the segments of the blocks are on this monitor's bar
+    hit_boxes: Vec<HitBox>,
 }
 
 impl Monitor {
+    fn hit(&self, x: f64) -> SelectedBlock {
+        self.hit_boxes
+            .iter()
+            .find(|hit_box| hit_box.contains(x))
+            .map_or(SelectedBlock::None, |hit_box| {
+                SelectedBlock::Block(hit_box.block, hit_box.segment)
+            })
+    }
+
     fn write_bar(
         &mut self,
         bar_settings: &BarSettings,
-        shared_data: &mut SharedData,
+        shared_data: &mut SharedData<SimpleLayer>,
         iced: &iced_tiny_skia::Backend,
+        ascii_font_width: f32,
         qh: &QueueHandle<SimpleLayer>,
     ) {
-        let (status_bar_primitives, bar_size) = shared_data.fmt(
-            if self.selected {
-                (
-                    bar_settings.color_active.0,
-                    (bar_settings.color_inactive.1, bar_settings.color_active.1),
-                )
-            } else {
-                (
-                    bar_settings.color_inactive.0,
-                    (bar_settings.color_inactive.1, bar_settings.color_active.1),
-                )
-            },
-            iced,
-            bar_settings.default_font,
-            self.output.viewport.logical_size(),
-            bar_settings.padding_x,
-            bar_settings.padding_y,
-            bar_settings.divider,
-        );
+        let (status_bar_primitives, bar_size, window_title_bounds) = BarBlocks::new(shared_data)
+            .fmt(
+                &self.output_name,
+                &mut self.hit_boxes,
+                if self.selected {
+                    (
+                        bar_settings.color_active.0,
+                        (bar_settings.color_inactive.1, bar_settings.color_active.1),
+                    )
+                } else {
+                    (
+                        bar_settings.color_inactive.0,
+                        (bar_settings.color_inactive.1, bar_settings.color_active.1),
+                    )
+                },
+                iced,
+                bar_settings.default_font,
+                self.output.viewport.logical_size(),
+                self.tags.width + bar_settings.padding_x + (ascii_font_width * 3.0),
+                bar_settings.padding_x,
+                bar_settings.padding_y,
+                bar_settings.divider,
+            );
 
         self.status_bar_primitives = Arc::new(status_bar_primitives);
         self.bar_size = bar_size;
+        self.window_title_bounds = window_title_bounds;
         self.bar_size.height += bar_settings.padding_y * 2.0;
 
         if let Some(ref mut info) = self.info_output {
@@ -1694,180 +642,6 @@ impl Output {
     }
 }
 
-pub struct BarSettings {
-    color_active: (Color, Color),
-    color_inactive: (Color, Color),
-    default_font_fallback_name: String,
-    default_font_name: String,
-    default_font: Font,
-    padding_x: f32,
-    padding_y: f32,
-    bar_show_time: u64,
-    browser_path: String,
-    browser: String,
-    divider: char,
-    top_bar: bool,
-}
-
-fn parse_color(
-    color_input: &mut [palette::Srgba; 2],
-    dconf_path: &str,
-    dconf_client: *mut DConfClient,
-) {
-    if let Some((color_one, color_two)) =
-        dconf_read_variant::<(String, String)>(dconf_client, dconf_path)
-    {
-        if let Ok((_, color)) = color::parse_color_with(
-            &mut DefaultColorParser::new(Some(&mut color::Color::LinSrgb(
-                color_input[0].into_linear(),
-            ))),
-            &mut Parser::new(&mut ParserInput::new(&color_one)),
-        ) {
-            let color: palette::LinSrgba = color.into_color();
-            color_input[0] = palette::Srgba::from_linear(color);
-        }
-
-        if let Ok((_, color)) = color::parse_color_with(
-            &mut DefaultColorParser::new(Some(&mut color::Color::LinSrgb(
-                color_input[1].into_linear(),
-            ))),
-            &mut Parser::new(&mut ParserInput::new(&color_two)),
-        ) {
-            let color: palette::LinSrgba = color.into_color();
-            color_input[1] = palette::Srgba::from_linear(color);
-        }
-    }
-}
-
-impl BarSettings {
-    fn new(
-        default_font_fallback_name: String,
-        default_font_name: String,
-        default_font: Font,
-        dconf: *mut DConfClient,
-        divider: char,
-    ) -> BarSettings {
-        let mut color_active: [palette::Srgba; 2] = [
-            palette::Srgba::from_components((1.0, 0.56, 0.25, 1.0)),
-            palette::Srgba::from_components((0.2, 0.227, 0.25, 1.0)),
-        ];
-
-        parse_color(&mut color_active, "/dotfiles/somebar/color-active", dconf);
-
-        let mut color_inactive: [palette::Srgba; 2] = [
-            palette::Srgba::from_components((0.701, 0.694, 0.678, 1.0)),
-            palette::Srgba::from_components((0.039, 0.054, 0.078, 1.0)),
-        ];
-
-        parse_color(
-            &mut color_inactive,
-            "/dotfiles/somebar/color-inactive",
-            dconf,
-        );
-
-        BarSettings {
-            color_active: (
-                Color::from_rgba(
-                    color_active[0].red,
-                    color_active[0].green,
-                    color_active[0].blue,
-                    color_active[0].alpha,
-                ),
-                Color::from_rgba(
-                    color_active[1].red,
-                    color_active[1].green,
-                    color_active[1].blue,
-                    color_active[1].alpha,
-                ),
-            ),
-            color_inactive: (
-                Color::from_rgba(
-                    color_inactive[0].red,
-                    color_inactive[0].green,
-                    color_inactive[0].blue,
-                    color_inactive[0].alpha,
-                ),
-                Color::from_rgba(
-                    color_inactive[1].red,
-                    color_inactive[1].green,
-                    color_inactive[1].blue,
-                    color_inactive[1].alpha,
-                ),
-            ),
-            default_font_fallback_name,
-            default_font,
-            default_font_name,
-            padding_x: dconf_read_variant::<f64>(dconf, "/dotfiles/somebar/padding-x")
-                .unwrap_or(10.0) as f32,
-            padding_y: dconf_read_variant::<f64>(dconf, "/dotfiles/somebar/padding-y")
-                .unwrap_or(3.0) as f32,
-            bar_show_time: dconf_read_variant(dconf, "/dotfiles/somebar/bar-show-time")
-                .unwrap_or(500),
-            top_bar: dconf_read_variant(dconf, "/dotfiles/somebar/top-bar").unwrap_or(true),
-            browser_path: dconf_read_variant(dconf, "/dotfiles/somebar/browser-path")
-                .unwrap_or_else(|| ".firedragon".to_owned()),
-            browser: format!(
-                "{} ",
-                dconf_read_variant(dconf, "/dotfiles/somebar/browser")
-                    .unwrap_or_else(|| "firedragon".to_owned())
-            ),
-            divider,
-        }
-    }
-
-    fn update_color_active(&mut self, dconf: *mut DConfClient) {
-        let mut color_active = [
-            palette::Srgba::from_components((1.0, 0.56, 0.25, 1.0)),
-            palette::Srgba::from_components((0.2, 0.227, 0.25, 1.0)),
-        ];
-
-        parse_color(&mut color_active, "/dotfiles/somebar/color-active", dconf);
-
-        self.color_active = (
-            Color::from_rgba(
-                color_active[0].red,
-                color_active[0].green,
-                color_active[0].blue,
-                color_active[0].alpha,
-            ),
-            Color::from_rgba(
-                color_active[1].red,
-                color_active[1].green,
-                color_active[1].blue,
-                color_active[1].alpha,
-            ),
-        );
-    }
-
-    fn update_color_inactive(&mut self, dconf: *mut DConfClient) {
-        let mut color_inactive = [
-            palette::Srgba::from_components((0.701, 0.694, 0.678, 1.0)),
-            palette::Srgba::from_components((0.039, 0.054, 0.078, 1.0)),
-        ];
-
-        parse_color(
-            &mut color_inactive,
-            "/dotfiles/somebar/color-inactive",
-            dconf,
-        );
-
-        self.color_inactive = (
-            Color::from_rgba(
-                color_inactive[0].red,
-                color_inactive[0].green,
-                color_inactive[0].blue,
-                color_inactive[0].alpha,
-            ),
-            Color::from_rgba(
-                color_inactive[1].red,
-                color_inactive[1].green,
-                color_inactive[1].blue,
-                color_inactive[1].alpha,
-            ),
-        );
-    }
-}
-
 enum OutputType {
     Bar,
     Info(ObjectId),
@@ -1899,15 +673,23 @@ pub struct SimpleLayer {
     keyboard: Option<wl_keyboard::WlKeyboard>,
     pointer: Option<wl_pointer::WlPointer>,
     dconf: *mut DConfClient,
-    shared_data: SharedData,
+    shared_data: SharedData<SimpleLayer>,
     bar_settings: BarSettings,
     matcher: nucleo_matcher::Matcher,
-    giac: UniquePtr<ffi::context>,
+    calculator: Calculator,
+    history: History,
+    /// Everything the launcher can run
+    desktop_entries: DesktopEntries,
+    /// The clients dwl sent so far for the window switcher, and whether
+    /// each one is focused
+    dwl_clients: Vec<(DesktopCommand, bool)>,
     clipboard_state: clipboard::state::State,
-    settings_parser: tree_sitter::Parser,
     modifiers: Modifiers,
     repeat_info: RepeatInfo,
     repeat_handle: RegistrationToken,
+    /// Takes the progress bar and notifications away again
+    time_handle: RegistrationToken,
+    qh: Rc<QueueHandle<SimpleLayer>>,
 }
 
 impl SimpleLayer {
@@ -1919,7 +701,7 @@ impl SimpleLayer {
         exit: LoopSignal,
         pool: SlotPool,
         iced: iced_tiny_skia::Backend,
-        shared_data: SharedData,
+        shared_data: SharedData<SimpleLayer>,
         dwl: ZnetTapesoftwareDwlWmV1,
         cursor_shape_manager: CursorShapeManager,
         dconf: *mut DConfClient,
@@ -1930,6 +712,7 @@ impl SimpleLayer {
         fractional_scaling: WpFractionalScaleManagerV1,
         viewporter: WpViewporter,
         clipboard_state: clipboard::state::State,
+        qh: Rc<QueueHandle<SimpleLayer>>,
     ) -> SimpleLayer {
         let mut tmp = [0; 4];
         Self {
@@ -1951,6 +734,14 @@ impl SimpleLayer {
                     |_, _, _| TimeoutAction::Drop,
                 )
                 .unwrap(),
+            time_handle: loop_handle
+                .insert_source(
+                    Timer::from_duration(Duration::from_secs(31_536_000)),
+                    |_, _, _| TimeoutAction::Drop,
+                )
+                .unwrap(),
+            desktop_entries: DesktopEntries::new(&loop_handle),
+            calculator: Calculator::new(&loop_handle),
             loop_handle,
             ascii_font_width: iced
                 .measure(
@@ -1981,197 +772,427 @@ impl SimpleLayer {
                 config.prefer_prefix = true;
                 config
             }),
-            settings_parser: {
-                let mut parser = tree_sitter::Parser::new();
-                parser
-                    .set_language(&tree_sitter_dconfsomebar::language())
-                    .unwrap();
-                parser.set_timeout_micros(500_000);
-                parser
-            },
             fractional_scaling,
             viewporter,
-            giac: ffi::new_ctx(),
+            history: History::open(),
+            dwl_clients: Vec::new(),
             clipboard_state,
             modifiers: Modifiers::default(),
             repeat_info: RepeatInfo::Disable,
+            qh,
         }
     }
 
     fn write_bar(&mut self, qh: &QueueHandle<Self>) {
+        self.shared_data.notify_subscribers();
         for monitor in self.monitors.values_mut() {
-            monitor.write_bar(&self.bar_settings, &mut self.shared_data, &self.iced, qh);
+            monitor.write_bar(
+                &self.bar_settings,
+                &mut self.shared_data,
+                &self.iced,
+                self.ascii_font_width,
+                qh,
+            );
         }
     }
 
-    fn layout_applauncher(&mut self) {
-        let monitor = self.monitors.values_mut().find(|o| o.selected).unwrap();
-        match &mut monitor.bar_state {
-            BarState::AppLauncher {
-                apps,
-                layout,
-                current_input,
-                selected,
-                prompt,
-                ..
-            } => {
-                layout.clear();
-                let logical_height = monitor.output.viewport.logical_size().height;
-                let input_string = format!("{}: {}", prompt, current_input.borrow());
+    /// Opens the launcher with the clients from dwl, the focused one
+    /// goes last so that the first entry is the one that was focused
+    /// before it
+    fn open_window_switcher(&mut self, qh: &QueueHandle<Self>) {
+        self.dwl_clients.sort_by_key(|(_, focused)| *focused);
+        let apps = self
+            .dwl_clients
+            .drain(..)
+            .map(|(client, _)| client)
+            .collect();
+        self.open_launcher(apps, "windows", qh);
+    }
 
-                let width = self
-                    .iced
-                    .measure(
-                        &input_string,
-                        self.iced.default_size(),
-                        LineHeight::Relative(1.0),
-                        self.bar_settings.default_font,
-                        Size {
-                            width: f32::INFINITY,
-                            height: f32::INFINITY,
-                        },
-                        Shaping::Basic,
-                    )
-                    .width;
+    /// Opens the launcher on the selected monitor with `apps` to pick from
+    fn open_launcher(
+        &mut self,
+        apps: Vec<DesktopCommand>,
+        prompt: &'static str,
+        qh: &QueueHandle<Self>,
+    ) {
+        let Some(monitor) = self.monitors.values_mut().find(|o| o.selected) else {
+            return;
+        };
+        if !monitor.is_in_overlay {
+            monitor.output.layer_surface.set_layer(Layer::Overlay);
+        }
+        // A progress bar that's still shown would close the launcher
+        self.loop_handle.remove(self.time_handle);
+        monitor
+            .output
+            .layer_surface
+            .set_keyboard_interactivity(KeyboardInteractivity::Exclusive);
+        monitor.is_in_overlay = true;
+        monitor.bar_state = BarState::AppLauncher {
+            apps: yoke::Yoke::attach_to_cart(apps, |cart| Commands(cart, Vec::new())),
+            default: String::new(),
+            current_input: Rc::new(RefCell::new(PushString::new())),
+            layout: Vec::new(),
+            selected: 0,
+            prompt,
+        };
+        monitor.output.frame(qh);
+        self.layout_applauncher();
+    }
 
-                layout.push(Primitive::Quad {
-                    bounds: Rectangle {
-                        x: 0.0,
-                        y: 0.0,
-                        width: (self.bar_settings.padding_x * 2.0) + width,
-                        height: logical_height,
-                    },
-                    background: Background::Color(self.bar_settings.color_active.1),
-                    border_radius: [0.0, 0.0, 0.0, 0.0],
-                    border_width: 0.0,
-                    border_color: Color::TRANSPARENT,
-                });
+    fn layout_applauncher(&mut self) {
+        let Some(monitor) = self.monitors.values_mut().find(|o| o.selected) else {
+            return;
+        };
+        layout_applauncher(
+            &mut monitor.bar_state,
+            monitor.output.viewport.logical_size().height,
+            &self.iced,
+            &self.bar_settings,
+            &mut self.matcher,
+            &self.calculator.history,
+            self.calculator.latest.as_ref(),
+        );
+        // The result shows up once the calculator has it
+        if let BarState::AppLauncher {
+            apps,
+            current_input,
+            prompt,
+            ..
+        } = &monitor.bar_state
+        {
+            if *prompt == "calc" || apps.get().1.is_empty() {
+                self.calculator.evaluate(current_input.borrow().as_ref());
+            }
+        }
+    }
 
-                layout.push(Primitive::Text {
-                    content: input_string,
-                    bounds: Rectangle {
-                        x: self.bar_settings.padding_x,
-                        y: logical_height / 2.0,
+    /// Lays the launcher out again if it's open, for results that come
+    /// back from the calculator
+    fn refresh_launcher(&mut self) {
+        let Some(monitor) = self.monitors.values_mut().find(|o| o.selected) else {
+            return;
+        };
+        if let BarState::AppLauncher { .. } = monitor.bar_state {
+            monitor.output.frame(&self.qh);
+            self.layout_applauncher();
+        }
+    }
+}
+
+/// Lays out the launcher in `bar_state`, which has to be
+/// [`BarState::AppLauncher`]. `latest` is the calculator's newest
+/// result, it's shown if it's for what was typed
+fn layout_applauncher(
+    bar_state: &mut BarState,
+    logical_height: f32,
+    iced: &iced_tiny_skia::Backend,
+    bar_settings: &BarSettings,
+    matcher: &mut nucleo_matcher::Matcher,
+    calc_history: &[Evaluation],
+    latest: Option<&Evaluation>,
+) {
+    match bar_state {
+        BarState::AppLauncher {
+            apps,
+            layout,
+            current_input,
+            selected,
+            prompt,
+            ..
+        } => {
+            layout.clear();
+            let input_string = format!("{}: {}", prompt, current_input.borrow());
+
+            let width = iced
+                .measure(
+                    &input_string,
+                    iced.default_size(),
+                    LineHeight::Relative(1.0),
+                    bar_settings.default_font,
+                    Size {
                         width: f32::INFINITY,
-                        height: logical_height,
+                        height: f32::INFINITY,
                     },
-                    color: self.bar_settings.color_active.0,
-                    size: self.iced.default_size(),
-                    line_height: LineHeight::Relative(1.0),
-                    font: self.bar_settings.default_font,
-                    horizontal_alignment: Horizontal::Left,
-                    vertical_alignment: Vertical::Center,
-                    shaping: Shaping::Basic,
-                });
+                    Shaping::Basic,
+                )
+                .width;
 
-                let mut width_at = (self.bar_settings.padding_x * 2.0) + width;
-                let query = Pattern::parse(
-                    current_input.borrow().as_ref(),
-                    nucleo_matcher::pattern::CaseMatching::Ignore,
-                    nucleo_matcher::pattern::Normalization::Smart,
-                );
+            layout.push(Primitive::Quad {
+                bounds: Rectangle {
+                    x: 0.0,
+                    y: 0.0,
+                    width: (bar_settings.padding_x * 2.0) + width,
+                    height: logical_height,
+                },
+                background: Background::Color(bar_settings.color_active.1),
+                border_radius: [0.0, 0.0, 0.0, 0.0],
+                border_width: 0.0,
+                border_color: Color::TRANSPARENT,
+            });
 
-                #[inline(always)]
-                fn map_project_query(
-                    cart: yoke::Yoke<Commands<'static>, Vec<DesktopCommand>>,
-                    matcher: &mut nucleo_matcher::Matcher,
-                    query: Pattern,
-                ) -> yoke::Yoke<Commands<'static>, Vec<DesktopCommand>> {
-                    cart.map_project(|cart, _| {
-                        Commands(cart.0, query.match_list(cart.0.iter(), matcher))
-                    })
-                }
+            layout.push(Primitive::Text {
+                content: input_string,
+                bounds: Rectangle {
+                    x: bar_settings.padding_x,
+                    y: logical_height / 2.0,
+                    width: f32::INFINITY,
+                    height: logical_height,
+                },
+                color: bar_settings.color_active.0,
+                size: iced.default_size(),
+                line_height: LineHeight::Relative(1.0),
+                font: bar_settings.default_font,
+                horizontal_alignment: Horizontal::Left,
+                vertical_alignment: Vertical::Center,
+                shaping: Shaping::Basic,
+            });
 
-                let mut apps_old: yoke::Yoke<Commands<'static>, Vec<DesktopCommand>> =
-                    yoke::Yoke::attach_to_cart(Vec::new(), |cart| Commands(cart, Vec::new()));
-                core::mem::swap(&mut apps_old, apps);
-                *apps = map_project_query(apps_old, &mut self.matcher, query);
-                let apps = apps.get();
-                if apps.1.is_empty() {
-                    let content = unsafe {
-                        ffi::eval(
-                            current_input.borrow().as_ptr(),
-                            self.giac.as_ref().unwrap() as *const _,
-                        )
-                    };
-                    width_at += self.bar_settings.padding_x;
-                    layout.push(Primitive::Text {
-                        content,
-                        bounds: Rectangle {
-                            x: width_at,
-                            y: logical_height / 2.0,
-                            width: f32::INFINITY,
-                            height: logical_height,
-                        },
-                        color: self.bar_settings.color_inactive.0,
-                        size: self.iced.default_size(),
-                        line_height: LineHeight::Relative(1.0),
-                        font: self.bar_settings.default_font,
-                        horizontal_alignment: Horizontal::Left,
-                        vertical_alignment: Vertical::Center,
-                        shaping: Shaping::Basic,
+            let mut width_at = (bar_settings.padding_x * 2.0) + width;
+            let query = Pattern::parse(
+                current_input.borrow().as_ref(),
+                nucleo_matcher::pattern::CaseMatching::Ignore,
+                nucleo_matcher::pattern::Normalization::Smart,
+            );
+
+            #[inline(always)]
+            fn map_project_query(
+                cart: yoke::Yoke<Commands<'static>, Vec<DesktopCommand>>,
+                matcher: &mut nucleo_matcher::Matcher,
+                query: Pattern,
+            ) -> yoke::Yoke<Commands<'static>, Vec<DesktopCommand>> {
+                cart.map_project(|cart, _| {
+                    let mut matches = query.match_list(cart.0.iter(), matcher);
+                    // Stable, so without a query or any history the
+                    // entries keep their order
+                    matches.sort_by(|a, b| {
+                        history::rank(b.1, b.0.frecency)
+                            .total_cmp(&history::rank(a.1, a.0.frecency))
                     });
+                    Commands(cart.0, matches)
+                })
+            }
+
+            let mut apps_old: yoke::Yoke<Commands<'static>, Vec<DesktopCommand>> =
+                yoke::Yoke::attach_to_cart(Vec::new(), |cart| Commands(cart, Vec::new()));
+            core::mem::swap(&mut apps_old, apps);
+            *apps = map_project_query(apps_old, matcher, query);
+            let apps = apps.get();
+            let result = latest
+                .filter(|latest| latest.input == current_input.borrow().as_ref())
+                .map(|latest| match &latest.result {
+                    Ok(result) => result.clone(),
+                    Err(error) => format!("\u{f071} {}", error),
+                });
+            if *prompt == "calc" {
+                if let Some(content) = result {
+                    width_at += push_item(
+                        layout,
+                        content,
+                        false,
+                        width_at,
+                        logical_height,
+                        iced,
+                        bar_settings,
+                        Shaping::Basic,
+                    );
+                }
+                // Newest first, from 1 since 0 is what's being typed
+                *selected = (*selected).min(calc_history.len());
+                let items = calc_history
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(index, evaluation)| (index + 1, evaluation))
+                    // Scrolls along with the selection
+                    .skip(selected.saturating_sub(15))
+                    .take(15);
+                for (index, evaluation) in items {
+                    let content = format!(
+                        "{} = {}",
+                        evaluation.input,
+                        evaluation.result.as_deref().unwrap_or_default()
+                    );
+                    width_at += push_item(
+                        layout,
+                        content,
+                        index == *selected,
+                        width_at,
+                        logical_height,
+                        iced,
+                        bar_settings,
+                        Shaping::Basic,
+                    );
+                }
+            } else if apps.1.is_empty() {
+                if let Some(content) = result {
+                    push_item(
+                        layout,
+                        content,
+                        false,
+                        width_at,
+                        logical_height,
+                        iced,
+                        bar_settings,
+                        Shaping::Basic,
+                    );
+                }
+            } else {
+                // Emoji need a fallback font
+                let shaping = if *prompt == "glyphs" {
+                    Shaping::Advanced
                 } else {
-                    for (index, (item, _)) in
-                        (&apps.1[..15.min(apps.1.len())]).into_iter().enumerate()
-                    {
-                        let measurement = self
-                            .iced
-                            .measure(
-                                &item.name,
-                                self.iced.default_size(),
-                                LineHeight::Relative(1.0),
-                                self.bar_settings.default_font,
-                                Size {
-                                    width: f32::INFINITY,
-                                    height: f32::INFINITY,
-                                },
-                                Shaping::Basic,
-                            )
-                            .width;
-                        if index == *selected {
-                            layout.push(Primitive::Quad {
-                                bounds: Rectangle {
-                                    x: width_at,
-                                    y: 0.0,
-                                    width: (self.bar_settings.padding_x * 2.0) + measurement,
-                                    height: logical_height,
-                                },
-                                background: Background::Color(self.bar_settings.color_active.1),
-                                border_radius: [0.0, 0.0, 0.0, 0.0],
-                                border_width: 0.0,
-                                border_color: Color::TRANSPARENT,
-                            });
-                        }
-                        width_at += self.bar_settings.padding_x;
-                        layout.push(Primitive::Text {
-                            content: item.name.clone(),
-                            bounds: Rectangle {
-                                x: width_at,
-                                y: logical_height / 2.0,
-                                width: f32::INFINITY,
-                                height: logical_height,
-                            },
-                            color: if index == *selected {
-                                self.bar_settings.color_active.0
-                            } else {
-                                self.bar_settings.color_inactive.0
-                            },
-                            size: self.iced.default_size(),
-                            line_height: LineHeight::Relative(1.0),
-                            font: self.bar_settings.default_font,
-                            horizontal_alignment: Horizontal::Left,
-                            vertical_alignment: Vertical::Center,
-                            shaping: Shaping::Basic,
-                        });
-                        width_at += measurement;
-                        width_at += self.bar_settings.padding_x;
-                    }
+                    Shaping::Basic
+                };
+                for (index, (item, _)) in (&apps.1[..15.min(apps.1.len())]).into_iter().enumerate()
+                {
+                    width_at += push_item(
+                        layout,
+                        item.name.clone(),
+                        index == *selected,
+                        width_at,
+                        logical_height,
+                        iced,
+                        bar_settings,
+                        shaping,
+                    );
                 }
             }
-            _ => unreachable!(),
         }
+        _ => unreachable!(),
+    }
+}
+
+/// Pushes one of the launcher's entries at `x`, highlighted if it's
+/// `selected`, and returns how much room it took
+fn push_item(
+    layout: &mut Vec<Primitive>,
+    content: String,
+    selected: bool,
+    x: f32,
+    logical_height: f32,
+    iced: &iced_tiny_skia::Backend,
+    bar_settings: &BarSettings,
+    shaping: Shaping,
+) -> f32 {
+    let measurement = iced
+        .measure(
+            &content,
+            iced.default_size(),
+            LineHeight::Relative(1.0),
+            bar_settings.default_font,
+            Size {
+                width: f32::INFINITY,
+                height: f32::INFINITY,
+            },
+            shaping,
+        )
+        .width;
+    if selected {
+        layout.push(Primitive::Quad {
+            bounds: Rectangle {
+                x,
+                y: 0.0,
+                width: (bar_settings.padding_x * 2.0) + measurement,
+                height: logical_height,
+            },
+            background: Background::Color(bar_settings.color_active.1),
+            border_radius: [0.0, 0.0, 0.0, 0.0],
+            border_width: 0.0,
+            border_color: Color::TRANSPARENT,
+        });
     }
+    layout.push(Primitive::Text {
+        content,
+        bounds: Rectangle {
+            x: x + bar_settings.padding_x,
+            y: logical_height / 2.0,
+            width: f32::INFINITY,
+            height: logical_height,
+        },
+        color: if selected {
+            bar_settings.color_active.0
+        } else {
+            bar_settings.color_inactive.0
+        },
+        size: iced.default_size(),
+        line_height: LineHeight::Relative(1.0),
+        font: bar_settings.default_font,
+        horizontal_alignment: Horizontal::Left,
+        vertical_alignment: Vertical::Center,
+        shaping,
+    });
+    measurement + bar_settings.padding_x * 2.0
+}
+
+/// Everything on the bar while it isn't showing the launcher or the
+/// progress bar
+fn bar_primitives(
+    tags: &Tags,
+    layout: &str,
+    window_title: &str,
+    window_title_bounds: &Range<f32>,
+    status_bar_primitives: &Arc<Primitive>,
+    selected: bool,
+    logical_size: Size<f32>,
+    iced: &iced_tiny_skia::Backend,
+    bar_settings: &BarSettings,
+) -> [Primitive; 6] {
+    [
+        Primitive::Cache {
+            content: Arc::clone(&tags.tags_background),
+        },
+        Primitive::Cache {
+            content: Arc::clone(&tags.primitives),
+        },
+        Primitive::Cache {
+            content: Arc::clone(&tags.tag_windows),
+        },
+        Primitive::Text {
+            content: layout.to_owned(),
+            bounds: Rectangle {
+                x: tags.width + bar_settings.padding_x,
+                y: logical_size.height / 2.0,
+                width: logical_size.width,
+                height: logical_size.height / 2.0,
+            },
+            color: if selected {
+                bar_settings.color_active.0
+            } else {
+                bar_settings.color_inactive.0
+            },
+            size: iced.default_size(),
+            line_height: LineHeight::Relative(1.0),
+            font: bar_settings.default_font,
+            horizontal_alignment: Horizontal::Left,
+            vertical_alignment: Vertical::Center,
+            shaping: Shaping::Basic,
+        },
+        Primitive::Text {
+            content: window_title.to_owned(),
+            bounds: Rectangle {
+                x: window_title_bounds.start,
+                y: logical_size.height / 2.0,
+                width: window_title_bounds.end - window_title_bounds.start,
+                height: logical_size.height / 2.0,
+            },
+            color: if selected {
+                bar_settings.color_active.0
+            } else {
+                bar_settings.color_inactive.0
+            },
+            size: iced.default_size(),
+            line_height: LineHeight::Relative(1.0),
+            font: bar_settings.default_font,
+            horizontal_alignment: Horizontal::Left,
+            vertical_alignment: Vertical::Center,
+            shaping: Shaping::Advanced,
+        },
+        Primitive::Cache {
+            content: Arc::clone(status_bar_primitives),
+        },
+    ]
 }
 
 impl CompositorHandler for SimpleLayer {
@@ -2239,21 +1260,31 @@ impl OutputHandler for SimpleLayer {
             1.0,
         );
 
-        let (primitives, mut bar_size) = self.shared_data.fmt(
-            (
-                self.bar_settings.color_inactive.0,
+        let output_name = self
+            .output_state
+            .info(&output)
+            .and_then(|info| info.name)
+            .unwrap_or_default();
+        let mut hit_boxes = Vec::new();
+        let (primitives, mut bar_size, window_title_bounds) = BarBlocks::new(&self.shared_data)
+            .fmt(
+                &output_name,
+                &mut hit_boxes,
                 (
-                    self.bar_settings.color_inactive.1,
-                    self.bar_settings.color_active.1,
+                    self.bar_settings.color_inactive.0,
+                    (
+                        self.bar_settings.color_inactive.1,
+                        self.bar_settings.color_active.1,
+                    ),
                 ),
-            ),
-            &self.iced,
-            self.bar_settings.default_font,
-            viewport.logical_size(),
-            self.bar_settings.padding_x,
-            self.bar_settings.padding_y,
-            self.bar_settings.divider,
-        );
+                &self.iced,
+                self.bar_settings.default_font,
+                viewport.logical_size(),
+                0.0,
+                self.bar_settings.padding_x,
+                self.bar_settings.padding_y,
+                self.bar_settings.divider,
+            );
         bar_size.height += self.bar_settings.padding_y * 2.0;
 
         viewport = Viewport::with_physical_size(
@@ -2301,7 +1332,9 @@ impl OutputHandler for SimpleLayer {
                 // fractional_scaler,
             },
             wl_output: output,
+            output_name,
             info_output: None,
+            info_hovered: false,
             window_title: String::new(),
             layout: 0,
             dwl: monitor,
@@ -2324,6 +1357,8 @@ impl OutputHandler for SimpleLayer {
                 default_text_size: self.iced.default_size(),
             }),
             bar_size,
+            window_title_bounds,
+            hit_boxes,
         };
 
         new_output.status_bar_primitives = Arc::new(primitives);
@@ -2536,7 +1571,9 @@ impl SimpleLayer {
         event: &KeyEvent,
     ) -> bool {
         self.clipboard_state.keyboard_key(serial);
-        let monitor = self.monitors.values_mut().find(|o| o.selected).unwrap();
+        let Some(monitor) = self.monitors.values_mut().find(|o| o.selected) else {
+            return false;
+        };
         match event.keysym {
             Keysym::Escape => {
                 monitor.bar_state = BarState::Normal;
@@ -2591,8 +1628,26 @@ impl SimpleLayer {
             Keysym::v => {
                 if let BarState::AppLauncher { current_input, .. } = &mut monitor.bar_state {
                     if self.modifiers.ctrl {
+                        let current_input = Rc::clone(current_input);
+                        let qh = qh.clone();
                         self.clipboard_state
-                            .load_selection(SelectionTarget::Clipboard, Rc::clone(current_input))
+                            .load_selection(SelectionTarget::Clipboard, move |state, selection| {
+                                // Files are pasted as their paths
+                                if let Some(text) = selection
+                                    .as_text()
+                                    .or_else(|| selection.uris().map(|uris| uris.join(" ")))
+                                {
+                                    current_input.borrow_mut().push_str(&text);
+                                }
+                                state.layout_applauncher();
+                                state
+                                    .monitors
+                                    .values_mut()
+                                    .find(|o| o.selected)
+                                    .unwrap()
+                                    .output
+                                    .frame(&qh);
+                            })
                             .unwrap();
                     } else if let Some(ref c) = event.utf8 {
                         current_input.borrow_mut().push_str(c);
@@ -2609,27 +1664,116 @@ impl SimpleLayer {
                     selected,
                     default,
                     current_input,
+                    prompt,
                     ..
                 } => {
+                    // Return keeps the result and stays open for the next one
+                    if *prompt == "calc" && !self.modifiers.shift && *selected == 0 {
+                        let input = current_input.borrow().as_ref().to_owned();
+                        if !input.trim().is_empty() {
+                            if self.calculator.commit(&input) {
+                                current_input.borrow_mut().clear();
+                            }
+                            monitor.output.frame(qh);
+                            self.layout_applauncher();
+                            return false;
+                        }
+                    }
+                    let mut launched = Ok(());
+                    let url = (*prompt == "browser")
+                        .then(|| browser::as_url(&current_input.borrow()))
+                        .flatten();
                     if self.modifiers.shift {
-                        let content = unsafe {
-                            ffi::eval(
-                                current_input.borrow().as_ptr(),
-                                self.giac.as_ref().unwrap() as *const _,
-                            )
-                        };
-                        self.clipboard_state
-                            .store_selection(clipboard::state::SelectionTarget::Clipboard, content)
-                            .unwrap();
+                        // What's shown, it isn't worth waiting for giac
+                        // if the result isn't there yet
+                        if let Some(Evaluation {
+                            input,
+                            result: Ok(content),
+                        }) = &self.calculator.latest
+                        {
+                            if *input == current_input.borrow().as_ref() {
+                                self.clipboard_state
+                                    .store_selection(
+                                        clipboard::state::SelectionTarget::Clipboard,
+                                        Selection::text(content.clone()),
+                                    )
+                                    .unwrap();
+                            }
+                        }
+                    } else if *prompt == "calc" {
+                        let evaluation = selected
+                            .checked_sub(1)
+                            .and_then(|back| self.calculator.history.iter().rev().nth(back));
+                        if let Some(Evaluation {
+                            result: Ok(content),
+                            ..
+                        }) = evaluation
+                        {
+                            self.clipboard_state
+                                .store_selection(
+                                    clipboard::state::SelectionTarget::Clipboard,
+                                    Selection::text(content.clone()),
+                                )
+                                .unwrap();
+                        }
+                    } else if let Some(url) = url {
+                        launched = browser::open(&self.bar_settings.browser, &url)
+                            .map_err(|e| format!("Couldn't open {}: {}", url, e));
                     } else if let Some((app, _)) = apps.get().1.get(*selected) {
-                        std::process::Command::new("sh")
-                            .args(["-c", &app.command])
-                            .spawn()
-                            .unwrap();
-                    } else {
-                        let _ = std::process::Command::new("sh")
-                            .args(["-c", &format!("{}{}", default, current_input.borrow())])
-                            .spawn();
+                        match app.target {
+                            Target::Run { ref args, terminal } => {
+                                let name = app.id.as_deref().unwrap_or(*prompt);
+                                let command = desktop_entries::command(
+                                    args,
+                                    terminal,
+                                    &self.bar_settings.terminal,
+                                );
+                                launched = launch::spawn(command, name)
+                                    .map_err(|e| format!("Couldn't launch {}: {}", app.name, e));
+                                if let (Ok(()), Some(id)) = (&launched, &app.id) {
+                                    self.history.record(id);
+                                }
+                            }
+                            Target::Client(id) => self.dwl.focus_client(id),
+                            Target::Copy(ref text) => self
+                                .clipboard_state
+                                .store_selection(
+                                    clipboard::state::SelectionTarget::Clipboard,
+                                    Selection::text(text.clone()),
+                                )
+                                .unwrap(),
+                            Target::ClipboardEntry(id) => {
+                                if let Some(selection) = self.clipboard_state.history.get(id) {
+                                    self.clipboard_state
+                                        .store_selection(
+                                            clipboard::state::SelectionTarget::Clipboard,
+                                            selection,
+                                        )
+                                        .unwrap();
+                                }
+                            }
+                        }
+                    } else if *prompt == "browser" {
+                        let url = browser::search_url(
+                            &current_input.borrow(),
+                            &self.bar_settings.search_engine,
+                        );
+                        launched = browser::open(&self.bar_settings.browser, &url)
+                            .map_err(|e| format!("Couldn't open {}: {}", url, e));
+                    } else if !matches!(*prompt, "windows" | "glyphs" | "clipboard") {
+                        let input = format!("{}{}", default, current_input.borrow());
+                        // Typed text has no field codes, a `%` is just a `%`
+                        launched = desktop_entries::parse_exec(
+                            &input.replace('%', "%%"),
+                            prompt,
+                            None,
+                            std::path::Path::new(""),
+                        )
+                        .and_then(|args| {
+                            let command = desktop_entries::command(&args, false, "");
+                            launch::spawn(command, *prompt).map_err(|e| e.to_string())
+                        })
+                        .map_err(|e| format!("Couldn't run {}: {}", input, e));
                     }
 
                     monitor.bar_state = BarState::Normal;
@@ -2650,6 +1794,9 @@ impl SimpleLayer {
                         monitor.output.frame_req = true;
                     }
                     monitor.output.layer_surface.commit();
+                    if let Err(e) = launched {
+                        self.show_notification(e, qh);
+                    }
                 }
                 _ => return false,
             },
@@ -2759,158 +1906,85 @@ impl PointerHandler for SimpleLayer {
                     }
                     Leave { .. } => {
                         if self.shared_data.selected != SelectedBlock::None {
-                            monitor.info_output.take();
-                            self.shared_data.selected = SelectedBlock::None;
-                            self.write_bar(qh);
+                            // The pointer might be on its way to the info
+                            // popup, whose enter event comes in the next frame
+                            let surface_id = event.surface.id();
+                            let qh = qh.clone();
+                            self.loop_handle.insert_idle(move |data| {
+                                if let Some(monitor) = data.monitors.get_mut(&surface_id) {
+                                    if !monitor.info_hovered {
+                                        monitor.info_output.take();
+                                        data.shared_data.selected = SelectedBlock::None;
+                                        data.write_bar(&qh);
+                                    }
+                                }
+                            });
                         }
                     }
                     Motion { .. } => {
-                        if let Some(ref media) = self.shared_data.playback {
-                            if event.position.0 >= media.x_at as f64 {
-                                if self.shared_data.selected != SelectedBlock::Playback {
-                                    if monitor.info_output.is_none() {
-                                        let surface = self.compositor_state.create_surface(&qh);
-                                        let info_layer = self.layer_shell.create_layer_surface(
-                                            &qh,
-                                            surface,
-                                            Layer::Overlay,
-                                            None::<String>,
-                                            Some(&monitor.wl_output),
-                                        );
-
-                                        info_layer.set_anchor(
-                                            if self.bar_settings.top_bar {
-                                                Anchor::TOP
-                                            } else {
-                                                Anchor::BOTTOM
-                                            } | Anchor::RIGHT,
-                                        );
-                                        info_layer.set_size(
-                                            256 + (self.bar_settings.padding_x as u32 * 2),
-                                            512 + (self.bar_settings.padding_y as u32 * 2),
-                                        );
-                                        info_layer.set_keyboard_interactivity(
-                                            KeyboardInteractivity::None,
-                                        );
-
-                                        info_layer.commit();
-                                        self.output_type_map.insert(
-                                            info_layer.wl_surface().id(),
-                                            OutputType::Info(
-                                                monitor.output.layer_surface.wl_surface().id(),
+                        let selected = monitor.hit(event.position.0);
+
+                        if self.shared_data.selected != selected {
+                            match selected {
+                                SelectedBlock::Block(b, s) => {
+                                    // What's fetched can change the size, and the
+                                    // popup of another segment has its own
+                                    self.shared_data.blocks[b].info_shown(s);
+                                    monitor.info_output.take();
+                                    let hit_box = monitor
+                                        .hit_boxes
+                                        .iter()
+                                        .find(|h| h.block == b && h.segment == s)
+                                        .copied();
+                                    if let (Some((width, height)), Some(hit_box)) =
+                                        (self.shared_data.blocks[b].info_size(s), hit_box)
+                                    {
+                                        // Under the segment, from the edge of the bar
+                                        // on its side
+                                        let bar_width =
+                                            monitor.output.viewport.logical_size().width;
+                                        let (edge, from_edge) = match hit_box.side {
+                                            Side::Left => (Anchor::LEFT, hit_box.x_at),
+                                            Side::Center | Side::Right => (
+                                                Anchor::RIGHT,
+                                                bar_width - hit_box.x_at - hit_box.width,
                                             ),
+                                        };
+                                        // Still fully on screen if it's wider than
+                                        // what's left on that side
+                                        let popup_width =
+                                            (width + self.bar_settings.padding_x as u32 * 2) as f32;
+                                        let margin =
+                                            from_edge.min(bar_width - popup_width).max(0.0) as i32;
+                                        let anchor = if self.bar_settings.top_bar {
+                                            Anchor::TOP
+                                        } else {
+                                            Anchor::BOTTOM
+                                        } | edge;
+                                        self.open_info(
+                                            qh,
+                                            &event.surface.id(),
+                                            anchor,
+                                            margin,
+                                            width,
+                                            height,
                                         );
-                                        let viewport = Viewport::with_physical_size(
-                                            Size {
-                                                width: (256.0
-                                                    + (self.bar_settings.padding_x as f64 * 2.0)
-                                                        * monitor.output.viewport.scale_factor())
-                                                    as u32,
-                                                height: (512.0
-                                                    + (self.bar_settings.padding_y as f64 * 2.0)
-                                                        * monitor.output.viewport.scale_factor())
-                                                    as u32,
-                                            },
-                                            monitor.output.viewport.scale_factor(),
-                                        );
-                                        let fractional_scaler =
-                                            self.fractional_scaling.get_fractional_scale(
-                                                info_layer.wl_surface(),
-                                                qh,
-                                                GlobalData,
-                                            );
-                                        self.fractional_map.insert(
-                                            fractional_scaler.id(),
-                                            info_layer.wl_surface().id(),
-                                        );
-                                        monitor.info_output = Some(Output {
-                                            // fractional_scaler,
-                                            viewporter_vp: self.viewporter.get_viewport(
-                                                info_layer.wl_surface(),
-                                                qh,
-                                                GlobalData,
-                                            ),
-                                            layer_surface: info_layer,
-                                            frame_req: false,
-                                            mask: Mask::new(
-                                                viewport.physical_width(),
-                                                viewport.physical_height(),
-                                            )
-                                            .unwrap(),
-                                            first_configure: true,
-                                            buffers: None,
-                                            viewport,
-                                        });
-                                    }
-                                    self.shared_data.selected = SelectedBlock::Playback;
-                                    self.write_bar(qh);
-                                }
-                                return;
-                            }
-                        }
-
-                        if let Some(ref wireplumber) = self.shared_data.wireplumber {
-                            if event.position.0 >= wireplumber.x_at as f64 {
-                                if self.shared_data.selected != SelectedBlock::WirePlumber {
-                                    self.shared_data.selected = SelectedBlock::WirePlumber;
-                                    self.write_bar(qh);
-                                }
-                                return;
-                            }
-                        }
-
-                        if let Some(ref connman) = self.shared_data.connman {
-                            if event.position.0 >= connman.x_at as f64 {
-                                if self.shared_data.selected != SelectedBlock::Connman {
-                                    self.shared_data.selected = SelectedBlock::Connman;
-                                    self.write_bar(qh);
-                                }
-                                return;
-                            }
-                        }
-
-                        if let Some(ref bat_block) = self.shared_data.bat_block {
-                            for (i, x_at) in bat_block.xs_at.iter().copied().enumerate() {
-                                if event.position.0 >= x_at as f64 {
-                                    if self.shared_data.selected != SelectedBlock::Battery(i) {
-                                        self.shared_data.selected = SelectedBlock::Battery(i);
-                                        self.write_bar(qh);
                                     }
-                                    return;
-                                }
-                            }
-                        }
-
-                        if let Some(ref brightness) = self.shared_data.brightness {
-                            if event.position.0 >= brightness.x_at as f64 {
-                                if self.shared_data.selected != SelectedBlock::Brightness {
-                                    self.shared_data.selected = SelectedBlock::Brightness;
-                                    self.write_bar(qh);
                                 }
-                                return;
-                            }
-                        }
-
-                        if let Some(ref time) = self.shared_data.time {
-                            for i in 0..2 {
-                                if event.position.0 >= time.xs_at[i] as f64 {
-                                    if self.shared_data.selected != SelectedBlock::Time(i) {
-                                        self.shared_data.selected = SelectedBlock::Time(i);
-                                        self.write_bar(qh);
-                                    }
-                                    return;
+                                SelectedBlock::None => {
+                                    monitor.info_output.take();
                                 }
                             }
-                        }
-
-                        if self.shared_data.selected != SelectedBlock::None {
-                            monitor.info_output.take();
-                            self.shared_data.selected = SelectedBlock::None;
+                            self.shared_data.selected = selected;
                             self.write_bar(qh);
                         }
                     }
-                    Press { button, .. } => match button {
-                        BTN_LEFT => {
+                    Press { button, .. } => {
+                        if let SelectedBlock::Block(b, s) = self.shared_data.selected {
+                            let response =
+                                self.shared_data.blocks[b].click(&monitor.output_name, s, button);
+                            self.respond(response, qh);
+                        } else if button == BTN_LEFT {
                             if event.position.0 < monitor.tags.width as f64 {
                                 if let Some(tag) = (0..monitor.tags.tags.len()).find(|&tag| {
                                     (monitor.tags.num_width as f64 * ((tag + 1) as f64))
@@ -2920,17 +1994,272 @@ impl PointerHandler for SimpleLayer {
                                 }
                             }
                         }
-                        _ => {}
-                    },
+                    }
                     Release { .. } => {
                         // println!("Release {:x} @ {:?}", button, event.position);
                     }
-                    Axis { .. } => {
+                    Axis { vertical, .. } => {
                         // println!("Scroll H:{horizontal:?}, V:{vertical:?}");
+                        if vertical.absolute != 0.0 {
+                            if let SelectedBlock::Block(b, s) = self.shared_data.selected {
+                                let response = self.shared_data.blocks[b].scroll(
+                                    &monitor.output_name,
+                                    s,
+                                    vertical.absolute,
+                                );
+                                self.respond(response, qh);
+                            }
+                        }
+                    }
+                }
+            } else if let Some(OutputType::Info(id)) = self.output_type_map.get(&event.surface.id())
+            {
+                let Some(monitor) = self.monitors.get_mut(id) else {
+                    continue;
+                };
+                match event.kind {
+                    Enter { .. } => {
+                        monitor.info_hovered = true;
+                    }
+                    Leave { .. } => {
+                        monitor.info_hovered = false;
+                        if self.shared_data.selected != SelectedBlock::None {
+                            monitor.info_output.take();
+                            self.shared_data.selected = SelectedBlock::None;
+                            self.write_bar(qh);
+                        }
+                    }
+                    Press { button, .. } => {
+                        if let SelectedBlock::Block(b, s) = self.shared_data.selected {
+                            let response = self.shared_data.blocks[b].info_click(
+                                s,
+                                event.position,
+                                button,
+                                &self.bar_settings,
+                            );
+                            self.respond(response, qh);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl Frontend for SimpleLayer {
+    fn shared_data(&mut self) -> &mut SharedData<Self> {
+        &mut self.shared_data
+    }
+
+    fn redraw(&mut self) {
+        let qh = Rc::clone(&self.qh);
+        self.write_bar(&qh);
+    }
+
+    fn progress(&mut self, percentage: f32, icon: char) {
+        let qh = Rc::clone(&self.qh);
+        self.show_progress(percentage, icon, &qh);
+    }
+
+    fn notify(&mut self, text: String) {
+        let qh = Rc::clone(&self.qh);
+        self.show_for_a_while(BarState::Notification { text }, WARNING_SHOW_TIME, &qh);
+    }
+
+    fn setting_changed(&mut self, kind: NodeKind) {
+        let qh = Rc::clone(&self.qh);
+        match kind {
+            NodeKind::Font => {
+                let new_font: String = dconf_read_variant(self.dconf, "/dotfiles/somebar/font")
+                    .unwrap_or(String::from("FiraCode Nerd Font 14"));
+
+                let split = new_font.rsplit_once(' ').unwrap();
+
+                let font = split.0;
+                let font_size = split.1.parse().unwrap();
+
+                self.iced = iced_tiny_skia::Backend::new(iced_tiny_skia::Settings {
+                    default_font: self.iced.default_font(),
+                    default_text_size: font_size,
+                });
+                self.monitors.iter_mut().for_each(|(_, m)| {
+                    m.info_iced = iced_tiny_skia::Backend::new(iced_tiny_skia::Settings {
+                        default_font: self.iced.default_font(),
+                        default_text_size: font_size,
+                    });
+                    if let Some(ref mut i) = m.info_output {
+                        i.frame(qh.as_ref());
+                    }
+                });
+                self.bar_settings.default_font = Font {
+                    family: Family::Name(unsafe { std::mem::transmute(font) }),
+                    ..Default::default()
+                };
+
+                self.bar_settings.default_font_name = new_font;
+                let mut tmp = [0; 4];
+                self.ascii_font_width = self
+                    .iced
+                    .measure(
+                        self.bar_settings.divider.encode_utf8(&mut tmp),
+                        self.iced.default_size(),
+                        LineHeight::Relative(1.0),
+                        self.bar_settings.default_font,
+                        Size {
+                            width: f32::INFINITY,
+                            height: f32::INFINITY,
+                        },
+                        Shaping::Basic,
+                    )
+                    .width;
+
+                self.relayout(Rc::clone(&qh));
+            }
+            NodeKind::FontFallback => {
+                let new_font: String =
+                    dconf_read_variant(self.dconf, "/dotfiles/somebar/font-fallback")
+                        .unwrap_or(String::from("Noto Sans"));
+
+                self.iced = iced_tiny_skia::Backend::new(iced_tiny_skia::Settings {
+                    default_font: Font {
+                        family: Family::Name(unsafe { std::mem::transmute(new_font.as_str()) }),
+                        ..Default::default()
+                    },
+                    default_text_size: self.iced.default_size(),
+                });
+                self.monitors.iter_mut().for_each(|(_, m)| {
+                    m.info_iced = iced_tiny_skia::Backend::new(iced_tiny_skia::Settings {
+                        default_font: Font {
+                            family: Family::Name(unsafe { std::mem::transmute(new_font.as_str()) }),
+                            ..Default::default()
+                        },
+                        default_text_size: self.iced.default_size(),
+                    });
+                    if let Some(ref mut i) = m.info_output {
+                        i.frame(qh.as_ref());
                     }
+                });
+                self.bar_settings.default_font_fallback_name = new_font;
+                self.relayout(Rc::clone(&qh));
+            }
+            NodeKind::Blocks => {
+                for monitor in self.monitors.values_mut() {
+                    monitor.info_output.take();
                 }
+                self.relayout(Rc::clone(&qh));
             }
+            NodeKind::Divider => {
+                self.bar_settings.divider =
+                    dconf_read_variant::<String>(self.dconf, "/dotfiles/somebar/divider")
+                        .and_then(|d| d.chars().next())
+                        .unwrap_or('');
+                self.write_bar(&qh);
+            }
+            NodeKind::BrowserPath => {
+                self.bar_settings.browser_path =
+                    dconf_read_variant(self.dconf, "/dotfiles/somebar/browser-path")
+                        .unwrap_or_else(|| ".firedragon".to_owned());
+            }
+            NodeKind::Browser => {
+                self.bar_settings.browser = format!(
+                    "{} ",
+                    dconf_read_variant(self.dconf, "/dotfiles/somebar/browser",)
+                        .unwrap_or_else(|| "firedragon".to_owned())
+                );
+            }
+            NodeKind::Terminal => {
+                self.bar_settings.terminal =
+                    dconf_read_variant(self.dconf, "/dotfiles/somebar/terminal")
+                        .unwrap_or_else(|| "foot".to_owned());
+            }
+            NodeKind::ClipboardExclude => {
+                self.bar_settings.clipboard_exclude = clipboard_exclude(self.dconf);
+            }
+            NodeKind::SearchEngine => {
+                self.bar_settings.search_engine =
+                    dconf_read_variant(self.dconf, "/dotfiles/somebar/search-engine")
+                        .unwrap_or_else(|| "https://duckduckgo.com/?q=%s".to_owned());
+            }
+            NodeKind::ColorActive => {
+                self.bar_settings.update_color_active(self.dconf);
+                self.relayout(Rc::clone(&qh));
+            }
+            NodeKind::ColorInactive => {
+                self.bar_settings.update_color_inactive(self.dconf);
+                self.relayout(Rc::clone(&qh));
+            }
+            NodeKind::PaddingX => {
+                self.bar_settings.padding_x =
+                    dconf_read_variant::<f64>(self.dconf, "/dotfiles/somebar/padding-x")
+                        .unwrap_or(10.0) as f32;
+
+                self.relayout(Rc::clone(&qh));
+            }
+            NodeKind::PaddingY => {
+                self.bar_settings.padding_y =
+                    dconf_read_variant::<f64>(self.dconf, "/dotfiles/somebar/padding-y")
+                        .unwrap_or(3.0) as f32;
+
+                self.relayout(Rc::clone(&qh));
+            }
+            NodeKind::TopBar => {
+                self.bar_settings.top_bar =
+                    dconf_read_variant(self.dconf, "/dotfiles/somebar/top-bar").unwrap_or(true);
+                for monitor in self.monitors.values_mut() {
+                    monitor.output.layer_surface.set_anchor(
+                        if self.bar_settings.top_bar {
+                            Anchor::TOP
+                        } else {
+                            Anchor::BOTTOM
+                        } | Anchor::LEFT
+                            | Anchor::RIGHT,
+                    );
+                    monitor.output.layer_surface.commit();
+                }
+            }
+            NodeKind::BarShowTime => {
+                self.bar_settings.bar_show_time =
+                    dconf_read_variant(self.dconf, "/dotfiles/somebar/bar-show-time")
+                        .unwrap_or(500);
+            }
+            _ => {}
+        }
+    }
+
+    fn command(&mut self, command: ipc::Command) -> Result<(), String> {
+        let qh = Rc::clone(&self.qh);
+        // The rest show up on the selected monitor
+        if !matches!(
+            command,
+            ipc::Command::ChargeThreshold(_) | ipc::Command::Reload
+        ) && !self.monitors.values().any(|o| o.selected)
+        {
+            return Err("no output is selected".to_owned());
+        }
+        if matches!(command, ipc::Command::WindowSwitcher) && self.dwl.version() < 2 {
+            return Err("dwl doesn't support listing clients".to_owned());
+        }
+        match command {
+            ipc::Command::LaunchApp => self.exec_wob_command(WobCommand::LaunchApp, &qh),
+            ipc::Command::LaunchBrowser => self.exec_wob_command(WobCommand::LaunchBrowser, &qh),
+            ipc::Command::WindowSwitcher => self.exec_wob_command(WobCommand::WindowSwitcher, &qh),
+            ipc::Command::Calculator => self.exec_wob_command(WobCommand::Calculator, &qh),
+            ipc::Command::GlyphPicker => self.exec_wob_command(WobCommand::GlyphPicker, &qh),
+            ipc::Command::ClipboardHistory => {
+                self.exec_wob_command(WobCommand::ClipboardHistory, &qh)
+            }
+            ipc::Command::Progress { percentage, icon } => {
+                self.show_progress(percentage, icon, &qh)
+            }
+            ipc::Command::Notify(text) => self.show_notification(text, &qh),
+            ipc::Command::OverlayToggle => self.exec_wob_command(WobCommand::Overlay, &qh),
+            ipc::Command::ChargeThreshold(threshold) => {
+                return set_charge_threshold(self, threshold)
+            }
+            ipc::Command::Reload => self.reload(qh),
         }
+        Ok(())
     }
 }
 
@@ -2941,6 +2270,149 @@ impl ShmHandler for SimpleLayer {
 }
 
 impl SimpleLayer {
+    /// Shows the progress bar on the selected monitor for `bar_show_time`
+    /// milliseconds, then goes back to the normal bar
+    pub fn show_progress(&mut self, percentage: f32, icon: char, qh: &QueueHandle<Self>) {
+        let duration = Duration::from_millis(self.bar_settings.bar_show_time);
+        self.show_for_a_while(BarState::ProgressBar { percentage, icon }, duration, qh);
+    }
+
+    /// Shows `text` in place of the bar on the selected monitor, for as
+    /// long as the progress bar would be shown
+    pub fn show_notification(&mut self, text: String, qh: &QueueHandle<Self>) {
+        let duration = Duration::from_millis(self.bar_settings.bar_show_time);
+        self.show_for_a_while(BarState::Notification { text }, duration, qh);
+    }
+
+    fn show_for_a_while(
+        &mut self,
+        bar_state: BarState,
+        duration: Duration,
+        qh: &QueueHandle<Self>,
+    ) {
+        let Some(monitor) = self.monitors.values_mut().find(|o| o.selected) else {
+            return;
+        };
+        if !monitor.is_in_overlay {
+            monitor.output.layer_surface.set_layer(Layer::Overlay);
+        }
+        self.loop_handle.remove(self.time_handle);
+        monitor.is_in_overlay = true;
+        monitor.bar_state = bar_state;
+        monitor.output.frame(qh);
+        // The monitor that was selected then, which may have gone away
+        let surface_id = monitor.output.layer_surface.wl_surface().id();
+        let qh = qh.clone();
+        self.time_handle = self
+            .loop_handle
+            .insert_source(Timer::from_duration(duration), move |_, _, data| {
+                let Some(monitor) = data.monitors.get_mut(&surface_id) else {
+                    return TimeoutAction::Drop;
+                };
+                monitor.bar_state = BarState::Normal;
+                if monitor.is_in_overlay {
+                    monitor.output.layer_surface.set_layer(Layer::Bottom);
+                    monitor.is_in_overlay = false;
+                }
+                monitor.output.frame(&qh);
+                TimeoutAction::Drop
+            })
+            .unwrap();
+    }
+
+    /// Acts on what a block did with a click or a scroll
+    fn respond(&mut self, response: Response, qh: &QueueHandle<Self>) {
+        match response {
+            Response::Ignored => {}
+            Response::Redraw => self.write_bar(qh),
+            Response::Progress { percentage, icon } => self.show_progress(percentage, icon, qh),
+            Response::Notify(text) => self.show_notification(text, qh),
+        }
+    }
+
+    /// [`SharedData::reload`], then lays the bars out again
+    pub fn reload(&mut self, qh: Rc<QueueHandle<Self>>) {
+        self.shared_data.reload();
+        for monitor in self.monitors.values_mut() {
+            monitor.info_output.take();
+        }
+        self.relayout(qh);
+    }
+
+    /// Runs a command from dwl or the socket on the selected monitor
+    pub fn exec_wob_command(&mut self, command: WobCommand, qh: &QueueHandle<Self>) {
+        // Older dwl can't list its clients
+        if command == WobCommand::WindowSwitcher && self.dwl.version() < 2 {
+            return;
+        }
+        let Some(monitor) = self.monitors.values_mut().find(|o| o.selected) else {
+            return;
+        };
+        match command {
+            WobCommand::VolumeUp | WobCommand::VolumeDown => {
+                if let Some(wireplumber) = self.shared_data.block::<WirePlumberBlock>() {
+                    let change = match command {
+                        WobCommand::VolumeUp => 0.05,
+                        WobCommand::VolumeDown => -0.05,
+                        _ => unreachable!(),
+                    };
+                    wireplumber.adjust_volume(change);
+                }
+            }
+            WobCommand::LightUp | WobCommand::LightDown => {
+                if let Some(brightness) = self.shared_data.block_mut::<BrightnessBlock>() {
+                    let change = match command {
+                        WobCommand::LightUp => 5,
+                        WobCommand::LightDown => -5,
+                        _ => unreachable!(),
+                    };
+                    if let Some(percentage) = brightness.adjust(&monitor.output_name, change) {
+                        let icon = brightness.icon(&monitor.output_name);
+                        self.show_progress(percentage, icon, qh);
+                    }
+                }
+            }
+            WobCommand::LaunchApp => {
+                let apps = self.desktop_entries.commands(&self.history.frecencies());
+                self.open_launcher(apps, "run", qh);
+            }
+            WobCommand::LaunchBrowser => {
+                let apps =
+                    browser::entries(&self.bar_settings.browser_path, &self.bar_settings.browser);
+                self.open_launcher(apps, "browser", qh);
+            }
+            WobCommand::Calculator => self.open_launcher(Vec::new(), "calc", qh),
+            WobCommand::GlyphPicker => self.open_launcher(glyphs::entries(), "glyphs", qh),
+            WobCommand::ClipboardHistory => {
+                let apps = self.clipboard_state.history.commands();
+                self.open_launcher(apps, "clipboard", qh);
+            }
+            WobCommand::WindowSwitcher => {
+                // The launcher opens once dwl sent every client
+                self.dwl_clients.clear();
+                self.dwl.list_clients();
+            }
+            WobCommand::Overlay => {
+                if monitor.is_in_overlay {
+                    monitor.output.layer_surface.set_layer(Layer::Bottom);
+                    monitor.is_in_overlay = false;
+                } else {
+                    self.loop_handle.remove(self.time_handle);
+                    monitor.output.layer_surface.set_layer(Layer::Overlay);
+                    monitor.is_in_overlay = true;
+                }
+                monitor.output.frame(qh);
+            }
+            WobCommand::PowerButton => {
+                monitor.output.frame(qh);
+                if monitor.info_output.is_none() {
+                    let surface_id = monitor.output.layer_surface.wl_surface().id();
+                    self.open_info(qh, &surface_id, Anchor::all(), 0, 512, 256);
+                }
+            }
+        }
+    }
+
     pub fn relayout(&mut self, qh: Rc<QueueHandle<Self>>) {
         self.write_bar(qh.as_ref());
 
@@ -2971,6 +2443,14 @@ impl SimpleLayer {
                 self.bar_settings.color_inactive.0,
                 self.bar_settings.padding_x,
             );
+            // Blocks on the left are placed after the tags
+            monitor.write_bar(
+                &self.bar_settings,
+                &mut self.shared_data,
+                &self.iced,
+                self.ascii_font_width,
+                qh.as_ref(),
+            );
 
             if !monitor.output.frame_req {
                 monitor.output.layer_surface.wl_surface().frame(
@@ -3000,63 +2480,17 @@ impl SimpleLayer {
                         self.iced.draw::<String>(
                             &mut pixmap,
                             &mut monitor.output.mask,
-                            &[
-                                Primitive::Cache {
-                                    content: Arc::clone(&monitor.tags.tags_background),
-                                },
-                                Primitive::Cache {
-                                    content: Arc::clone(&monitor.tags.primitives),
-                                },
-                                Primitive::Cache {
-                                    content: Arc::clone(&monitor.tags.tag_windows),
-                                },
-                                Primitive::Text {
-                                    content: self.layouts[monitor.layout].clone(),
-                                    bounds: Rectangle {
-                                        x: monitor.tags.width + self.bar_settings.padding_x,
-                                        y: logical_size.height / 2.0,
-                                        width: logical_size.width,
-                                        height: logical_size.height / 2.0,
-                                    },
-                                    color: if monitor.selected {
-                                        self.bar_settings.color_active.0
-                                    } else {
-                                        self.bar_settings.color_inactive.0
-                                    },
-                                    size: self.iced.default_size(),
-                                    line_height: LineHeight::Relative(1.0),
-                                    font: self.bar_settings.default_font,
-                                    horizontal_alignment: Horizontal::Left,
-                                    vertical_alignment: Vertical::Center,
-                                    shaping: Shaping::Basic,
-                                },
-                                Primitive::Text {
-                                    content: monitor.window_title.clone(),
-                                    bounds: Rectangle {
-                                        x: monitor.tags.width
-                                            + (self.bar_settings.padding_x * 2.0)
-                                            + (self.ascii_font_width * 3.0),
-                                        y: logical_size.height / 2.0,
-                                        width: (logical_size.width - monitor.bar_size.width)
-                                            - (monitor.tags.width + (self.ascii_font_width * 3.0)),
-                                        height: logical_size.height / 2.0,
-                                    },
-                                    color: if monitor.selected {
-                                        self.bar_settings.color_active.0
-                                    } else {
-                                        self.bar_settings.color_inactive.0
-                                    },
-                                    size: self.iced.default_size(),
-                                    line_height: LineHeight::Relative(1.0),
-                                    font: self.bar_settings.default_font,
-                                    horizontal_alignment: Horizontal::Left,
-                                    vertical_alignment: Vertical::Center,
-                                    shaping: Shaping::Advanced,
-                                },
-                                Primitive::Cache {
-                                    content: Arc::clone(&monitor.status_bar_primitives),
-                                },
-                            ],
+                            &bar_primitives(
+                                &monitor.tags,
+                                &self.layouts[monitor.layout],
+                                &monitor.window_title,
+                                &monitor.window_title_bounds,
+                                &monitor.status_bar_primitives,
+                                monitor.selected,
+                                logical_size,
+                                &self.iced,
+                                &self.bar_settings,
+                            ),
                             &monitor.output.viewport,
                             &[Rectangle {
                                 x: 0.0,
@@ -3142,6 +2576,56 @@ impl SimpleLayer {
                             &[],
                         );
                     }
+                    BarState::Notification { text } => {
+                        let (foreground, background) = if monitor.selected {
+                            self.bar_settings.color_active
+                        } else {
+                            self.bar_settings.color_inactive
+                        };
+                        self.iced.draw::<String>(
+                            &mut pixmap,
+                            &mut monitor.output.mask,
+                            &[
+                                Primitive::Quad {
+                                    bounds: Rectangle {
+                                        x: 0.0,
+                                        y: 0.0,
+                                        width: logical_size.width,
+                                        height: logical_size.height,
+                                    },
+                                    background: Background::Color(background),
+                                    border_radius: [0.0; 4],
+                                    border_width: 0.0,
+                                    border_color: Color::TRANSPARENT,
+                                },
+                                Primitive::Text {
+                                    content: text.clone(),
+                                    bounds: Rectangle {
+                                        x: self.bar_settings.padding_x,
+                                        y: logical_size.height / 2.0,
+                                        width: logical_size.width,
+                                        height: logical_size.height,
+                                    },
+                                    color: foreground,
+                                    size: self.iced.default_size(),
+                                    line_height: LineHeight::Relative(1.0),
+                                    font: self.bar_settings.default_font,
+                                    horizontal_alignment: Horizontal::Left,
+                                    vertical_alignment: Vertical::Center,
+                                    shaping: Shaping::Advanced,
+                                },
+                            ],
+                            &monitor.output.viewport,
+                            &[Rectangle {
+                                x: 0.0,
+                                y: 0.0,
+                                width: width as f32,
+                                height: height as f32,
+                            }],
+                            background,
+                            &[],
+                        );
+                    }
                     BarState::AppLauncher { layout, .. } => {
                         self.iced.draw::<String>(
                             &mut pixmap,
@@ -3184,6 +2668,72 @@ impl SimpleLayer {
             }
         }
     }
+    /// Opens the info popup next to the bar on `surface_id`, `width` and
+    /// `height` are logical and don't include the padding. `margin` is
+    /// kept from the left or right edge it's anchored to
+    fn open_info(
+        &mut self,
+        qh: &QueueHandle<Self>,
+        surface_id: &ObjectId,
+        anchor: Anchor,
+        margin: i32,
+        width: u32,
+        height: u32,
+    ) {
+        let monitor = self.monitors.get_mut(surface_id).unwrap();
+        let surface = self.compositor_state.create_surface(&qh);
+        let info_layer = self.layer_shell.create_layer_surface(
+            &qh,
+            surface,
+            Layer::Overlay,
+            None::<String>,
+            Some(&monitor.wl_output),
+        );
+
+        info_layer.set_anchor(anchor);
+        // Margins only apply to the edges it's anchored to
+        info_layer.set_margin(0, margin, 0, margin);
+        info_layer.set_size(
+            width + (self.bar_settings.padding_x as u32 * 2),
+            height + (self.bar_settings.padding_y as u32 * 2),
+        );
+        info_layer.set_keyboard_interactivity(KeyboardInteractivity::None);
+
+        info_layer.commit();
+        self.output_type_map.insert(
+            info_layer.wl_surface().id(),
+            OutputType::Info(monitor.output.layer_surface.wl_surface().id()),
+        );
+        let viewport = Viewport::with_physical_size(
+            Size {
+                width: (width as f64
+                    + (self.bar_settings.padding_x as f64 * 2.0)
+                        * monitor.output.viewport.scale_factor()) as u32,
+                height: (height as f64
+                    + (self.bar_settings.padding_y as f64 * 2.0)
+                        * monitor.output.viewport.scale_factor()) as u32,
+            },
+            monitor.output.viewport.scale_factor(),
+        );
+        let fractional_scaler =
+            self.fractional_scaling
+                .get_fractional_scale(info_layer.wl_surface(), qh, GlobalData);
+        self.fractional_map
+            .insert(fractional_scaler.id(), info_layer.wl_surface().id());
+        monitor.info_output = Some(Output {
+            // fractional_scaler,
+            viewporter_vp: self
+                .viewporter
+                .get_viewport(info_layer.wl_surface(), qh, GlobalData),
+            layer_surface: info_layer,
+            frame_req: false,
+            mask: Mask::new(viewport.physical_width(), viewport.physical_height()).unwrap(),
+            first_configure: true,
+            buffers: None,
+            viewport,
+        });
+    }
+
     pub fn draw_info_box(&mut self, _qh: &QueueHandle<Self>, surface_id: &ObjectId) {
         if let Some(monitor) = self.monitors.get_mut(surface_id) {
             if let Some(ref mut output) = monitor.info_output {
@@ -3196,139 +2746,36 @@ impl SimpleLayer {
                 if let Some(ref mut buffers) = output.buffers {
                     let canvas = buffers.canvas(&mut self.pool).unwrap();
                     let mut pixmap = PixmapMut::from_bytes(canvas, width, height).unwrap();
-                    match self.shared_data.selected {
-                        SelectedBlock::Playback => {
-                            let playback_block = self.shared_data.playback.as_ref().unwrap();
-
-                            let mut y = self.bar_settings.padding_y;
-                            let bounds = Rectangle {
-                                x: logical_size.width / 2.0,
-                                y,
-                                width: logical_size.width,
-                                height: logical_size.height,
-                            };
-                            let title = Primitive::Text {
-                                content: playback_block.song_metadata.title.clone(),
-                                bounds,
-                                color: self.bar_settings.color_active.0,
-                                size: monitor.info_iced.default_size(),
-                                line_height: LineHeight::Relative(1.0),
-                                font: self.bar_settings.default_font,
-                                horizontal_alignment: Horizontal::Center,
-                                vertical_alignment: Vertical::Top,
-                                shaping: Shaping::Advanced,
-                            };
-                            let measurement = monitor.info_iced.measure(
-                                &playback_block.song_metadata.title,
-                                monitor.info_iced.default_size(),
-                                LineHeight::Relative(1.0),
-                                self.bar_settings.default_font,
-                                bounds.size(),
-                                Shaping::Advanced,
-                            );
-                            y += measurement.height + self.bar_settings.padding_y;
-                            let bounds = Rectangle {
-                                x: logical_size.width / 2.0,
-                                y,
-                                width: logical_size.width,
-                                height: logical_size.height,
-                            };
-                            let artist = Primitive::Text {
-                                content: playback_block.song_metadata.artist.clone(),
-                                bounds,
-                                color: self.bar_settings.color_active.0,
-                                size: monitor.info_iced.default_size() / 1.25,
-                                line_height: LineHeight::Relative(1.0),
-                                font: self.bar_settings.default_font,
-                                horizontal_alignment: Horizontal::Center,
-                                vertical_alignment: Vertical::Top,
-                                shaping: Shaping::Advanced,
-                            };
-                            let measurement = monitor.info_iced.measure(
-                                &playback_block.song_metadata.artist,
-                                monitor.info_iced.default_size(),
-                                LineHeight::Relative(1.0),
-                                self.bar_settings.default_font,
-                                bounds.size(),
-                                Shaping::Advanced,
-                            );
-                            y += measurement.height + self.bar_settings.padding_y;
-                            let image = if let Some(handle) = playback_block.album_art.clone() {
-                                let width =
-                                    logical_size.width - (self.bar_settings.padding_x * 2.0);
-                                let bounds = Rectangle {
-                                    x: self.bar_settings.padding_x,
-                                    y,
-                                    width,
-                                    height: width,
-                                };
-                                y += width;
-                                Primitive::Image { handle, bounds }
-                            } else {
-                                Primitive::Group {
-                                    primitives: Vec::new(),
-                                }
-                            };
-                            y += self.bar_settings.padding_y * 2.0;
-                            let bounds = Rectangle {
-                                x: logical_size.width / 2.0,
-                                y,
-                                width: logical_size.width,
-                                height: logical_size.height,
-                            };
-                            let album = Primitive::Text {
-                                content: playback_block.song_metadata.album.clone(),
-                                bounds,
-                                color: self.bar_settings.color_active.0,
-                                size: monitor.info_iced.default_size(),
-                                line_height: LineHeight::Relative(1.0),
-                                font: self.bar_settings.default_font,
-                                horizontal_alignment: Horizontal::Center,
-                                vertical_alignment: Vertical::Top,
-                                shaping: Shaping::Advanced,
-                            };
-                            let measurement = monitor.info_iced.measure(
-                                &playback_block.song_metadata.album,
-                                monitor.info_iced.default_size(),
-                                LineHeight::Relative(1.0),
-                                self.bar_settings.default_font,
-                                bounds.size(),
-                                Shaping::Advanced,
-                            );
-                            y += measurement.height + self.bar_settings.padding_y;
-                            let album_artist = Primitive::Text {
-                                content: playback_block.song_metadata.album_artist.clone(),
-                                bounds: Rectangle {
-                                    x: logical_size.width / 2.0,
-                                    y,
-                                    width: logical_size.width,
-                                    height: logical_size.height,
-                                },
-                                color: self.bar_settings.color_active.0,
-                                size: monitor.info_iced.default_size() / 1.25,
-                                line_height: LineHeight::Relative(1.0),
-                                font: self.bar_settings.default_font,
-                                horizontal_alignment: Horizontal::Center,
-                                vertical_alignment: Vertical::Top,
-                                shaping: Shaping::Advanced,
-                            };
-
-                            monitor.info_iced.draw::<String>(
-                                &mut pixmap,
-                                &mut output.mask,
-                                &[title, artist, image, album, album_artist],
-                                &output.viewport,
-                                &[Rectangle {
-                                    x: 0.0,
-                                    y: 0.0,
-                                    width: width as f32,
-                                    height: height as f32,
-                                }],
-                                self.bar_settings.color_active.1,
-                                &[],
-                            );
-                        }
-                        _ => {}
+                    if let SelectedBlock::Block(b, s) = self.shared_data.selected {
+                        let primitives = self.shared_data.blocks[b].info(
+                            s,
+                            &monitor.info_iced,
+                            &self.bar_settings,
+                            logical_size,
+                        );
+
+                        monitor.info_iced.draw::<String>(
+                            &mut pixmap,
+                            &mut output.mask,
+                            &primitives,
+                            &output.viewport,
+                            &[Rectangle {
+                                x: 0.0,
+                                y: 0.0,
+                                width: width as f32,
+                                height: height as f32,
+                            }],
+                            self.bar_settings.color_active.1,
+                            &[],
+                        );
+                        let scale = output.viewport.scale_factor() as f32;
+                        self.shared_data.blocks[b].draw_info(
+                            s,
+                            &mut pixmap,
+                            Transform::from_scale(scale, scale),
+                            &self.bar_settings,
+                            logical_size,
+                        );
                     }
                     // Damage the entire window
                     output.layer_surface.wl_surface().damage_buffer(
@@ -3503,256 +2950,35 @@ impl
             }
             znet_dwl::znet_tapesoftware_dwl_wm_v1::Event::ExecWobCommand { command } => {
                 if let Ok(command) = command.into_result() {
-                    let monitor = state.monitors.values_mut().find(|o| o.selected).unwrap();
-                    if !monitor.is_in_overlay {
-                        monitor.output.layer_surface.set_layer(Layer::Overlay);
-                    }
-                    state.loop_handle.remove(state.shared_data.time_handle);
-                    let number = String::from_utf8(match command {
-                        WobCommand::VolumeUp | WobCommand::VolumeDown => {
-                            if let Some(ref wireplumber) = state.shared_data.wireplumber {
-                                let change = match command {
-                                    WobCommand::VolumeUp => 0.05,
-                                    WobCommand::VolumeDown => -0.05,
-                                    _ => unreachable!()
-                                };
-                                wireplumber.adjust_volume(change);
-                            }
-                            return;
-                        }
-                        WobCommand::LightUp => {
-                            std::process::Command::new("light")
-                                .args(&["-A", "5"])
-                                .status()
-                                .unwrap();
-                            std::process::Command::new("light")
-                                .arg("-G")
-                                .output()
-                                .unwrap()
-                                .stdout
-                        }
-                        WobCommand::LightDown => {
-                            std::process::Command::new("light")
-                                .args(&["-U", "5"])
-                                .status()
-                                .unwrap();
-                            std::process::Command::new("light")
-                                .arg("-G")
-                                .output()
-                                .unwrap()
-                                .stdout
-                        }
-                        WobCommand::LaunchApp => {
-                            monitor
-                                .output
-                                .layer_surface
-                                .set_keyboard_interactivity(KeyboardInteractivity::Exclusive);
-                            monitor.is_in_overlay = true;
-                            let apps = freedesktop_desktop_entry::Iter::new(default_paths()).fold(
-                                Vec::new(),
-                                |mut items, entry| {
-                                    if let Ok(bytes) = std::fs::read_to_string(&entry) {
-                                        if let Ok(entry) = DesktopEntry::decode(&entry, &bytes) {
-                                            if let Some(name) = entry.name(None) {
-                                                if let Some(exec) = entry.exec() {
-                                                    let mut command = exec.to_owned();
-                                                    while let Some(index) =
-                                                        memchr(b'%', command.as_bytes())
-                                                    {
-                                                        if index + 1 == command.len() {
-                                                            command.pop();
-                                                            command.pop();
-                                                        } else {
-                                                            command.remove(index + 1);
-                                                            command.remove(index);
-                                                        }
-                                                    }
-                                                    items.push(DesktopCommand {
-                                                        name: name.into_owned(),
-                                                        command,
-                                                    });
-                                                }
-                                            }
-                                        }
-                                    }
-                                    items
-                                },
-                            );
-                            monitor.bar_state = BarState::AppLauncher {
-                                apps: yoke::Yoke::attach_to_cart(apps, |cart| {
-                                    Commands(cart, Vec::new())
-                                }),
-                                default: String::new(),
-                                current_input: Rc::new(RefCell::new(PushString::new())),
-                                layout: Vec::new(),
-                                selected: 0,
-                                prompt: "run"
-                            };
-                            monitor.output.frame(qh);
-                            state.layout_applauncher();
-                            return;
-                        }
-                        WobCommand::LaunchBrowser => {
-                            monitor
-                                .output
-                                .layer_surface
-                                .set_keyboard_interactivity(KeyboardInteractivity::Exclusive);
-                            monitor.is_in_overlay = true;
-                            let path = format!(
-                                "echo file:$(realpath ~/{}/*.default-release/places.sqlite)?immutable=true",
-                                state.bar_settings.browser_path
-                            );
-                            let cmd = std::process::Command::new("sh")
-                                .args(["-c", &path])
-                                .output()
-                                .unwrap()
-                                .stdout;
-                            let conn =
-                                rusqlite::Connection::open_with_flags(String::from_utf8_lossy(&cmd).trim(), OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI)
-                                    .unwrap();
-                            let mut stmt = conn
-                                .prepare(
-                                    "SELECT \
-                                moz_bookmarks.title, \
-                                url \
-                                FROM moz_bookmarks \
-                                INNER JOIN \
-                                moz_places on \
-                                moz_bookmarks.fk = moz_places.id \
-                                ORDER BY frecency DESC;",
-                                )
-                                .unwrap();
-
-                            let apps = stmt
-                                .query_map([], |row| {
-                                    Ok(DesktopCommand {
-                                        name: row.get(0).unwrap(),
-                                        command: format!(
-                                            "{}{}",
-                                            state.bar_settings.browser,
-                                            row.get::<_, String>(1).unwrap()
-                                        ),
-                                    })
-                                })
-                                .unwrap()
-                                .map(|d| d.unwrap())
-                                .collect();
-
-                            monitor.bar_state = BarState::AppLauncher {
-                                apps: yoke::Yoke::attach_to_cart(apps, |cart| {
-                                    Commands(cart, Vec::new())
-                                }),
-                                default: state.bar_settings.browser.clone(),
-                                current_input: Rc::new(RefCell::new(PushString::new())),
-                                layout: Vec::new(),
-                                selected: 0,
-                                prompt: "browser"
-                            };
-                            monitor.output.frame(qh);
-                            state.layout_applauncher();
-                            return;
-                        }
-                        WobCommand::Overlay => {
-                            if monitor.is_in_overlay {
-                                monitor.output.layer_surface.set_layer(Layer::Bottom);
-                                monitor.is_in_overlay = false;
-                            } else {
-                                monitor.is_in_overlay = true;
-                            }
-                            monitor.output.frame(qh);
-                            return;
-                        }
-                        WobCommand::PowerButton => {
-                            monitor.output.frame(qh);
-                            if monitor.info_output.is_none() {
-                                let surface = state.compositor_state.create_surface(&qh);
-                                let info_layer = state.layer_shell.create_layer_surface(
-                                    &qh,
-                                    surface,
-                                    Layer::Overlay,
-                                    None::<String>,
-                                    Some(&monitor.wl_output),
-                                );
-
-                                info_layer.set_anchor(Anchor::all());
-                                info_layer.set_size(
-                                    512 + (state.bar_settings.padding_x as u32 * 2),
-                                    256 + (state.bar_settings.padding_y as u32 * 2),
-                                );
-                                info_layer.set_keyboard_interactivity(KeyboardInteractivity::None);
-
-                                info_layer.commit();
-                                state.output_type_map.insert(
-                                    info_layer.wl_surface().id(),
-                                    OutputType::Info(
-                                        monitor.output.layer_surface.wl_surface().id(),
-                                    ),
-                                );
-                                let viewport = Viewport::with_physical_size(
-                                    Size {
-                                        width: (512.0 + (state.bar_settings.padding_x as f64 * 2.0)
-                                            * monitor.output.viewport.scale_factor()) as u32,
-                                        height: (256.0 + (state.bar_settings.padding_y as f64 * 2.0)
-                                            * monitor.output.viewport.scale_factor()) as u32,
-                                    },
-                                    monitor.output.viewport.scale_factor(),
-                                );
-                                let fractional_scaler = state
-                                        .fractional_scaling
-                                        .get_fractional_scale(
-                                            info_layer.wl_surface(),
-                                            qh,
-                                            GlobalData,
-                                        );
-                                state.fractional_map.insert(fractional_scaler.id(), info_layer.wl_surface().id());
-                                monitor.info_output = Some(Output {
-                                    // fractional_scaler,
-                                    viewporter_vp: state.viewporter.get_viewport(info_layer.wl_surface(), qh, GlobalData),
-                                    layer_surface: info_layer,
-                                    frame_req: false,
-                                    mask: Mask::new(
-                                        viewport.physical_width(),
-                                        viewport.physical_height(),
-                                    )
-                                    .unwrap(),
-                                    first_configure: true,
-                                    buffers: None,
-                                    viewport,
-                                });
-                            }
-                            return;
-                        }
-                    })
-                    .unwrap();
-
-                    monitor.is_in_overlay = true;
-                    monitor.bar_state = BarState::ProgressBar {
-                        percentage: number.trim().parse::<f32>().unwrap() / 100.0,
-                        icon: command.into(),
-                    };
-                    monitor.output.frame(qh);
-                    let qh: &'static QueueHandle<Self> = unsafe { std::mem::transmute(qh) };
-                    state.shared_data.time_handle = state
-                        .loop_handle
-                        .insert_source(
-                            Timer::from_duration(Duration::from_millis(
-                                state.bar_settings.bar_show_time,
-                            )),
-                            move |_, _, data| {
-                                let monitor =
-                                    data.monitors.values_mut().find(|o| o.selected).unwrap();
-                                monitor.bar_state = BarState::Normal;
-                                if monitor.is_in_overlay {
-                                    monitor.output.layer_surface.set_layer(Layer::Bottom);
-                                    monitor.is_in_overlay = false;
-                                }
-                                monitor.output.frame(qh);
-                                TimeoutAction::Drop
-                            },
-                        )
-                        .unwrap();
+                    state.exec_wob_command(command, qh);
                 }
             }
+            znet_dwl::znet_tapesoftware_dwl_wm_v1::Event::Client {
+                id,
+                title,
+                app_id,
+                tags,
+                monitor,
+                focused,
+            } => {
+                let tags = (0..u32::BITS)
+                    .filter(|tag| tags & (1 << tag) != 0)
+                    .map(|tag| (tag + 1).to_string())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                state.dwl_clients.push((
+                    DesktopCommand {
+                        name: format!("{}: {} [{}] {}", app_id, title, tags, monitor),
+                        target: Target::Client(id),
+                        id: None,
+                        frecency: 0.0,
+                    },
+                    focused != 0,
+                ));
+            }
+            znet_dwl::znet_tapesoftware_dwl_wm_v1::Event::ClientsDone => {
+                state.open_window_switcher(qh);
+            }
         }
     }
 }
@@ -3832,7 +3058,13 @@ impl client::Dispatch<WpFractionalScaleV1, GlobalData> for SimpleLayer {
         //     .layer_surface
         //     .set_buffer_scale(new_factor as u32)
         //     .unwrap();
-        monitor.write_bar(&state.bar_settings, &mut state.shared_data, &state.iced, qh);
+        monitor.write_bar(
+            &state.bar_settings,
+            &mut state.shared_data,
+            &state.iced,
+            state.ascii_font_width,
+            qh,
+        );
     }
 }
 
@@ -0,0 +1,115 @@
+// This code was autogenerated with `dbus-codegen-rust -s -g -m None -d org.freedesktop.NetworkManager -p /org/freedesktop/NetworkManager`, see https://github.com/diwic/dbus-rs
+// Only the properties the network block reads are kept
+use dbus;
+#[allow(unused_imports)]
+use dbus::arg;
+use dbus::blocking;
+
+pub trait OrgFreedesktopNetworkManager {
+    fn state(&self) -> Result<u32, dbus::Error>;
+    fn active_connections(&self) -> Result<Vec<dbus::Path<'static>>, dbus::Error>;
+    fn primary_connection(&self) -> Result<dbus::Path<'static>, dbus::Error>;
+}
+
+impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>> OrgFreedesktopNetworkManager
+    for blocking::Proxy<'a, C>
+{
+    fn state(&self) -> Result<u32, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.NetworkManager",
+            "State",
+        )
+    }
+
+    fn active_connections(&self) -> Result<Vec<dbus::Path<'static>>, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.NetworkManager",
+            "ActiveConnections",
+        )
+    }
+
+    fn primary_connection(&self) -> Result<dbus::Path<'static>, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.NetworkManager",
+            "PrimaryConnection",
+        )
+    }
+}
+
+pub trait OrgFreedesktopNetworkManagerConnectionActive {
+    fn id(&self) -> Result<String, dbus::Error>;
+    fn type_(&self) -> Result<String, dbus::Error>;
+    fn devices(&self) -> Result<Vec<dbus::Path<'static>>, dbus::Error>;
+    fn state(&self) -> Result<u32, dbus::Error>;
+}
+
+impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>>
+    OrgFreedesktopNetworkManagerConnectionActive for blocking::Proxy<'a, C>
+{
+    fn id(&self) -> Result<String, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.NetworkManager.Connection.Active",
+            "Id",
+        )
+    }
+
+    fn type_(&self) -> Result<String, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.NetworkManager.Connection.Active",
+            "Type",
+        )
+    }
+
+    fn devices(&self) -> Result<Vec<dbus::Path<'static>>, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.NetworkManager.Connection.Active",
+            "Devices",
+        )
+    }
+
+    fn state(&self) -> Result<u32, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.NetworkManager.Connection.Active",
+            "State",
+        )
+    }
+}
+
+pub trait OrgFreedesktopNetworkManagerDeviceWireless {
+    fn active_access_point(&self) -> Result<dbus::Path<'static>, dbus::Error>;
+}
+
+impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>>
+    OrgFreedesktopNetworkManagerDeviceWireless for blocking::Proxy<'a, C>
+{
+    fn active_access_point(&self) -> Result<dbus::Path<'static>, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.NetworkManager.Device.Wireless",
+            "ActiveAccessPoint",
+        )
+    }
+}
+
+pub trait OrgFreedesktopNetworkManagerAccessPoint {
+    fn strength(&self) -> Result<u8, dbus::Error>;
+}
+
+impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>>
+    OrgFreedesktopNetworkManagerAccessPoint for blocking::Proxy<'a, C>
+{
+    fn strength(&self) -> Result<u8, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.NetworkManager.AccessPoint",
+            "Strength",
+        )
+    }
+}
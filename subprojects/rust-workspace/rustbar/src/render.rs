@@ -0,0 +1,558 @@
+use std::{
+    cell::RefCell,
+    io::{BufWriter, Write},
+    os::unix::net::UnixStream,
+    path::PathBuf,
+    rc::Rc,
+    sync::Arc,
+};
+
+use iced_tiny_skia::{
+    core::{
+        alignment::{Horizontal, Vertical},
+        font::Family,
+        text::{LineHeight, Shaping},
+        Color, Font, Rectangle, Size,
+    },
+    graphics::{backend::Text, Viewport},
+    Primitive,
+};
+use tiny_skia::{Mask, Pixmap};
+
+use rustbar::{
+    components::{Block, BlockKind, Handles, Layout, Segment},
+    BarSettings, SelectedBlock,
+};
+
+use crate::{
+    bar_primitives,
+    calc::{self, Evaluation},
+    ffi, layout_applauncher,
+    push_str::PushString,
+    tags::Tags,
+    znet_dwl::znet_tapesoftware_dwl_wm_monitor_v1::TagState,
+    BarBlocks, BarState, Commands, DesktopCommand, Target,
+};
+
+/// The monitor the fixture blocks are laid out for
+const OUTPUT: &str = "eDP-1";
+const USAGE: &str =
+    "usage: rustbar render [--mode bar|launcher|info] --width N [--scale S] --out bar.png";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Bar,
+    Launcher,
+    Info,
+}
+
+/// A block that shows the same thing every time, standing in for the
+/// real blocks which need dbus and sysfs
+struct FixtureBlock {
+    kind: BlockKind,
+    segments: &'static [(&'static str, bool)],
+    info: &'static [(&'static str, &'static str)],
+}
+
+impl Block for FixtureBlock {
+    fn kind(&self) -> BlockKind {
+        self.kind
+    }
+
+    fn fmt(&self, _output: &str, segments: &mut Vec<Segment>) {
+        for &(content, urgent) in self.segments {
+            segments.push(Segment {
+                shaping: Shaping::Advanced,
+                urgent,
+                ..Segment::new(content.to_owned())
+            });
+        }
+    }
+
+    fn fmt_table(&self, f: &mut BufWriter<UnixStream>) -> std::io::Result<()> {
+        self.segments
+            .iter()
+            .try_for_each(|(content, _)| writeln!(f, "{}", content.trim()))
+    }
+
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "segments": self
+                .segments
+                .iter()
+                .map(|(content, _)| content.trim())
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    fn info_size(&self, _segment: usize) -> Option<(u32, u32)> {
+        if self.info.is_empty() {
+            None
+        } else {
+            Some((256, 24 * self.info.len() as u32))
+        }
+    }
+
+    fn info(
+        &self,
+        _segment: usize,
+        backend: &iced_tiny_skia::Backend,
+        bar_settings: &BarSettings,
+        logical_size: Size<f32>,
+    ) -> Vec<Primitive> {
+        let text =
+            |content: &str, x: f32, y: f32, horizontal_alignment: Horizontal| Primitive::Text {
+                content: content.to_owned(),
+                bounds: Rectangle {
+                    x,
+                    y,
+                    width: logical_size.width,
+                    height: 24.0,
+                },
+                color: bar_settings.color_active.0,
+                size: backend.default_size(),
+                line_height: LineHeight::Relative(1.0),
+                font: bar_settings.default_font,
+                horizontal_alignment,
+                vertical_alignment: Vertical::Center,
+                shaping: Shaping::Advanced,
+            };
+        let mut primitives = Vec::new();
+        for (row, (left, right)) in self.info.iter().enumerate() {
+            let y = bar_settings.padding_y + 24.0 * (row as f32 + 0.5);
+            primitives.push(text(left, bar_settings.padding_x, y, Horizontal::Left));
+            primitives.push(text(
+                right,
+                logical_size.width - bar_settings.padding_x,
+                y,
+                Horizontal::Right,
+            ));
+        }
+        primitives
+    }
+
+    fn unregister(&self, _handles: &Handles) {}
+}
+
+fn fixture_block(kind: BlockKind) -> FixtureBlock {
+    let (segments, info): (&[_], &[_]) = match kind {
+        BlockKind::Playback => (&[(" 󰐊 Everything In Its Right Place ", false)], &[]),
+        BlockKind::WirePlumber => (&[(" 󰖀 45% ", false)], &[]),
+        BlockKind::Connman => (&[(" 󰅟 󰤨 home ", false)], &[]),
+        BlockKind::Bluetooth => (
+            &[(" 󰂱 WH-1000XM4 80% ", false)],
+            &[("󰂱 Bluetooth", "On"), ("󰋋 WH-1000XM4 80%", "Disconnect")],
+        ),
+        BlockKind::Battery => (
+            &[(" 󰂄 󰁹 96% 󰁞 12.0 minutes ", false), (" 󰁺 12% ", true)],
+            &[
+                ("󰁹 BAT0", "96%"),
+                ("Energy rate", "8.4 W"),
+                ("Capacity", "91%"),
+            ],
+        ),
+        BlockKind::Brightness => (&[(" 󰃟 80% ", false)], &[]),
+        BlockKind::Time => (&[(" 󰃶 Sat Oct 18 ", false), ("󱑊 12:34 ", false)], &[]),
+    };
+    FixtureBlock {
+        kind,
+        segments,
+        info,
+    }
+}
+
+/// Draws what the bar would show on a monitor, without a compositor
+pub struct Scene {
+    iced: iced_tiny_skia::Backend,
+    bar_settings: BarSettings,
+    ascii_font_width: f32,
+    layout: Layout,
+    blocks: Vec<Box<dyn Block>>,
+}
+
+impl Scene {
+    /// Uses the default font and settings, as if dconf were empty
+    pub fn new() -> Self {
+        let default_font = Font {
+            monospaced: true,
+            family: Family::Name("FiraCode Nerd Font"),
+            ..Default::default()
+        };
+
+        let iced = iced_tiny_skia::Backend::new(iced_tiny_skia::Settings {
+            default_text_size: 14.0,
+            default_font: Font {
+                monospaced: true,
+                family: Family::Name("Noto Sans"),
+                ..Default::default()
+            },
+        });
+
+        let divider = '';
+        let mut tmp = [0; 4];
+        let ascii_font_width = iced
+            .measure(
+                divider.encode_utf8(&mut tmp),
+                iced.default_size(),
+                LineHeight::Relative(1.0),
+                default_font,
+                Size::INFINITY,
+                Shaping::Basic,
+            )
+            .width;
+
+        let bar_settings = BarSettings::new(
+            String::from("Noto Sans"),
+            String::from("FiraCode Nerd Font 14"),
+            default_font,
+            std::ptr::null_mut(),
+            divider,
+        );
+
+        let layout = Layout::default();
+        let blocks = layout
+            .0
+            .iter()
+            .map(|&(kind, _)| Box::new(fixture_block(kind)) as Box<dyn Block>)
+            .collect();
+
+        Self {
+            iced,
+            bar_settings,
+            ascii_font_width,
+            layout,
+            blocks,
+        }
+    }
+
+    fn bar_blocks(&self, selected: SelectedBlock) -> BarBlocks {
+        BarBlocks {
+            blocks: &self.blocks,
+            layout: &self.layout,
+            selected,
+        }
+    }
+
+    fn colors(&self) -> (Color, (Color, Color)) {
+        (
+            self.bar_settings.color_active.0,
+            (
+                self.bar_settings.color_inactive.1,
+                self.bar_settings.color_active.1,
+            ),
+        )
+    }
+
+    /// The logical height of the bar, the same way `new_output` gets it
+    fn bar_height(&self, width: f32) -> f32 {
+        let (_, bar_size, _) = self.bar_blocks(SelectedBlock::None).fmt(
+            OUTPUT,
+            &mut Vec::new(),
+            self.colors(),
+            &self.iced,
+            self.bar_settings.default_font,
+            Size { width, height: 0.0 },
+            0.0,
+            self.bar_settings.padding_x,
+            self.bar_settings.padding_y,
+            self.bar_settings.divider,
+        );
+        bar_size.height + self.bar_settings.padding_y * 2.0
+    }
+
+    /// The bar of the focused monitor, with the first tag focused and the
+    /// second one holding a window
+    pub fn bar(&mut self, width: f32, scale: f32) -> Pixmap {
+        let height = self.bar_height(width);
+        let logical_size = Size { width, height };
+
+        let mut tags = Tags::new(
+            9,
+            self.bar_settings.padding_x,
+            height,
+            self.ascii_font_width,
+            &self.iced,
+            self.bar_settings.default_font,
+        );
+        tags.tags[0].state = TagState::Active;
+        tags.tags[0].num_clients = 1;
+        tags.tags[1].num_clients = 2;
+        tags.relayout_bg(
+            self.bar_settings.color_inactive,
+            self.bar_settings.color_active,
+            height,
+        );
+        tags.relayout_windows(
+            self.bar_settings.color_active.0,
+            self.bar_settings.color_inactive.0,
+            self.bar_settings.padding_x,
+        );
+
+        let (status_bar_primitives, _, window_title_bounds) =
+            self.bar_blocks(SelectedBlock::None).fmt(
+                OUTPUT,
+                &mut Vec::new(),
+                self.colors(),
+                &self.iced,
+                self.bar_settings.default_font,
+                logical_size,
+                tags.width + self.bar_settings.padding_x + (self.ascii_font_width * 3.0),
+                self.bar_settings.padding_x,
+                self.bar_settings.padding_y,
+                self.bar_settings.divider,
+            );
+
+        let primitives = bar_primitives(
+            &tags,
+            "[]=",
+            "rustbar — ~/dotfiles",
+            &window_title_bounds,
+            &Arc::new(status_bar_primitives),
+            true,
+            logical_size,
+            &self.iced,
+            &self.bar_settings,
+        );
+        let background = self.bar_settings.color_active.1;
+        self.rasterize(&primitives, logical_size, scale, background)
+    }
+
+    /// The app launcher with `input` typed in
+    pub fn launcher(&mut self, width: f32, scale: f32, input: &str) -> Pixmap {
+        let height = self.bar_height(width);
+        let apps = [
+            ("Firefox", "firefox"),
+            ("Foot", "foot"),
+            ("Files", "nautilus"),
+            ("GIMP", "gimp"),
+            ("Inkscape", "inkscape"),
+            (
+                "mpv Media Player",
+                "mpv --player-operation-mode=pseudo-gui --",
+            ),
+        ]
+        .into_iter()
+        .map(|(name, command)| DesktopCommand {
+            name: name.to_owned(),
+            target: Target::Run {
+                args: command.split(' ').map(str::to_owned).collect(),
+                terminal: false,
+            },
+            id: None,
+            frecency: 0.0,
+        })
+        .collect();
+        let mut current_input = PushString::new();
+        current_input.push_str(input);
+        let mut bar_state = BarState::AppLauncher {
+            apps: yoke::Yoke::attach_to_cart(apps, |cart| Commands(cart, Vec::new())),
+            layout: Vec::new(),
+            current_input: Rc::new(RefCell::new(current_input)),
+            default: String::new(),
+            selected: 0,
+            prompt: "run",
+        };
+
+        let mut matcher = nucleo_matcher::Matcher::new({
+            let mut config = nucleo_matcher::Config::DEFAULT;
+            config.prefer_prefix = true;
+            config
+        });
+        // Evaluated here, there's no event loop for the calculator
+        let giac = ffi::new_ctx();
+        let latest = Evaluation {
+            input: input.to_owned(),
+            result: calc::eval(giac.as_ref().unwrap(), input),
+        };
+        layout_applauncher(
+            &mut bar_state,
+            height,
+            &self.iced,
+            &self.bar_settings,
+            &mut matcher,
+            &[],
+            Some(&latest),
+        );
+
+        let BarState::AppLauncher { layout, .. } = bar_state else {
+            unreachable!()
+        };
+        let background = self.bar_settings.color_inactive.1;
+        self.rasterize(&layout, Size { width, height }, scale, background)
+    }
+
+    /// The popup of the first block that has one, `width` is ignored
+    /// since the block picks the size of its popup
+    pub fn info(&mut self, scale: f32) -> Pixmap {
+        let (block, (width, height)) = self
+            .blocks
+            .iter()
+            .find_map(|block| block.info_size(0).map(|size| (block, size)))
+            .unwrap();
+        // The same padding `open_info` adds
+        let logical_size = Size {
+            width: width as f32 + self.bar_settings.padding_x * 2.0,
+            height: height as f32 + self.bar_settings.padding_y * 2.0,
+        };
+        let primitives = block.info(0, &self.iced, &self.bar_settings, logical_size);
+        let background = self.bar_settings.color_active.1;
+        self.rasterize(&primitives, logical_size, scale, background)
+    }
+
+    pub fn render(&mut self, mode: Mode, width: f32, scale: f32) -> Pixmap {
+        match mode {
+            Mode::Bar => self.bar(width, scale),
+            Mode::Launcher => self.launcher(width, scale, "f"),
+            Mode::Info => self.info(scale),
+        }
+    }
+
+    fn rasterize(
+        &mut self,
+        primitives: &[Primitive],
+        logical_size: Size<f32>,
+        scale: f32,
+        background: Color,
+    ) -> Pixmap {
+        let viewport = Viewport::with_physical_size(
+            Size {
+                width: (logical_size.width * scale).round() as u32,
+                height: (logical_size.height * scale).round() as u32,
+            },
+            scale as f64,
+        );
+        let width = viewport.physical_width();
+        let height = viewport.physical_height();
+        let mut pixmap = Pixmap::new(width, height).unwrap();
+        let mut mask = Mask::new(width, height).unwrap();
+        self.iced.draw::<String>(
+            &mut pixmap.as_mut(),
+            &mut mask,
+            primitives,
+            &viewport,
+            &[Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: width as f32,
+                height: height as f32,
+            }],
+            background,
+            &[],
+        );
+        // iced_tiny_skia draws in BGRA for wl_shm, PNGs are RGBA
+        for pixel in pixmap.data_mut().chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        pixmap
+    }
+}
+
+/// `rustbar render`, `args` are the arguments after `render`
+pub fn main(mut args: impl Iterator<Item = String>) {
+    let mut mode = Mode::Bar;
+    let mut width = None;
+    let mut scale = 1.0;
+    let mut out = None;
+    while let Some(arg) = args.next() {
+        let Some(value) = args.next() else {
+            eprintln!("{} needs a value\n{}", arg, USAGE);
+            std::process::exit(1);
+        };
+        match arg.as_str() {
+            "--mode" => {
+                mode = match value.as_str() {
+                    "bar" => Mode::Bar,
+                    "launcher" => Mode::Launcher,
+                    "info" => Mode::Info,
+                    _ => {
+                        eprintln!("unknown mode {}\n{}", value, USAGE);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--width" => width = value.parse::<u32>().ok(),
+            "--scale" => scale = value.parse().unwrap_or(0.0),
+            "--out" => out = Some(PathBuf::from(value)),
+            _ => {
+                eprintln!("unknown argument {}\n{}", arg, USAGE);
+                std::process::exit(1);
+            }
+        }
+    }
+    let (Some(width), Some(out)) = (width, out) else {
+        eprintln!("{}", USAGE);
+        std::process::exit(1);
+    };
+    if width == 0 || scale.is_nan() || scale <= 0.0 {
+        eprintln!("--width and --scale have to be positive\n{}", USAGE);
+        std::process::exit(1);
+    }
+
+    let pixmap = Scene::new().render(mode, width as f32, scale);
+    if let Err(e) = pixmap.save_png(&out) {
+        eprintln!("couldn't write {}: {}", out.display(), e);
+        std::process::exit(1);
+    }
+}
+
+/// Compares against `golden/<name>`. Set `RUSTBAR_BLESS=1` to write the
+/// golden images again after a change to the layout that's on purpose.
+/// They depend on the fonts that are installed, so CI needs FiraCode
+/// Nerd Font and Noto Sans
+#[cfg(test)]
+fn assert_golden(name: &str, pixmap: &Pixmap) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("golden")
+        .join(name);
+    if std::env::var_os("RUSTBAR_BLESS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        pixmap.save_png(&path).unwrap();
+        return;
+    }
+    assert!(
+        path.exists(),
+        "{} has no golden image, run with RUSTBAR_BLESS=1 to write it",
+        name
+    );
+    let golden = Pixmap::load_png(&path).unwrap();
+    assert_eq!(
+        (golden.width(), golden.height()),
+        (pixmap.width(), pixmap.height()),
+        "{} changed size",
+        name
+    );
+    assert!(
+        golden.data() == pixmap.data(),
+        "{} doesn't match its golden image, run with RUSTBAR_BLESS=1 if that's expected",
+        name
+    );
+}
+
+#[test]
+fn test_bar() {
+    let pixmap = Scene::new().bar(1280.0, 1.0);
+    assert_eq!(pixmap.width(), 1280);
+    assert_golden("bar.png", &pixmap);
+}
+
+#[test]
+fn test_bar_scaled() {
+    let mut scene = Scene::new();
+    let height = scene.bar_height(1280.0);
+    let pixmap = scene.bar(1280.0, 1.5);
+    assert_eq!(pixmap.width(), 1920);
+    assert_eq!(pixmap.height(), (height * 1.5).round() as u32);
+    assert_golden("bar@1.5x.png", &pixmap);
+}
+
+#[test]
+fn test_launcher() {
+    let pixmap = Scene::new().launcher(1280.0, 1.0, "f");
+    assert_golden("launcher.png", &pixmap);
+}
+
+#[test]
+fn test_info() {
+    let pixmap = Scene::new().info(1.0);
+    assert_golden("info.png", &pixmap);
+}
@@ -295,6 +295,7 @@ pub trait OrgFreedesktopUPowerDevice {
         resolution: u32,
     ) -> Result<Vec<(u32, f64, u32)>, dbus::Error>;
     fn get_statistics(&self, type_: &str) -> Result<Vec<(f64, f64)>, dbus::Error>;
+    fn enable_charge_threshold(&self, charge_threshold: bool) -> Result<(), dbus::Error>;
     fn native_path(&self) -> Result<String, dbus::Error>;
     fn vendor(&self) -> Result<String, dbus::Error>;
     fn model(&self) -> Result<String, dbus::Error>;
@@ -325,6 +326,10 @@ pub trait OrgFreedesktopUPowerDevice {
     fn warning_level(&self) -> Result<u32, dbus::Error>;
     fn battery_level(&self) -> Result<u32, dbus::Error>;
     fn icon_name(&self) -> Result<String, dbus::Error>;
+    fn charge_start_threshold(&self) -> Result<u32, dbus::Error>;
+    fn charge_end_threshold(&self) -> Result<u32, dbus::Error>;
+    fn charge_threshold_enabled(&self) -> Result<bool, dbus::Error>;
+    fn charge_threshold_supported(&self) -> Result<bool, dbus::Error>;
 }
 
 impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>> OrgFreedesktopUPowerDevice
@@ -353,6 +358,14 @@ impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>> OrgFreed
             .and_then(|r: (Vec<(f64, f64)>,)| Ok(r.0))
     }
 
+    fn enable_charge_threshold(&self, charge_threshold: bool) -> Result<(), dbus::Error> {
+        self.method_call(
+            "org.freedesktop.UPower.Device",
+            "EnableChargeThreshold",
+            (charge_threshold,),
+        )
+    }
+
     fn native_path(&self) -> Result<String, dbus::Error> {
         <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
             &self,
@@ -592,4 +605,36 @@ impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>> OrgFreed
             "IconName",
         )
     }
+
+    fn charge_start_threshold(&self) -> Result<u32, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.UPower.Device",
+            "ChargeStartThreshold",
+        )
+    }
+
+    fn charge_end_threshold(&self) -> Result<u32, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.UPower.Device",
+            "ChargeEndThreshold",
+        )
+    }
+
+    fn charge_threshold_enabled(&self) -> Result<bool, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.UPower.Device",
+            "ChargeThresholdEnabled",
+        )
+    }
+
+    fn charge_threshold_supported(&self) -> Result<bool, dbus::Error> {
+        <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
+            &self,
+            "org.freedesktop.UPower.Device",
+            "ChargeThresholdSupported",
+        )
+    }
 }
@@ -121,6 +121,7 @@ impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>> OrgFreed
 
 pub trait OrgWireplumberDefaultNode {
     fn set_volume(&self, volume: f64) -> Result<bool, dbus::Error>;
+    fn set_mute(&self, mute: bool) -> Result<bool, dbus::Error>;
     fn volume(&self) -> Result<arg::PropMap, dbus::Error>;
 }
 
@@ -132,6 +133,11 @@ impl<'a, T: blocking::BlockingSender, C: ::std::ops::Deref<Target = T>> OrgWirep
             .and_then(|r: (bool,)| Ok(r.0))
     }
 
+    fn set_mute(&self, mute: bool) -> Result<bool, dbus::Error> {
+        self.method_call("org.wireplumber.DefaultNode", "SetMute", (mute,))
+            .and_then(|r: (bool,)| Ok(r.0))
+    }
+
     fn volume(&self) -> Result<arg::PropMap, dbus::Error> {
         <Self as blocking::stdintf::org_freedesktop_dbus::Properties>::get(
             &self,
@@ -1,5 +1,6 @@
 #include "wrapper.h"
 #include <fstream>
+#include <stdexcept>
 using namespace std;
 using namespace giac;
 
@@ -12,16 +13,29 @@ std::unique_ptr<giac::context> new_ctx() {
   return ctx;
 }
 
+// Errors are thrown, cxx hands them to rust as an Err with the message
 rust::String eval(const char *in, const giac::context *ctx) {
-  try {
-    gen e(in, ctx);
-    e = eval(e, 1, ctx);
-    stringstream s;
-    s << e;
+  // Nothing else clears them after an interrupt
+  ctrl_c = false;
+  interrupted = false;
+  first_error_line(0, ctx);
+  gen e(in, ctx);
+  if (first_error_line(ctx))
+    throw runtime_error("syntax error near " + error_token_name(ctx));
+  e = eval(e, 1, ctx);
+  // giac's errors are strings with a subtype of -1
+  if (e.type == _STRNG && e.subtype == -1)
+    throw runtime_error(*e._STRNGptr);
+  stringstream s;
+  s << e;
 
-    return s.str();
-  } catch (std::runtime_error e) {
-    return "undef";
-  }
+  return s.str();
+}
+
+// giac checks these flags while it evaluates, the eval that sees them
+// throws
+void interrupt() {
+  ctrl_c = true;
+  interrupted = true;
 }
 } // namespace wrapper
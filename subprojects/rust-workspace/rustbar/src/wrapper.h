@@ -7,4 +7,5 @@
 namespace wrapper {
 rust::String eval(const char *in, const giac::context *ctx);
 std::unique_ptr<giac::context> new_ctx();
+void interrupt();
 } // namespace wrapper
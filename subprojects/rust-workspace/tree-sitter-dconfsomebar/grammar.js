@@ -12,11 +12,20 @@ module.exports = grammar({
       $.date_fmt,
       $.browser_path,
       $.browser,
+      $.search_engine,
+      $.terminal,
+      $.clipboard_exclude,
       $.time_fmt,
       $.update_time_ntp,
       $.brightness_block,
       $.battery_block,
+      $.battery_low,
+      $.battery_critical,
+      $.battery_hook,
+      $.battery_action,
       $.connman_block,
+      $.network_throughput,
+      $.bluetooth_block,
       $.media_block,
       $.wireplumber_block,
       $.wireplumber_max_volume,
@@ -28,6 +37,7 @@ module.exports = grammar({
       $.time_servers,
       $.bar_show_time,
       $.divider,
+      $.blocks,
     ),
     font: _ => "/dotfiles/somebar/font", 
     font_fallback: _ => "/dotfiles/somebar/font-fallback", 
@@ -35,11 +45,20 @@ module.exports = grammar({
     date_fmt: _ => "/dotfiles/somebar/date-fmt", 
     browser_path: _ => "/dotfiles/somebar/browser-path", 
     browser: _ => "/dotfiles/somebar/browser", 
+    search_engine: _ => "/dotfiles/somebar/search-engine",
+    terminal: _ => "/dotfiles/somebar/terminal",
+    clipboard_exclude: _ => "/dotfiles/somebar/clipboard-exclude",
     time_fmt: _ => "/dotfiles/somebar/time-fmt", 
     update_time_ntp: _ => "/dotfiles/somebar/update-time-ntp", 
     brightness_block: _ => "/dotfiles/somebar/brightness-block", 
     battery_block: _ => "/dotfiles/somebar/battery-block", 
+    battery_low: _ => "/dotfiles/somebar/battery-low",
+    battery_critical: _ => "/dotfiles/somebar/battery-critical",
+    battery_hook: _ => "/dotfiles/somebar/battery-hook",
+    battery_action: _ => "/dotfiles/somebar/battery-action",
     connman_block: _ => "/dotfiles/somebar/connman-block", 
+    network_throughput: _ => "/dotfiles/somebar/network-throughput",
+    bluetooth_block: _ => "/dotfiles/somebar/bluetooth-block",
     media_block: _ => "/dotfiles/somebar/media-block", 
     wireplumber_block: _ => "/dotfiles/somebar/wireplumber-block",
     wireplumber_max_volume: _ => "/dotfiles/somebar/wireplumber-max-volume",
@@ -51,6 +70,7 @@ module.exports = grammar({
     time_servers: _ => "/dotfiles/somebar/time-servers", 
     bar_show_time: _ => "/dotfiles/somebar/bar-show-time", 
     divider: _ => "/dotfiles/somebar/divider",
+    blocks: _ => "/dotfiles/somebar/blocks",
   }
 });
 
@@ -7,9 +7,9 @@
 #define LANGUAGE_VERSION 14
 #define STATE_COUNT 4
 #define LARGE_STATE_COUNT 2
-#define SYMBOL_COUNT 24
+#define SYMBOL_COUNT 34
 #define ALIAS_COUNT 0
-#define TOKEN_COUNT 23
+#define TOKEN_COUNT 33
 #define EXTERNAL_TOKEN_COUNT 0
 #define FIELD_COUNT 0
 #define MAX_ALIAS_SEQUENCE_LENGTH 1
@@ -22,23 +22,33 @@ enum ts_symbol_identifiers {
   sym_date_fmt = 4,
   sym_browser_path = 5,
   sym_browser = 6,
-  sym_time_fmt = 7,
-  sym_update_time_ntp = 8,
-  sym_brightness_block = 9,
-  sym_battery_block = 10,
-  sym_connman_block = 11,
-  sym_media_block = 12,
-  sym_wireplumber_block = 13,
-  sym_wireplumber_max_volume = 14,
-  sym_color_active = 15,
-  sym_color_inactive = 16,
-  sym_padding_x = 17,
-  sym_padding_y = 18,
-  sym_top_bar = 19,
-  sym_time_servers = 20,
-  sym_bar_show_time = 21,
-  sym_divider = 22,
-  sym_source_file = 23,
+  sym_search_engine = 7,
+  sym_terminal = 8,
+  sym_clipboard_exclude = 9,
+  sym_time_fmt = 10,
+  sym_update_time_ntp = 11,
+  sym_brightness_block = 12,
+  sym_battery_block = 13,
+  sym_battery_low = 14,
+  sym_battery_critical = 15,
+  sym_battery_hook = 16,
+  sym_battery_action = 17,
+  sym_connman_block = 18,
+  sym_network_throughput = 19,
+  sym_bluetooth_block = 20,
+  sym_media_block = 21,
+  sym_wireplumber_block = 22,
+  sym_wireplumber_max_volume = 23,
+  sym_color_active = 24,
+  sym_color_inactive = 25,
+  sym_padding_x = 26,
+  sym_padding_y = 27,
+  sym_top_bar = 28,
+  sym_time_servers = 29,
+  sym_bar_show_time = 30,
+  sym_divider = 31,
+  sym_blocks = 32,
+  sym_source_file = 33,
 };
 
 static const char * const ts_symbol_names[] = {
@@ -49,11 +59,20 @@ static const char * const ts_symbol_names[] = {
   [sym_date_fmt] = "date_fmt",
   [sym_browser_path] = "browser_path",
   [sym_browser] = "browser",
+  [sym_search_engine] = "search_engine",
+  [sym_terminal] = "terminal",
+  [sym_clipboard_exclude] = "clipboard_exclude",
   [sym_time_fmt] = "time_fmt",
   [sym_update_time_ntp] = "update_time_ntp",
   [sym_brightness_block] = "brightness_block",
   [sym_battery_block] = "battery_block",
+  [sym_battery_low] = "battery_low",
+  [sym_battery_critical] = "battery_critical",
+  [sym_battery_hook] = "battery_hook",
+  [sym_battery_action] = "battery_action",
   [sym_connman_block] = "connman_block",
+  [sym_network_throughput] = "network_throughput",
+  [sym_bluetooth_block] = "bluetooth_block",
   [sym_media_block] = "media_block",
   [sym_wireplumber_block] = "wireplumber_block",
   [sym_wireplumber_max_volume] = "wireplumber_max_volume",
@@ -65,6 +84,7 @@ static const char * const ts_symbol_names[] = {
   [sym_time_servers] = "time_servers",
   [sym_bar_show_time] = "bar_show_time",
   [sym_divider] = "divider",
+  [sym_blocks] = "blocks",
   [sym_source_file] = "source_file",
 };
 
@@ -76,11 +96,20 @@ static const TSSymbol ts_symbol_map[] = {
   [sym_date_fmt] = sym_date_fmt,
   [sym_browser_path] = sym_browser_path,
   [sym_browser] = sym_browser,
+  [sym_search_engine] = sym_search_engine,
+  [sym_terminal] = sym_terminal,
+  [sym_clipboard_exclude] = sym_clipboard_exclude,
   [sym_time_fmt] = sym_time_fmt,
   [sym_update_time_ntp] = sym_update_time_ntp,
   [sym_brightness_block] = sym_brightness_block,
   [sym_battery_block] = sym_battery_block,
+  [sym_battery_low] = sym_battery_low,
+  [sym_battery_critical] = sym_battery_critical,
+  [sym_battery_hook] = sym_battery_hook,
+  [sym_battery_action] = sym_battery_action,
   [sym_connman_block] = sym_connman_block,
+  [sym_network_throughput] = sym_network_throughput,
+  [sym_bluetooth_block] = sym_bluetooth_block,
   [sym_media_block] = sym_media_block,
   [sym_wireplumber_block] = sym_wireplumber_block,
   [sym_wireplumber_max_volume] = sym_wireplumber_max_volume,
@@ -92,6 +121,7 @@ static const TSSymbol ts_symbol_map[] = {
   [sym_time_servers] = sym_time_servers,
   [sym_bar_show_time] = sym_bar_show_time,
   [sym_divider] = sym_divider,
+  [sym_blocks] = sym_blocks,
   [sym_source_file] = sym_source_file,
 };
 
@@ -124,6 +154,18 @@ static const TSSymbolMetadata ts_symbol_metadata[] = {
     .visible = true,
     .named = true,
   },
+  [sym_search_engine] = {
+    .visible = true,
+    .named = true,
+  },
+  [sym_terminal] = {
+    .visible = true,
+    .named = true,
+  },
+  [sym_clipboard_exclude] = {
+    .visible = true,
+    .named = true,
+  },
   [sym_time_fmt] = {
     .visible = true,
     .named = true,
@@ -140,10 +182,34 @@ static const TSSymbolMetadata ts_symbol_metadata[] = {
     .visible = true,
     .named = true,
   },
+  [sym_battery_low] = {
+    .visible = true,
+    .named = true,
+  },
+  [sym_battery_critical] = {
+    .visible = true,
+    .named = true,
+  },
+  [sym_battery_hook] = {
+    .visible = true,
+    .named = true,
+  },
+  [sym_battery_action] = {
+    .visible = true,
+    .named = true,
+  },
   [sym_connman_block] = {
     .visible = true,
     .named = true,
   },
+  [sym_network_throughput] = {
+    .visible = true,
+    .named = true,
+  },
+  [sym_bluetooth_block] = {
+    .visible = true,
+    .named = true,
+  },
   [sym_media_block] = {
     .visible = true,
     .named = true,
@@ -188,6 +254,10 @@ static const TSSymbolMetadata ts_symbol_metadata[] = {
     .visible = true,
     .named = true,
   },
+  [sym_blocks] = {
+    .visible = true,
+    .named = true,
+  },
   [sym_source_file] = {
     .visible = true,
     .named = true,
@@ -214,675 +284,964 @@ static bool ts_lex(TSLexer *lexer, TSStateId state) {
   eof = lexer->eof(lexer);
   switch (state) {
     case 0:
-      if (eof) ADVANCE(193);
-      if (lookahead == '/') ADVANCE(52);
+      if (eof) ADVANCE(276);
+      if (lookahead == '/') ADVANCE(1);
       END_STATE();
     case 1:
-      if (lookahead == '-') ADVANCE(34);
+      if (lookahead == 'd') ADVANCE(2);
       END_STATE();
     case 2:
-      if (lookahead == '-') ADVANCE(190);
+      if (lookahead == 'o') ADVANCE(3);
       END_STATE();
     case 3:
-      if (lookahead == '-') ADVANCE(42);
+      if (lookahead == 't') ADVANCE(4);
       END_STATE();
     case 4:
-      if (lookahead == '-') ADVANCE(78);
+      if (lookahead == 'f') ADVANCE(5);
       END_STATE();
     case 5:
-      if (lookahead == '-') ADVANCE(38);
+      if (lookahead == 'i') ADVANCE(6);
       END_STATE();
     case 6:
-      if (lookahead == '-') ADVANCE(20);
+      if (lookahead == 'l') ADVANCE(7);
       END_STATE();
     case 7:
-      if (lookahead == '-') ADVANCE(163);
+      if (lookahead == 'e') ADVANCE(8);
       END_STATE();
     case 8:
-      if (lookahead == '-') ADVANCE(185);
+      if (lookahead == 's') ADVANCE(9);
       END_STATE();
     case 9:
-      if (lookahead == '-') ADVANCE(175);
+      if (lookahead == '/') ADVANCE(10);
       END_STATE();
     case 10:
-      if (lookahead == '-') ADVANCE(128);
+      if (lookahead == 's') ADVANCE(11);
       END_STATE();
     case 11:
-      if (lookahead == '-') ADVANCE(37);
+      if (lookahead == 'o') ADVANCE(12);
       END_STATE();
     case 12:
-      if (lookahead == '-') ADVANCE(39);
+      if (lookahead == 'm') ADVANCE(13);
       END_STATE();
     case 13:
-      if (lookahead == '-') ADVANCE(179);
+      if (lookahead == 'e') ADVANCE(14);
       END_STATE();
     case 14:
-      if (lookahead == '-') ADVANCE(40);
+      if (lookahead == 'b') ADVANCE(15);
       END_STATE();
     case 15:
-      if (lookahead == '-') ADVANCE(41);
+      if (lookahead == 'a') ADVANCE(16);
       END_STATE();
     case 16:
-      if (lookahead == '/') ADVANCE(33);
+      if (lookahead == 'r') ADVANCE(17);
       END_STATE();
     case 17:
-      if (lookahead == '/') ADVANCE(162);
+      if (lookahead == '/') ADVANCE(18);
       END_STATE();
     case 18:
-      if (lookahead == 'a') ADVANCE(154);
+      if (lookahead == 'b') ADVANCE(19);
+      if (lookahead == 'c') ADVANCE(96);
+      if (lookahead == 'd') ADVANCE(139);
+      if (lookahead == 'f') ADVANCE(151);
+      if (lookahead == 'm') ADVANCE(162);
+      if (lookahead == 'n') ADVANCE(172);
+      if (lookahead == 'p') ADVANCE(189);
+      if (lookahead == 's') ADVANCE(197);
+      if (lookahead == 't') ADVANCE(209);
+      if (lookahead == 'u') ADVANCE(237);
+      if (lookahead == 'w') ADVANCE(251);
       END_STATE();
     case 19:
-      if (lookahead == 'a') ADVANCE(149);
-      if (lookahead == 'r') ADVANCE(86);
+      if (lookahead == 'a') ADVANCE(20);
+      if (lookahead == 'l') ADVANCE(58);
+      if (lookahead == 'r') ADVANCE(74);
       END_STATE();
     case 20:
-      if (lookahead == 'a') ADVANCE(50);
-      if (lookahead == 'i') ADVANCE(127);
+      if (lookahead == 'r') ADVANCE(21);
+      if (lookahead == 't') ADVANCE(31);
       END_STATE();
     case 21:
-      if (lookahead == 'a') ADVANCE(191);
+      if (lookahead == '-') ADVANCE(22);
       END_STATE();
     case 22:
-      if (lookahead == 'a') ADVANCE(172);
-      if (lookahead == 'i') ADVANCE(183);
+      if (lookahead == 's') ADVANCE(23);
       END_STATE();
     case 23:
-      if (lookahead == 'a') ADVANCE(57);
+      if (lookahead == 'h') ADVANCE(24);
       END_STATE();
     case 24:
-      if (lookahead == 'a') ADVANCE(107);
+      if (lookahead == 'o') ADVANCE(25);
       END_STATE();
     case 25:
-      if (lookahead == 'a') ADVANCE(130);
+      if (lookahead == 'w') ADVANCE(26);
       END_STATE();
     case 26:
-      if (lookahead == 'a') ADVANCE(5);
+      if (lookahead == '-') ADVANCE(27);
       END_STATE();
     case 27:
-      if (lookahead == 'a') ADVANCE(47);
+      if (lookahead == 't') ADVANCE(28);
       END_STATE();
     case 28:
-      if (lookahead == 'a') ADVANCE(153);
+      if (lookahead == 'i') ADVANCE(29);
       END_STATE();
     case 29:
-      if (lookahead == 'a') ADVANCE(171);
+      if (lookahead == 'm') ADVANCE(30);
       END_STATE();
     case 30:
-      if (lookahead == 'a') ADVANCE(178);
+      if (lookahead == 'e') ADVANCE(306);
       END_STATE();
     case 31:
-      if (lookahead == 'a') ADVANCE(51);
+      if (lookahead == 't') ADVANCE(32);
       END_STATE();
     case 32:
-      if (lookahead == 'b') ADVANCE(18);
+      if (lookahead == 'e') ADVANCE(33);
       END_STATE();
     case 33:
-      if (lookahead == 'b') ADVANCE(19);
-      if (lookahead == 'c') ADVANCE(133);
-      if (lookahead == 'd') ADVANCE(22);
-      if (lookahead == 'f') ADVANCE(134);
-      if (lookahead == 'm') ADVANCE(64);
-      if (lookahead == 'p') ADVANCE(23);
-      if (lookahead == 't') ADVANCE(91);
-      if (lookahead == 'u') ADVANCE(147);
-      if (lookahead == 'w') ADVANCE(87);
+      if (lookahead == 'r') ADVANCE(34);
       END_STATE();
     case 34:
-      if (lookahead == 'b') ADVANCE(108);
-      if (lookahead == 'f') ADVANCE(120);
-      if (lookahead == 's') ADVANCE(73);
+      if (lookahead == 'y') ADVANCE(35);
       END_STATE();
     case 35:
-      if (lookahead == 'b') ADVANCE(27);
+      if (lookahead == '-') ADVANCE(36);
       END_STATE();
     case 36:
-      if (lookahead == 'b') ADVANCE(76);
+      if (lookahead == 'a') ADVANCE(37);
+      if (lookahead == 'b') ADVANCE(42);
+      if (lookahead == 'c') ADVANCE(46);
+      if (lookahead == 'h') ADVANCE(53);
+      if (lookahead == 'l') ADVANCE(56);
       END_STATE();
     case 37:
-      if (lookahead == 'b') ADVANCE(28);
+      if (lookahead == 'c') ADVANCE(38);
       END_STATE();
     case 38:
-      if (lookahead == 'b') ADVANCE(109);
+      if (lookahead == 't') ADVANCE(39);
       END_STATE();
     case 39:
-      if (lookahead == 'b') ADVANCE(110);
+      if (lookahead == 'i') ADVANCE(40);
       END_STATE();
     case 40:
-      if (lookahead == 'b') ADVANCE(111);
+      if (lookahead == 'o') ADVANCE(41);
       END_STATE();
     case 41:
-      if (lookahead == 'b') ADVANCE(112);
+      if (lookahead == 'n') ADVANCE(293);
       END_STATE();
     case 42:
-      if (lookahead == 'b') ADVANCE(113);
-      if (lookahead == 'm') ADVANCE(21);
+      if (lookahead == 'l') ADVANCE(43);
       END_STATE();
     case 43:
-      if (lookahead == 'c') ADVANCE(96);
+      if (lookahead == 'o') ADVANCE(44);
       END_STATE();
     case 44:
-      if (lookahead == 'c') ADVANCE(97);
+      if (lookahead == 'c') ADVANCE(45);
       END_STATE();
     case 45:
-      if (lookahead == 'c') ADVANCE(98);
+      if (lookahead == 'k') ADVANCE(289);
       END_STATE();
     case 46:
-      if (lookahead == 'c') ADVANCE(99);
+      if (lookahead == 'r') ADVANCE(47);
       END_STATE();
     case 47:
-      if (lookahead == 'c') ADVANCE(100);
+      if (lookahead == 'i') ADVANCE(48);
       END_STATE();
     case 48:
-      if (lookahead == 'c') ADVANCE(101);
+      if (lookahead == 't') ADVANCE(49);
       END_STATE();
     case 49:
-      if (lookahead == 'c') ADVANCE(102);
+      if (lookahead == 'i') ADVANCE(50);
       END_STATE();
     case 50:
-      if (lookahead == 'c') ADVANCE(176);
+      if (lookahead == 'c') ADVANCE(51);
       END_STATE();
     case 51:
-      if (lookahead == 'c') ADVANCE(180);
+      if (lookahead == 'a') ADVANCE(52);
       END_STATE();
     case 52:
-      if (lookahead == 'd') ADVANCE(131);
+      if (lookahead == 'l') ADVANCE(291);
       END_STATE();
     case 53:
-      if (lookahead == 'd') ADVANCE(30);
+      if (lookahead == 'o') ADVANCE(54);
       END_STATE();
     case 54:
-      if (lookahead == 'd') ADVANCE(89);
+      if (lookahead == 'o') ADVANCE(55);
       END_STATE();
     case 55:
-      if (lookahead == 'd') ADVANCE(88);
+      if (lookahead == 'k') ADVANCE(292);
       END_STATE();
     case 56:
-      if (lookahead == 'd') ADVANCE(71);
+      if (lookahead == 'o') ADVANCE(57);
       END_STATE();
     case 57:
-      if (lookahead == 'd') ADVANCE(55);
+      if (lookahead == 'w') ADVANCE(290);
       END_STATE();
     case 58:
-      if (lookahead == 'e') ADVANCE(160);
+      if (lookahead == 'o') ADVANCE(59);
+      if (lookahead == 'u') ADVANCE(62);
       END_STATE();
     case 59:
-      if (lookahead == 'e') ADVANCE(32);
+      if (lookahead == 'c') ADVANCE(60);
       END_STATE();
     case 60:
-      if (lookahead == 'e') ADVANCE(208);
+      if (lookahead == 'k') ADVANCE(61);
       END_STATE();
     case 61:
-      if (lookahead == 'e') ADVANCE(214);
+      if (lookahead == 's') ADVANCE(308);
       END_STATE();
     case 62:
-      if (lookahead == 'e') ADVANCE(209);
+      if (lookahead == 'e') ADVANCE(63);
       END_STATE();
     case 63:
-      if (lookahead == 'e') ADVANCE(207);
+      if (lookahead == 't') ADVANCE(64);
       END_STATE();
     case 64:
-      if (lookahead == 'e') ADVANCE(54);
+      if (lookahead == 'o') ADVANCE(65);
       END_STATE();
     case 65:
-      if (lookahead == 'e') ADVANCE(145);
+      if (lookahead == 'o') ADVANCE(66);
       END_STATE();
     case 66:
-      if (lookahead == 'e') ADVANCE(4);
+      if (lookahead == 't') ADVANCE(67);
       END_STATE();
     case 67:
-      if (lookahead == 'e') ADVANCE(150);
+      if (lookahead == 'h') ADVANCE(68);
       END_STATE();
     case 68:
-      if (lookahead == 'e') ADVANCE(1);
+      if (lookahead == '-') ADVANCE(69);
       END_STATE();
     case 69:
-      if (lookahead == 'e') ADVANCE(164);
+      if (lookahead == 'b') ADVANCE(70);
       END_STATE();
     case 70:
-      if (lookahead == 'e') ADVANCE(151);
+      if (lookahead == 'l') ADVANCE(71);
       END_STATE();
     case 71:
-      if (lookahead == 'e') ADVANCE(152);
+      if (lookahead == 'o') ADVANCE(72);
       END_STATE();
     case 72:
-      if (lookahead == 'e') ADVANCE(9);
+      if (lookahead == 'c') ADVANCE(73);
       END_STATE();
     case 73:
-      if (lookahead == 'e') ADVANCE(155);
+      if (lookahead == 'k') ADVANCE(296);
       END_STATE();
     case 74:
-      if (lookahead == 'e') ADVANCE(158);
+      if (lookahead == 'i') ADVANCE(75);
+      if (lookahead == 'o') ADVANCE(88);
       END_STATE();
     case 75:
-      if (lookahead == 'e') ADVANCE(10);
+      if (lookahead == 'g') ADVANCE(76);
       END_STATE();
     case 76:
-      if (lookahead == 'e') ADVANCE(159);
+      if (lookahead == 'h') ADVANCE(77);
       END_STATE();
     case 77:
-      if (lookahead == 'f') ADVANCE(85);
+      if (lookahead == 't') ADVANCE(78);
       END_STATE();
     case 78:
-      if (lookahead == 'f') ADVANCE(117);
+      if (lookahead == 'n') ADVANCE(79);
       END_STATE();
     case 79:
-      if (lookahead == 'f') ADVANCE(24);
+      if (lookahead == 'e') ADVANCE(80);
       END_STATE();
     case 80:
-      if (lookahead == 'g') ADVANCE(83);
+      if (lookahead == 's') ADVANCE(81);
       END_STATE();
     case 81:
-      if (lookahead == 'g') ADVANCE(2);
+      if (lookahead == 's') ADVANCE(82);
       END_STATE();
     case 82:
-      if (lookahead == 'h') ADVANCE(198);
+      if (lookahead == '-') ADVANCE(83);
       END_STATE();
     case 83:
-      if (lookahead == 'h') ADVANCE(173);
+      if (lookahead == 'b') ADVANCE(84);
       END_STATE();
     case 84:
-      if (lookahead == 'h') ADVANCE(135);
+      if (lookahead == 'l') ADVANCE(85);
       END_STATE();
     case 85:
-      if (lookahead == 'i') ADVANCE(103);
+      if (lookahead == 'o') ADVANCE(86);
       END_STATE();
     case 86:
-      if (lookahead == 'i') ADVANCE(80);
-      if (lookahead == 'o') ADVANCE(188);
+      if (lookahead == 'c') ADVANCE(87);
       END_STATE();
     case 87:
-      if (lookahead == 'i') ADVANCE(157);
+      if (lookahead == 'k') ADVANCE(288);
       END_STATE();
     case 88:
-      if (lookahead == 'i') ADVANCE(125);
+      if (lookahead == 'w') ADVANCE(89);
       END_STATE();
     case 89:
-      if (lookahead == 'i') ADVANCE(26);
+      if (lookahead == 's') ADVANCE(90);
       END_STATE();
     case 90:
-      if (lookahead == 'i') ADVANCE(56);
+      if (lookahead == 'e') ADVANCE(91);
       END_STATE();
     case 91:
-      if (lookahead == 'i') ADVANCE(118);
-      if (lookahead == 'o') ADVANCE(146);
+      if (lookahead == 'r') ADVANCE(282);
       END_STATE();
     case 92:
-      if (lookahead == 'i') ADVANCE(186);
+      if (lookahead == 'p') ADVANCE(93);
       END_STATE();
     case 93:
-      if (lookahead == 'i') ADVANCE(121);
+      if (lookahead == 'a') ADVANCE(94);
       END_STATE();
     case 94:
-      if (lookahead == 'i') ADVANCE(187);
+      if (lookahead == 't') ADVANCE(95);
       END_STATE();
     case 95:
-      if (lookahead == 'i') ADVANCE(122);
+      if (lookahead == 'h') ADVANCE(281);
       END_STATE();
     case 96:
-      if (lookahead == 'k') ADVANCE(196);
+      if (lookahead == 'l') ADVANCE(97);
+      if (lookahead == 'o') ADVANCE(112);
       END_STATE();
     case 97:
-      if (lookahead == 'k') ADVANCE(205);
+      if (lookahead == 'i') ADVANCE(98);
       END_STATE();
     case 98:
-      if (lookahead == 'k') ADVANCE(203);
+      if (lookahead == 'p') ADVANCE(99);
       END_STATE();
     case 99:
-      if (lookahead == 'k') ADVANCE(204);
+      if (lookahead == 'b') ADVANCE(100);
       END_STATE();
     case 100:
-      if (lookahead == 'k') ADVANCE(195);
+      if (lookahead == 'o') ADVANCE(101);
       END_STATE();
     case 101:
-      if (lookahead == 'k') ADVANCE(202);
+      if (lookahead == 'a') ADVANCE(102);
       END_STATE();
     case 102:
-      if (lookahead == 'k') ADVANCE(206);
+      if (lookahead == 'r') ADVANCE(103);
       END_STATE();
     case 103:
-      if (lookahead == 'l') ADVANCE(58);
+      if (lookahead == 'd') ADVANCE(104);
       END_STATE();
     case 104:
-      if (lookahead == 'l') ADVANCE(181);
+      if (lookahead == '-') ADVANCE(105);
       END_STATE();
     case 105:
-      if (lookahead == 'l') ADVANCE(182);
+      if (lookahead == 'e') ADVANCE(106);
       END_STATE();
     case 106:
-      if (lookahead == 'l') ADVANCE(137);
-      if (lookahead == 'n') ADVANCE(124);
+      if (lookahead == 'x') ADVANCE(107);
       END_STATE();
     case 107:
-      if (lookahead == 'l') ADVANCE(114);
+      if (lookahead == 'c') ADVANCE(108);
       END_STATE();
     case 108:
-      if (lookahead == 'l') ADVANCE(136);
+      if (lookahead == 'l') ADVANCE(109);
       END_STATE();
     case 109:
-      if (lookahead == 'l') ADVANCE(138);
+      if (lookahead == 'u') ADVANCE(110);
       END_STATE();
     case 110:
-      if (lookahead == 'l') ADVANCE(139);
+      if (lookahead == 'd') ADVANCE(111);
       END_STATE();
     case 111:
-      if (lookahead == 'l') ADVANCE(140);
+      if (lookahead == 'e') ADVANCE(285);
       END_STATE();
     case 112:
-      if (lookahead == 'l') ADVANCE(142);
+      if (lookahead == 'l') ADVANCE(113);
+      if (lookahead == 'n') ADVANCE(129);
       END_STATE();
     case 113:
-      if (lookahead == 'l') ADVANCE(143);
+      if (lookahead == 'o') ADVANCE(114);
       END_STATE();
     case 114:
-      if (lookahead == 'l') ADVANCE(35);
+      if (lookahead == 'r') ADVANCE(115);
       END_STATE();
     case 115:
-      if (lookahead == 'm') ADVANCE(59);
+      if (lookahead == '-') ADVANCE(116);
       END_STATE();
     case 116:
-      if (lookahead == 'm') ADVANCE(36);
+      if (lookahead == 'a') ADVANCE(117);
+      if (lookahead == 'i') ADVANCE(122);
       END_STATE();
     case 117:
-      if (lookahead == 'm') ADVANCE(169);
+      if (lookahead == 'c') ADVANCE(118);
       END_STATE();
     case 118:
-      if (lookahead == 'm') ADVANCE(68);
+      if (lookahead == 't') ADVANCE(119);
       END_STATE();
     case 119:
-      if (lookahead == 'm') ADVANCE(25);
+      if (lookahead == 'i') ADVANCE(120);
       END_STATE();
     case 120:
-      if (lookahead == 'm') ADVANCE(170);
+      if (lookahead == 'v') ADVANCE(121);
       END_STATE();
     case 121:
-      if (lookahead == 'm') ADVANCE(75);
+      if (lookahead == 'e') ADVANCE(300);
       END_STATE();
     case 122:
-      if (lookahead == 'm') ADVANCE(61);
+      if (lookahead == 'n') ADVANCE(123);
       END_STATE();
     case 123:
-      if (lookahead == 'm') ADVANCE(63);
+      if (lookahead == 'a') ADVANCE(124);
       END_STATE();
     case 124:
-      if (lookahead == 'n') ADVANCE(119);
+      if (lookahead == 'c') ADVANCE(125);
       END_STATE();
     case 125:
-      if (lookahead == 'n') ADVANCE(81);
+      if (lookahead == 't') ADVANCE(126);
       END_STATE();
     case 126:
-      if (lookahead == 'n') ADVANCE(168);
+      if (lookahead == 'i') ADVANCE(127);
       END_STATE();
     case 127:
-      if (lookahead == 'n') ADVANCE(31);
+      if (lookahead == 'v') ADVANCE(128);
       END_STATE();
     case 128:
-      if (lookahead == 'n') ADVANCE(174);
+      if (lookahead == 'e') ADVANCE(301);
       END_STATE();
     case 129:
-      if (lookahead == 'n') ADVANCE(69);
+      if (lookahead == 'n') ADVANCE(130);
       END_STATE();
     case 130:
-      if (lookahead == 'n') ADVANCE(14);
+      if (lookahead == 'm') ADVANCE(131);
       END_STATE();
     case 131:
-      if (lookahead == 'o') ADVANCE(167);
+      if (lookahead == 'a') ADVANCE(132);
       END_STATE();
     case 132:
-      if (lookahead == 'o') ADVANCE(115);
+      if (lookahead == 'n') ADVANCE(133);
       END_STATE();
     case 133:
-      if (lookahead == 'o') ADVANCE(106);
+      if (lookahead == '-') ADVANCE(134);
       END_STATE();
     case 134:
-      if (lookahead == 'o') ADVANCE(126);
+      if (lookahead == 'b') ADVANCE(135);
       END_STATE();
     case 135:
-      if (lookahead == 'o') ADVANCE(189);
+      if (lookahead == 'l') ADVANCE(136);
       END_STATE();
     case 136:
-      if (lookahead == 'o') ADVANCE(43);
+      if (lookahead == 'o') ADVANCE(137);
       END_STATE();
     case 137:
-      if (lookahead == 'o') ADVANCE(156);
+      if (lookahead == 'c') ADVANCE(138);
       END_STATE();
     case 138:
-      if (lookahead == 'o') ADVANCE(44);
+      if (lookahead == 'k') ADVANCE(294);
       END_STATE();
     case 139:
-      if (lookahead == 'o') ADVANCE(45);
+      if (lookahead == 'a') ADVANCE(140);
+      if (lookahead == 'i') ADVANCE(146);
       END_STATE();
     case 140:
-      if (lookahead == 'o') ADVANCE(46);
+      if (lookahead == 't') ADVANCE(141);
       END_STATE();
     case 141:
-      if (lookahead == 'o') ADVANCE(105);
+      if (lookahead == 'e') ADVANCE(142);
       END_STATE();
     case 142:
-      if (lookahead == 'o') ADVANCE(48);
+      if (lookahead == '-') ADVANCE(143);
       END_STATE();
     case 143:
-      if (lookahead == 'o') ADVANCE(49);
+      if (lookahead == 'f') ADVANCE(144);
       END_STATE();
     case 144:
-      if (lookahead == 'p') ADVANCE(201);
+      if (lookahead == 'm') ADVANCE(145);
       END_STATE();
     case 145:
-      if (lookahead == 'p') ADVANCE(104);
+      if (lookahead == 't') ADVANCE(280);
       END_STATE();
     case 146:
-      if (lookahead == 'p') ADVANCE(11);
+      if (lookahead == 'v') ADVANCE(147);
       END_STATE();
     case 147:
-      if (lookahead == 'p') ADVANCE(53);
+      if (lookahead == 'i') ADVANCE(148);
       END_STATE();
     case 148:
-      if (lookahead == 'p') ADVANCE(29);
+      if (lookahead == 'd') ADVANCE(149);
       END_STATE();
     case 149:
-      if (lookahead == 'r') ADVANCE(7);
-      if (lookahead == 't') ADVANCE(177);
+      if (lookahead == 'e') ADVANCE(150);
       END_STATE();
     case 150:
-      if (lookahead == 'r') ADVANCE(192);
+      if (lookahead == 'r') ADVANCE(307);
       END_STATE();
     case 151:
-      if (lookahead == 'r') ADVANCE(199);
+      if (lookahead == 'o') ADVANCE(152);
       END_STATE();
     case 152:
-      if (lookahead == 'r') ADVANCE(215);
+      if (lookahead == 'n') ADVANCE(153);
       END_STATE();
     case 153:
-      if (lookahead == 'r') ADVANCE(212);
+      if (lookahead == 't') ADVANCE(277);
       END_STATE();
     case 154:
-      if (lookahead == 'r') ADVANCE(16);
+      if (lookahead == 'f') ADVANCE(155);
       END_STATE();
     case 155:
-      if (lookahead == 'r') ADVANCE(184);
+      if (lookahead == 'a') ADVANCE(156);
       END_STATE();
     case 156:
-      if (lookahead == 'r') ADVANCE(6);
+      if (lookahead == 'l') ADVANCE(157);
       END_STATE();
     case 157:
-      if (lookahead == 'r') ADVANCE(65);
+      if (lookahead == 'l') ADVANCE(158);
       END_STATE();
     case 158:
-      if (lookahead == 'r') ADVANCE(161);
+      if (lookahead == 'b') ADVANCE(159);
       END_STATE();
     case 159:
-      if (lookahead == 'r') ADVANCE(3);
+      if (lookahead == 'a') ADVANCE(160);
       END_STATE();
     case 160:
-      if (lookahead == 's') ADVANCE(17);
+      if (lookahead == 'c') ADVANCE(161);
       END_STATE();
     case 161:
-      if (lookahead == 's') ADVANCE(213);
+      if (lookahead == 'k') ADVANCE(278);
       END_STATE();
     case 162:
-      if (lookahead == 's') ADVANCE(132);
+      if (lookahead == 'e') ADVANCE(163);
       END_STATE();
     case 163:
-      if (lookahead == 's') ADVANCE(84);
+      if (lookahead == 'd') ADVANCE(164);
       END_STATE();
     case 164:
-      if (lookahead == 's') ADVANCE(166);
+      if (lookahead == 'i') ADVANCE(165);
       END_STATE();
     case 165:
-      if (lookahead == 's') ADVANCE(70);
+      if (lookahead == 'a') ADVANCE(166);
       END_STATE();
     case 166:
-      if (lookahead == 's') ADVANCE(15);
+      if (lookahead == '-') ADVANCE(167);
       END_STATE();
     case 167:
-      if (lookahead == 't') ADVANCE(77);
+      if (lookahead == 'b') ADVANCE(168);
       END_STATE();
     case 168:
-      if (lookahead == 't') ADVANCE(194);
+      if (lookahead == 'l') ADVANCE(169);
       END_STATE();
     case 169:
-      if (lookahead == 't') ADVANCE(197);
+      if (lookahead == 'o') ADVANCE(170);
       END_STATE();
     case 170:
-      if (lookahead == 't') ADVANCE(200);
+      if (lookahead == 'c') ADVANCE(171);
       END_STATE();
     case 171:
-      if (lookahead == 't') ADVANCE(82);
+      if (lookahead == 'k') ADVANCE(297);
       END_STATE();
     case 172:
-      if (lookahead == 't') ADVANCE(66);
+      if (lookahead == 'e') ADVANCE(173);
       END_STATE();
     case 173:
-      if (lookahead == 't') ADVANCE(129);
+      if (lookahead == 't') ADVANCE(174);
       END_STATE();
     case 174:
-      if (lookahead == 't') ADVANCE(144);
+      if (lookahead == 'w') ADVANCE(175);
       END_STATE();
     case 175:
-      if (lookahead == 't') ADVANCE(93);
+      if (lookahead == 'o') ADVANCE(176);
       END_STATE();
     case 176:
-      if (lookahead == 't') ADVANCE(92);
+      if (lookahead == 'r') ADVANCE(177);
       END_STATE();
     case 177:
-      if (lookahead == 't') ADVANCE(67);
+      if (lookahead == 'k') ADVANCE(178);
       END_STATE();
     case 178:
-      if (lookahead == 't') ADVANCE(72);
+      if (lookahead == '-') ADVANCE(179);
       END_STATE();
     case 179:
-      if (lookahead == 't') ADVANCE(95);
+      if (lookahead == 't') ADVANCE(180);
       END_STATE();
     case 180:
-      if (lookahead == 't') ADVANCE(94);
+      if (lookahead == 'h') ADVANCE(181);
       END_STATE();
     case 181:
-      if (lookahead == 'u') ADVANCE(116);
+      if (lookahead == 'r') ADVANCE(182);
       END_STATE();
     case 182:
-      if (lookahead == 'u') ADVANCE(123);
+      if (lookahead == 'o') ADVANCE(183);
       END_STATE();
     case 183:
-      if (lookahead == 'v') ADVANCE(90);
+      if (lookahead == 'u') ADVANCE(184);
       END_STATE();
     case 184:
-      if (lookahead == 'v') ADVANCE(74);
+      if (lookahead == 'g') ADVANCE(185);
       END_STATE();
     case 185:
-      if (lookahead == 'v') ADVANCE(141);
+      if (lookahead == 'h') ADVANCE(186);
       END_STATE();
     case 186:
-      if (lookahead == 'v') ADVANCE(60);
+      if (lookahead == 'p') ADVANCE(187);
       END_STATE();
     case 187:
-      if (lookahead == 'v') ADVANCE(62);
+      if (lookahead == 'u') ADVANCE(188);
       END_STATE();
     case 188:
-      if (lookahead == 'w') ADVANCE(165);
+      if (lookahead == 't') ADVANCE(295);
       END_STATE();
     case 189:
-      if (lookahead == 'w') ADVANCE(13);
+      if (lookahead == 'a') ADVANCE(190);
       END_STATE();
     case 190:
-      if (lookahead == 'x') ADVANCE(210);
-      if (lookahead == 'y') ADVANCE(211);
+      if (lookahead == 'd') ADVANCE(191);
       END_STATE();
     case 191:
-      if (lookahead == 'x') ADVANCE(8);
+      if (lookahead == 'd') ADVANCE(192);
       END_STATE();
     case 192:
-      if (lookahead == 'y') ADVANCE(12);
+      if (lookahead == 'i') ADVANCE(193);
       END_STATE();
     case 193:
-      ACCEPT_TOKEN(ts_builtin_sym_end);
+      if (lookahead == 'n') ADVANCE(194);
       END_STATE();
     case 194:
-      ACCEPT_TOKEN(sym_font);
-      if (lookahead == '-') ADVANCE(79);
+      if (lookahead == 'g') ADVANCE(195);
       END_STATE();
     case 195:
-      ACCEPT_TOKEN(sym_font_fallback);
+      if (lookahead == '-') ADVANCE(196);
       END_STATE();
     case 196:
-      ACCEPT_TOKEN(sym_time_block);
+      if (lookahead == 'x') ADVANCE(302);
+      if (lookahead == 'y') ADVANCE(303);
       END_STATE();
     case 197:
-      ACCEPT_TOKEN(sym_date_fmt);
+      if (lookahead == 'e') ADVANCE(198);
       END_STATE();
     case 198:
-      ACCEPT_TOKEN(sym_browser_path);
+      if (lookahead == 'a') ADVANCE(199);
       END_STATE();
     case 199:
-      ACCEPT_TOKEN(sym_browser);
-      if (lookahead == '-') ADVANCE(148);
+      if (lookahead == 'r') ADVANCE(200);
       END_STATE();
     case 200:
-      ACCEPT_TOKEN(sym_time_fmt);
+      if (lookahead == 'c') ADVANCE(201);
       END_STATE();
     case 201:
-      ACCEPT_TOKEN(sym_update_time_ntp);
+      if (lookahead == 'h') ADVANCE(202);
       END_STATE();
     case 202:
-      ACCEPT_TOKEN(sym_brightness_block);
+      if (lookahead == '-') ADVANCE(203);
       END_STATE();
     case 203:
-      ACCEPT_TOKEN(sym_battery_block);
+      if (lookahead == 'e') ADVANCE(204);
       END_STATE();
     case 204:
-      ACCEPT_TOKEN(sym_connman_block);
+      if (lookahead == 'n') ADVANCE(205);
       END_STATE();
     case 205:
-      ACCEPT_TOKEN(sym_media_block);
+      if (lookahead == 'g') ADVANCE(206);
       END_STATE();
     case 206:
-      ACCEPT_TOKEN(sym_wireplumber_block);
+      if (lookahead == 'i') ADVANCE(207);
       END_STATE();
     case 207:
-      ACCEPT_TOKEN(sym_wireplumber_max_volume);
+      if (lookahead == 'n') ADVANCE(208);
       END_STATE();
     case 208:
-      ACCEPT_TOKEN(sym_color_active);
+      if (lookahead == 'e') ADVANCE(283);
       END_STATE();
     case 209:
-      ACCEPT_TOKEN(sym_color_inactive);
+      if (lookahead == 'e') ADVANCE(210);
+      if (lookahead == 'i') ADVANCE(216);
+      if (lookahead == 'o') ADVANCE(232);
       END_STATE();
     case 210:
-      ACCEPT_TOKEN(sym_padding_x);
+      if (lookahead == 'r') ADVANCE(211);
       END_STATE();
     case 211:
-      ACCEPT_TOKEN(sym_padding_y);
+      if (lookahead == 'm') ADVANCE(212);
       END_STATE();
     case 212:
-      ACCEPT_TOKEN(sym_top_bar);
+      if (lookahead == 'i') ADVANCE(213);
       END_STATE();
     case 213:
-      ACCEPT_TOKEN(sym_time_servers);
+      if (lookahead == 'n') ADVANCE(214);
       END_STATE();
     case 214:
-      ACCEPT_TOKEN(sym_bar_show_time);
+      if (lookahead == 'a') ADVANCE(215);
       END_STATE();
     case 215:
+      if (lookahead == 'l') ADVANCE(284);
+      END_STATE();
+    case 216:
+      if (lookahead == 'm') ADVANCE(217);
+      END_STATE();
+    case 217:
+      if (lookahead == 'e') ADVANCE(218);
+      END_STATE();
+    case 218:
+      if (lookahead == '-') ADVANCE(219);
+      END_STATE();
+    case 219:
+      if (lookahead == 'b') ADVANCE(220);
+      if (lookahead == 'f') ADVANCE(224);
+      if (lookahead == 's') ADVANCE(226);
+      END_STATE();
+    case 220:
+      if (lookahead == 'l') ADVANCE(221);
+      END_STATE();
+    case 221:
+      if (lookahead == 'o') ADVANCE(222);
+      END_STATE();
+    case 222:
+      if (lookahead == 'c') ADVANCE(223);
+      END_STATE();
+    case 223:
+      if (lookahead == 'k') ADVANCE(279);
+      END_STATE();
+    case 224:
+      if (lookahead == 'm') ADVANCE(225);
+      END_STATE();
+    case 225:
+      if (lookahead == 't') ADVANCE(286);
+      END_STATE();
+    case 226:
+      if (lookahead == 'e') ADVANCE(227);
+      END_STATE();
+    case 227:
+      if (lookahead == 'r') ADVANCE(228);
+      END_STATE();
+    case 228:
+      if (lookahead == 'v') ADVANCE(229);
+      END_STATE();
+    case 229:
+      if (lookahead == 'e') ADVANCE(230);
+      END_STATE();
+    case 230:
+      if (lookahead == 'r') ADVANCE(231);
+      END_STATE();
+    case 231:
+      if (lookahead == 's') ADVANCE(305);
+      END_STATE();
+    case 232:
+      if (lookahead == 'p') ADVANCE(233);
+      END_STATE();
+    case 233:
+      if (lookahead == '-') ADVANCE(234);
+      END_STATE();
+    case 234:
+      if (lookahead == 'b') ADVANCE(235);
+      END_STATE();
+    case 235:
+      if (lookahead == 'a') ADVANCE(236);
+      END_STATE();
+    case 236:
+      if (lookahead == 'r') ADVANCE(304);
+      END_STATE();
+    case 237:
+      if (lookahead == 'p') ADVANCE(238);
+      END_STATE();
+    case 238:
+      if (lookahead == 'd') ADVANCE(239);
+      END_STATE();
+    case 239:
+      if (lookahead == 'a') ADVANCE(240);
+      END_STATE();
+    case 240:
+      if (lookahead == 't') ADVANCE(241);
+      END_STATE();
+    case 241:
+      if (lookahead == 'e') ADVANCE(242);
+      END_STATE();
+    case 242:
+      if (lookahead == '-') ADVANCE(243);
+      END_STATE();
+    case 243:
+      if (lookahead == 't') ADVANCE(244);
+      END_STATE();
+    case 244:
+      if (lookahead == 'i') ADVANCE(245);
+      END_STATE();
+    case 245:
+      if (lookahead == 'm') ADVANCE(246);
+      END_STATE();
+    case 246:
+      if (lookahead == 'e') ADVANCE(247);
+      END_STATE();
+    case 247:
+      if (lookahead == '-') ADVANCE(248);
+      END_STATE();
+    case 248:
+      if (lookahead == 'n') ADVANCE(249);
+      END_STATE();
+    case 249:
+      if (lookahead == 't') ADVANCE(250);
+      END_STATE();
+    case 250:
+      if (lookahead == 'p') ADVANCE(287);
+      END_STATE();
+    case 251:
+      if (lookahead == 'i') ADVANCE(252);
+      END_STATE();
+    case 252:
+      if (lookahead == 'r') ADVANCE(253);
+      END_STATE();
+    case 253:
+      if (lookahead == 'e') ADVANCE(254);
+      END_STATE();
+    case 254:
+      if (lookahead == 'p') ADVANCE(255);
+      END_STATE();
+    case 255:
+      if (lookahead == 'l') ADVANCE(256);
+      END_STATE();
+    case 256:
+      if (lookahead == 'u') ADVANCE(257);
+      END_STATE();
+    case 257:
+      if (lookahead == 'm') ADVANCE(258);
+      END_STATE();
+    case 258:
+      if (lookahead == 'b') ADVANCE(259);
+      END_STATE();
+    case 259:
+      if (lookahead == 'e') ADVANCE(260);
+      END_STATE();
+    case 260:
+      if (lookahead == 'r') ADVANCE(261);
+      END_STATE();
+    case 261:
+      if (lookahead == '-') ADVANCE(262);
+      END_STATE();
+    case 262:
+      if (lookahead == 'b') ADVANCE(263);
+      if (lookahead == 'm') ADVANCE(267);
+      END_STATE();
+    case 263:
+      if (lookahead == 'l') ADVANCE(264);
+      END_STATE();
+    case 264:
+      if (lookahead == 'o') ADVANCE(265);
+      END_STATE();
+    case 265:
+      if (lookahead == 'c') ADVANCE(266);
+      END_STATE();
+    case 266:
+      if (lookahead == 'k') ADVANCE(298);
+      END_STATE();
+    case 267:
+      if (lookahead == 'a') ADVANCE(268);
+      END_STATE();
+    case 268:
+      if (lookahead == 'x') ADVANCE(269);
+      END_STATE();
+    case 269:
+      if (lookahead == '-') ADVANCE(270);
+      END_STATE();
+    case 270:
+      if (lookahead == 'v') ADVANCE(271);
+      END_STATE();
+    case 271:
+      if (lookahead == 'o') ADVANCE(272);
+      END_STATE();
+    case 272:
+      if (lookahead == 'l') ADVANCE(273);
+      END_STATE();
+    case 273:
+      if (lookahead == 'u') ADVANCE(274);
+      END_STATE();
+    case 274:
+      if (lookahead == 'm') ADVANCE(275);
+      END_STATE();
+    case 275:
+      if (lookahead == 'e') ADVANCE(299);
+      END_STATE();
+    case 276:
+      ACCEPT_TOKEN(ts_builtin_sym_end);
+      END_STATE();
+    case 277:
+      ACCEPT_TOKEN(sym_font);
+      if (lookahead == '-') ADVANCE(154);
+      END_STATE();
+    case 278:
+      ACCEPT_TOKEN(sym_font_fallback);
+      END_STATE();
+    case 279:
+      ACCEPT_TOKEN(sym_time_block);
+      END_STATE();
+    case 280:
+      ACCEPT_TOKEN(sym_date_fmt);
+      END_STATE();
+    case 281:
+      ACCEPT_TOKEN(sym_browser_path);
+      END_STATE();
+    case 282:
+      ACCEPT_TOKEN(sym_browser);
+      if (lookahead == '-') ADVANCE(92);
+      END_STATE();
+    case 283:
+      ACCEPT_TOKEN(sym_search_engine);
+      END_STATE();
+    case 284:
+      ACCEPT_TOKEN(sym_terminal);
+      END_STATE();
+    case 285:
+      ACCEPT_TOKEN(sym_clipboard_exclude);
+      END_STATE();
+    case 286:
+      ACCEPT_TOKEN(sym_time_fmt);
+      END_STATE();
+    case 287:
+      ACCEPT_TOKEN(sym_update_time_ntp);
+      END_STATE();
+    case 288:
+      ACCEPT_TOKEN(sym_brightness_block);
+      END_STATE();
+    case 289:
+      ACCEPT_TOKEN(sym_battery_block);
+      END_STATE();
+    case 290:
+      ACCEPT_TOKEN(sym_battery_low);
+      END_STATE();
+    case 291:
+      ACCEPT_TOKEN(sym_battery_critical);
+      END_STATE();
+    case 292:
+      ACCEPT_TOKEN(sym_battery_hook);
+      END_STATE();
+    case 293:
+      ACCEPT_TOKEN(sym_battery_action);
+      END_STATE();
+    case 294:
+      ACCEPT_TOKEN(sym_connman_block);
+      END_STATE();
+    case 295:
+      ACCEPT_TOKEN(sym_network_throughput);
+      END_STATE();
+    case 296:
+      ACCEPT_TOKEN(sym_bluetooth_block);
+      END_STATE();
+    case 297:
+      ACCEPT_TOKEN(sym_media_block);
+      END_STATE();
+    case 298:
+      ACCEPT_TOKEN(sym_wireplumber_block);
+      END_STATE();
+    case 299:
+      ACCEPT_TOKEN(sym_wireplumber_max_volume);
+      END_STATE();
+    case 300:
+      ACCEPT_TOKEN(sym_color_active);
+      END_STATE();
+    case 301:
+      ACCEPT_TOKEN(sym_color_inactive);
+      END_STATE();
+    case 302:
+      ACCEPT_TOKEN(sym_padding_x);
+      END_STATE();
+    case 303:
+      ACCEPT_TOKEN(sym_padding_y);
+      END_STATE();
+    case 304:
+      ACCEPT_TOKEN(sym_top_bar);
+      END_STATE();
+    case 305:
+      ACCEPT_TOKEN(sym_time_servers);
+      END_STATE();
+    case 306:
+      ACCEPT_TOKEN(sym_bar_show_time);
+      END_STATE();
+    case 307:
       ACCEPT_TOKEN(sym_divider);
       END_STATE();
+    case 308:
+      ACCEPT_TOKEN(sym_blocks);
+      END_STATE();
     default:
       return false;
   }
@@ -904,11 +1263,20 @@ static const uint16_t ts_parse_table[LARGE_STATE_COUNT][SYMBOL_COUNT] = {
     [sym_date_fmt] = ACTIONS(1),
     [sym_browser_path] = ACTIONS(1),
     [sym_browser] = ACTIONS(1),
+    [sym_search_engine] = ACTIONS(1),
+    [sym_terminal] = ACTIONS(1),
+    [sym_clipboard_exclude] = ACTIONS(1),
     [sym_time_fmt] = ACTIONS(1),
     [sym_update_time_ntp] = ACTIONS(1),
     [sym_brightness_block] = ACTIONS(1),
     [sym_battery_block] = ACTIONS(1),
+    [sym_battery_low] = ACTIONS(1),
+    [sym_battery_critical] = ACTIONS(1),
+    [sym_battery_hook] = ACTIONS(1),
+    [sym_battery_action] = ACTIONS(1),
     [sym_connman_block] = ACTIONS(1),
+    [sym_network_throughput] = ACTIONS(1),
+    [sym_bluetooth_block] = ACTIONS(1),
     [sym_media_block] = ACTIONS(1),
     [sym_wireplumber_block] = ACTIONS(1),
     [sym_wireplumber_max_volume] = ACTIONS(1),
@@ -920,6 +1288,7 @@ static const uint16_t ts_parse_table[LARGE_STATE_COUNT][SYMBOL_COUNT] = {
     [sym_time_servers] = ACTIONS(1),
     [sym_bar_show_time] = ACTIONS(1),
     [sym_divider] = ACTIONS(1),
+    [sym_blocks] = ACTIONS(1),
   },
   [1] = {
     [sym_source_file] = STATE(3),
@@ -929,11 +1298,20 @@ static const uint16_t ts_parse_table[LARGE_STATE_COUNT][SYMBOL_COUNT] = {
     [sym_date_fmt] = ACTIONS(5),
     [sym_browser_path] = ACTIONS(5),
     [sym_browser] = ACTIONS(3),
+    [sym_search_engine] = ACTIONS(5),
+    [sym_terminal] = ACTIONS(5),
+    [sym_clipboard_exclude] = ACTIONS(5),
     [sym_time_fmt] = ACTIONS(5),
     [sym_update_time_ntp] = ACTIONS(5),
     [sym_brightness_block] = ACTIONS(5),
     [sym_battery_block] = ACTIONS(5),
+    [sym_battery_low] = ACTIONS(5),
+    [sym_battery_critical] = ACTIONS(5),
+    [sym_battery_hook] = ACTIONS(5),
+    [sym_battery_action] = ACTIONS(5),
     [sym_connman_block] = ACTIONS(5),
+    [sym_network_throughput] = ACTIONS(5),
+    [sym_bluetooth_block] = ACTIONS(5),
     [sym_media_block] = ACTIONS(5),
     [sym_wireplumber_block] = ACTIONS(5),
     [sym_wireplumber_max_volume] = ACTIONS(5),
@@ -945,6 +1323,7 @@ static const uint16_t ts_parse_table[LARGE_STATE_COUNT][SYMBOL_COUNT] = {
     [sym_time_servers] = ACTIONS(5),
     [sym_bar_show_time] = ACTIONS(5),
     [sym_divider] = ACTIONS(5),
+    [sym_blocks] = ACTIONS(5),
   },
 };
 
@@ -137,6 +137,26 @@ static gboolean on_set_volume(WpMonitorOrgWireplumberDefaultNode *interface,
   return TRUE;
 }
 
+static gboolean on_set_mute(WpMonitorOrgWireplumberDefaultNode *interface,
+                            GDBusMethodInvocation *invocation,
+                            const gboolean in, gpointer data) {
+  print_context *context = data;
+  g_auto(GVariantBuilder) builder =
+      G_VARIANT_BUILDER_INIT(G_VARIANT_TYPE_VARDICT);
+  gboolean res = FALSE;
+
+  g_variant_builder_add(&builder, "{sv}", "mute", g_variant_new_boolean(in));
+  GVariant *variant = g_variant_builder_end(&builder);
+
+  g_signal_emit_by_name(context->self->mixer_api, "set-volume",
+                        context->default_node, variant, &res);
+
+  wp_monitor_org_wireplumber_default_node_complete_set_mute(context->dbus,
+                                                            invocation, res);
+
+  return TRUE;
+}
+
 static void on_name_acquired(GDBusConnection *connection, const gchar *name,
                              gpointer user_data) {
   g_autoptr(GError) error = NULL;
@@ -153,6 +173,8 @@ static void on_name_acquired(GDBusConnection *connection, const gchar *name,
                            (GCallback)onDefaultNodesApiChanged, context);
   g_signal_connect(context->dbus, "handle-set-volume", (GCallback)on_set_volume,
                    context);
+  g_signal_connect(context->dbus, "handle-set-mute", (GCallback)on_set_mute,
+                   context);
 
   if (!g_dbus_interface_skeleton_export(
           G_DBUS_INTERFACE_SKELETON(context->dbus), connection, "/", &error)) {